@@ -2,6 +2,8 @@ use std::env;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Default Bitcoin Core tag, overridable with the `BITCOIN_CORE_VERSION`
+/// environment variable.
 const BITCOIN_CORE_VERSION: &str = "v30.2";
 const BITCOIN_CORE_REPO: &str = "https://github.com/bitcoin/bitcoin.git";
 
@@ -14,19 +16,30 @@ fn main() {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
 
-    let bitcoin_src = get_bitcoin_source(&manifest_dir, &out_dir);
+    println!("cargo:rerun-if-env-changed=BITCOIN_CORE_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=BITCOIN_CORE_VERSION");
 
-    let dst = cmake::Config::new(&manifest_dir)
-        .define("CMAKE_BUILD_TYPE", "Release")
-        .define("BUILD_SHARED_LIBS", "OFF")
-        .define("BITCOIN_SRC_DIR", bitcoin_src.to_str().unwrap())
-        .build();
+    let (lib_dirs, include_dir) = if env::var("CARGO_FEATURE_SYSTEM_BITCOIN").is_ok() {
+        use_prebuilt_libs()
+    } else {
+        let version = bitcoin_core_version();
+        let bitcoin_src = get_bitcoin_source(&manifest_dir, &out_dir, &version);
 
-    println!("cargo:rustc-link-search=native={}/lib", dst.display());
-    println!(
-        "cargo:rustc-link-search=native={}/build/secp256k1/lib",
-        dst.display()
-    );
+        let dst = cmake::Config::new(&manifest_dir)
+            .define("CMAKE_BUILD_TYPE", "Release")
+            .define("BUILD_SHARED_LIBS", "OFF")
+            .define("BITCOIN_SRC_DIR", bitcoin_src.to_str().unwrap())
+            .build();
+
+        (
+            vec![dst.join("lib"), dst.join("build/secp256k1/lib")],
+            Some(bitcoin_src),
+        )
+    };
+
+    for dir in &lib_dirs {
+        println!("cargo:rustc-link-search=native={}", dir.display());
+    }
     println!("cargo:rustc-link-lib=static=miniscript_wrapper");
     println!("cargo:rustc-link-lib=static=secp256k1");
 
@@ -48,7 +61,7 @@ fn main() {
                 .to_str()
                 .unwrap(),
         )
-        .clang_arg(format!("-I{}", bitcoin_src.display()))
+        .clang_args(include_dir.map(|dir| format!("-I{}", dir.display())))
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         // Miniscript types
         .allowlist_type("MiniscriptContext")
@@ -58,17 +71,25 @@ fn main() {
         .allowlist_type("SatisfierCallbacks")
         .allowlist_type("SatisfactionResult")
         .allowlist_type("MiniscriptAvailability")
+        .allowlist_type("MiniscriptFragment")
+        .allowlist_type("MiniscriptTreeNode")
+        .allowlist_type("MiniscriptContextLimits")
+        .allowlist_type("MiniscriptBranchTimelock")
         // Descriptor types
         .allowlist_type("DescriptorNode")
+        .allowlist_type("DescriptorCacheNode")
         .allowlist_type("DescriptorResult")
         .allowlist_type("DescriptorNetwork")
+        .allowlist_type("DescriptorOutputType")
         .allowlist_type("ExpandedScript")
         .allowlist_type("PubKeyInfo")
         .allowlist_function("descriptor_.*")
         // Enums
         .rustified_enum("MiniscriptContext")
         .rustified_enum("MiniscriptAvailability")
+        .rustified_enum("MiniscriptFragment")
         .rustified_enum("DescriptorNetwork")
+        .rustified_enum("DescriptorOutputType")
         .derive_debug(true)
         .generate()
         .expect("Unable to generate bindings");
@@ -85,7 +106,67 @@ fn main() {
     println!("cargo:rerun-if-changed=CMakeLists.txt");
 }
 
-fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path) -> PathBuf {
+/// Static libraries that must be present in a prebuilt library directory.
+const PREBUILT_LIBS: [&str; 2] = ["miniscript_wrapper", "secp256k1"];
+
+/// Locate prebuilt static libraries for the `system-bitcoin` feature.
+///
+/// `BITCOIN_CORE_LIB_DIR` must point to a directory laid out as:
+///
+/// ```text
+/// $BITCOIN_CORE_LIB_DIR/
+///   lib/libminiscript_wrapper.a   (miniscript_wrapper.lib on MSVC)
+///   lib/libsecp256k1.a            (secp256k1.lib on MSVC)
+///   include/                      (optional, Bitcoin Core headers for bindgen)
+/// ```
+///
+/// Returns the library search path and the include directory, if any.
+fn use_prebuilt_libs() -> (Vec<PathBuf>, Option<PathBuf>) {
+    let Ok(root) = env::var("BITCOIN_CORE_LIB_DIR") else {
+        panic!(
+            "The system-bitcoin feature requires BITCOIN_CORE_LIB_DIR to point to a directory \
+            containing lib/libminiscript_wrapper.a and lib/libsecp256k1.a"
+        );
+    };
+    let root = PathBuf::from(root);
+    let lib_dir = root.join("lib");
+
+    let missing: Vec<String> = PREBUILT_LIBS
+        .iter()
+        .filter(|name| {
+            !lib_dir.join(format!("lib{name}.a")).exists()
+                && !lib_dir.join(format!("{name}.lib")).exists()
+        })
+        .map(|name| format!("lib{name}.a"))
+        .collect();
+    assert!(
+        missing.is_empty(),
+        "BITCOIN_CORE_LIB_DIR={} is missing {} in {}",
+        root.display(),
+        missing.join(", "),
+        lib_dir.display()
+    );
+
+    println!(
+        "cargo:warning=Using prebuilt libraries from BITCOIN_CORE_LIB_DIR={}",
+        root.display()
+    );
+
+    let include_dir = root.join("include");
+    (vec![lib_dir], include_dir.is_dir().then_some(include_dir))
+}
+
+/// Get the Bitcoin Core tag to build: `BITCOIN_CORE_VERSION` if set, else
+/// the default.
+fn bitcoin_core_version() -> String {
+    env::var("BITCOIN_CORE_VERSION")
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| BITCOIN_CORE_VERSION.to_string())
+}
+
+fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path, version: &str) -> PathBuf {
     let vendor_src = manifest_dir.join("vendor/bitcoin/src");
     if vendor_src.join("script/miniscript.h").exists() {
         println!("cargo:warning=Using Bitcoin Core from vendor/bitcoin");
@@ -100,7 +181,8 @@ fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path) -> PathBuf {
         }
     }
 
-    let bitcoin_dir = out_dir.join("bitcoin");
+    // One checkout per tag, so changing the version never reuses a stale clone
+    let bitcoin_dir = out_dir.join(format!("bitcoin-{version}"));
     let bitcoin_src = bitcoin_dir.join("src");
 
     if bitcoin_src.join("script/miniscript.h").exists() {
@@ -111,7 +193,7 @@ fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path) -> PathBuf {
         return bitcoin_src;
     }
 
-    println!("cargo:warning=Downloading Bitcoin Core {BITCOIN_CORE_VERSION} ...");
+    println!("cargo:warning=Downloading Bitcoin Core {version} ...");
 
     let status = Command::new("git")
         .args([
@@ -119,7 +201,7 @@ fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path) -> PathBuf {
             "--depth",
             "1",
             "--branch",
-            BITCOIN_CORE_VERSION,
+            version,
             "--single-branch",
             BITCOIN_CORE_REPO,
             bitcoin_dir.to_str().unwrap(),
@@ -138,7 +220,7 @@ fn get_bitcoin_source(manifest_dir: &Path, out_dir: &Path) -> PathBuf {
         "Bitcoin Core downloaded but miniscript.h not found!"
     );
 
-    println!("cargo:warning=Bitcoin Core {BITCOIN_CORE_VERSION} downloaded successfully");
+    println!("cargo:warning=Bitcoin Core {version} downloaded successfully");
     bitcoin_src
 }
 
@@ -162,6 +244,38 @@ pub enum MiniscriptAvailability {
     MINISCRIPT_AVAILABILITY_MAYBE = 2,
 }
 
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum MiniscriptFragment {
+    MINISCRIPT_FRAGMENT_JUST_0 = 0,
+    MINISCRIPT_FRAGMENT_JUST_1 = 1,
+    MINISCRIPT_FRAGMENT_PK_K = 2,
+    MINISCRIPT_FRAGMENT_PK_H = 3,
+    MINISCRIPT_FRAGMENT_OLDER = 4,
+    MINISCRIPT_FRAGMENT_AFTER = 5,
+    MINISCRIPT_FRAGMENT_SHA256 = 6,
+    MINISCRIPT_FRAGMENT_HASH256 = 7,
+    MINISCRIPT_FRAGMENT_RIPEMD160 = 8,
+    MINISCRIPT_FRAGMENT_HASH160 = 9,
+    MINISCRIPT_FRAGMENT_WRAP_A = 10,
+    MINISCRIPT_FRAGMENT_WRAP_S = 11,
+    MINISCRIPT_FRAGMENT_WRAP_C = 12,
+    MINISCRIPT_FRAGMENT_WRAP_D = 13,
+    MINISCRIPT_FRAGMENT_WRAP_V = 14,
+    MINISCRIPT_FRAGMENT_WRAP_J = 15,
+    MINISCRIPT_FRAGMENT_WRAP_N = 16,
+    MINISCRIPT_FRAGMENT_AND_V = 17,
+    MINISCRIPT_FRAGMENT_AND_B = 18,
+    MINISCRIPT_FRAGMENT_OR_B = 19,
+    MINISCRIPT_FRAGMENT_OR_C = 20,
+    MINISCRIPT_FRAGMENT_OR_D = 21,
+    MINISCRIPT_FRAGMENT_OR_I = 22,
+    MINISCRIPT_FRAGMENT_ANDOR = 23,
+    MINISCRIPT_FRAGMENT_THRESH = 24,
+    MINISCRIPT_FRAGMENT_MULTI = 25,
+    MINISCRIPT_FRAGMENT_MULTI_A = 26,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct MiniscriptNode {
@@ -173,6 +287,7 @@ pub struct MiniscriptNode {
 pub struct MiniscriptResult {
     pub success: bool,
     pub error_message: *mut ::std::os::raw::c_char,
+    pub error_code: ::std::os::raw::c_int,
 }
 
 /// Callback function types for the Satisfier
@@ -227,6 +342,36 @@ pub struct SatisfactionResult {
     pub error_message: *mut ::std::os::raw::c_char,
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MiniscriptTreeNode {
+    pub fragment: MiniscriptFragment,
+    pub k: u32,
+    pub child_count: usize,
+    pub keys: *mut *mut ::std::os::raw::c_char,
+    pub key_count: usize,
+    pub data: *mut u8,
+    pub data_len: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MiniscriptContextLimits {
+    pub max_script_size: u32,
+    pub max_ops: u32,
+    pub max_witness_items: u32,
+    pub max_exec_stack_size: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct MiniscriptBranchTimelock {
+    pub after_height: u32,
+    pub after_time: u32,
+    pub older_blocks: u32,
+    pub older_time: u32,
+}
+
 unsafe extern "C" {
     pub fn miniscript_from_string(
         input: *const ::std::os::raw::c_char,
@@ -234,7 +379,17 @@ unsafe extern "C" {
         out_node: *mut *mut MiniscriptNode,
     ) -> MiniscriptResult;
 
+    pub fn miniscript_from_string_permissive(
+        input: *const ::std::os::raw::c_char,
+        ctx: MiniscriptContext,
+        out_node: *mut *mut MiniscriptNode,
+    ) -> MiniscriptResult;
+
     pub fn miniscript_to_string(node: *const MiniscriptNode) -> *mut ::std::os::raw::c_char;
+    pub fn miniscript_to_string_ex(
+        node: *const MiniscriptNode,
+        expand_sugar: bool,
+    ) -> *mut ::std::os::raw::c_char;
 
     pub fn miniscript_to_script(
         node: *const MiniscriptNode,
@@ -253,9 +408,46 @@ unsafe extern "C" {
         out_size: *mut usize,
     ) -> bool;
 
+    pub fn miniscript_satisfaction_size_with_sig(
+        node: *const MiniscriptNode,
+        sig_len: usize,
+        out_size: *mut usize,
+    ) -> bool;
     pub fn miniscript_is_non_malleable(node: *const MiniscriptNode) -> bool;
     pub fn miniscript_needs_signature(node: *const MiniscriptNode) -> bool;
     pub fn miniscript_has_timelock_mix(node: *const MiniscriptNode) -> bool;
+    pub fn miniscript_timelock_mix_detail(
+        node: *const MiniscriptNode,
+        out_after: *mut bool,
+        out_older: *mut bool,
+    ) -> bool;
+    pub fn miniscript_can_satisfy_with_keys(
+        node: *const MiniscriptNode,
+        keys: *const *const u8,
+        key_lens: *const usize,
+        key_count: usize,
+    ) -> bool;
+    pub fn miniscript_spend_path_count(node: *const MiniscriptNode, out_count: *mut usize) -> bool;
+    pub fn miniscript_required_signers(
+        node: *const MiniscriptNode,
+        max_paths: usize,
+        out_keys: *mut *mut *mut ::std::os::raw::c_char,
+        out_set_sizes: *mut *mut usize,
+        out_set_count: *mut usize,
+        out_truncated: *mut bool,
+    ) -> bool;
+    pub fn miniscript_free_signer_sets(
+        keys: *mut *mut ::std::os::raw::c_char,
+        set_sizes: *mut usize,
+        set_count: usize,
+    );
+    pub fn miniscript_all_branch_timelocks(
+        node: *const MiniscriptNode,
+        max_paths: usize,
+        out_locks: *mut *mut MiniscriptBranchTimelock,
+        out_count: *mut usize,
+    ) -> bool;
+    pub fn miniscript_free_branch_timelocks(locks: *mut MiniscriptBranchTimelock);
     pub fn miniscript_is_valid_top_level(node: *const MiniscriptNode) -> bool;
     pub fn miniscript_check_ops_limit(node: *const MiniscriptNode) -> bool;
     pub fn miniscript_check_stack_size(node: *const MiniscriptNode) -> bool;
@@ -265,6 +457,18 @@ unsafe extern "C" {
     pub fn miniscript_get_stack_size(node: *const MiniscriptNode, out_size: *mut u32) -> bool;
     pub fn miniscript_get_exec_stack_size(node: *const MiniscriptNode, out_size: *mut u32) -> bool;
     pub fn miniscript_get_script_size(node: *const MiniscriptNode, out_size: *mut usize) -> bool;
+    pub fn miniscript_max_script_size(ctx: MiniscriptContext) -> u32;
+    pub fn miniscript_context_limits(
+        ctx: MiniscriptContext,
+        out_limits: *mut MiniscriptContextLimits,
+    ) -> bool;
+    pub fn miniscript_key_bytes(
+        name: *const ::std::os::raw::c_char,
+        name_len: usize,
+        ctx: MiniscriptContext,
+        out_key: *mut u8,
+        out_len: *mut usize,
+    ) -> bool;
 
     pub fn miniscript_from_script(
         script: *const u8,
@@ -273,16 +477,53 @@ unsafe extern "C" {
         out_node: *mut *mut MiniscriptNode,
     ) -> MiniscriptResult;
 
+    pub fn miniscript_subexpressions(
+        node: *const MiniscriptNode,
+        out_nodes: *mut *mut *mut MiniscriptNode,
+        out_count: *mut usize,
+    ) -> bool;
+    pub fn miniscript_free_node_array(nodes: *mut *mut MiniscriptNode);
+    pub fn miniscript_subtree_strings(
+        node: *const MiniscriptNode,
+        positions: *const usize,
+        count: usize,
+        out_strings: *mut *mut *mut ::std::os::raw::c_char,
+    ) -> bool;
+    pub fn miniscript_free_strings(strings: *mut *mut ::std::os::raw::c_char, count: usize);
+
     pub fn miniscript_find_insane_sub(node: *const MiniscriptNode) -> *mut MiniscriptNode;
     pub fn miniscript_valid_satisfactions(node: *const MiniscriptNode) -> bool;
     pub fn miniscript_get_static_ops(node: *const MiniscriptNode, out_ops: *mut u32) -> bool;
 
+    pub fn miniscript_get_tree(
+        node: *const MiniscriptNode,
+        out_nodes: *mut *mut MiniscriptTreeNode,
+        out_count: *mut usize,
+    ) -> bool;
+
+    pub fn miniscript_free_tree(nodes: *mut MiniscriptTreeNode, count: usize);
+
     pub fn miniscript_satisfy(
         node: *const MiniscriptNode,
         callbacks: *const SatisfierCallbacks,
         nonmalleable: bool,
     ) -> SatisfactionResult;
 
+    pub fn miniscript_satisfy_branch(
+        node: *const MiniscriptNode,
+        branch: usize,
+        callbacks: *const SatisfierCallbacks,
+        nonmalleable: bool,
+    ) -> SatisfactionResult;
+
+    pub fn miniscript_witness_executes(
+        node: *const MiniscriptNode,
+        elems: *const *const u8,
+        lens: *const usize,
+        count: usize,
+        out_valid: *mut bool,
+    ) -> bool;
+
     pub fn miniscript_satisfaction_result_free(result: *mut SatisfactionResult);
 
     pub fn miniscript_node_free(node: *mut MiniscriptNode);
@@ -292,6 +533,10 @@ unsafe extern "C" {
     pub fn miniscript_free_bytes(bytes: *mut u8);
 
     pub fn miniscript_version() -> *const ::std::os::raw::c_char;
+
+    pub fn miniscript_bitcoin_core_version() -> *const ::std::os::raw::c_char;
+
+    pub fn miniscript_bitcoin_core_commit() -> *const ::std::os::raw::c_char;
 }
 
 // Descriptor types for docs.rs stub bindings
@@ -305,17 +550,42 @@ pub enum DescriptorNetwork {
     DESCRIPTOR_NETWORK_REGTEST = 3,
 }
 
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum DescriptorOutputType {
+    DESCRIPTOR_OUTPUT_LEGACY = 0,
+    DESCRIPTOR_OUTPUT_P2SH_SEGWIT = 1,
+    DESCRIPTOR_OUTPUT_BECH32 = 2,
+    DESCRIPTOR_OUTPUT_BECH32M = 3,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct DescriptorNode {
     _unused: [u8; 0],
 }
 
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct DescriptorCacheNode {
+    _unused: [u8; 0],
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 pub struct DescriptorResult {
     pub success: bool,
     pub error_message: *mut ::std::os::raw::c_char,
+    pub error_code: ::std::os::raw::c_int,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct PubKeyInfo {
+    pub pubkey: *mut u8,
+    pub pubkey_len: usize,
+    pub origin_fingerprint: *mut ::std::os::raw::c_char,
+    pub origin_path: *mut ::std::os::raw::c_char,
 }
 
 unsafe extern "C" {
@@ -325,11 +595,22 @@ unsafe extern "C" {
         out_node: *mut *mut DescriptorNode,
     ) -> DescriptorResult;
 
+    pub fn descriptor_parse_lenient(
+        descriptor_str: *const ::std::os::raw::c_char,
+        network: DescriptorNetwork,
+        out_node: *mut *mut DescriptorNode,
+    ) -> DescriptorResult;
+
     pub fn descriptor_is_range(node: *const DescriptorNode) -> bool;
 
     pub fn descriptor_is_solvable(node: *const DescriptorNode) -> bool;
 
+    pub fn descriptor_is_lenient(node: *const DescriptorNode) -> bool;
+
+    pub fn descriptor_requires_private_key_to_expand(node: *const DescriptorNode) -> bool;
+
     pub fn descriptor_to_string(node: *const DescriptorNode) -> *mut ::std::os::raw::c_char;
+    pub fn descriptor_checksum(node: *const DescriptorNode) -> *mut ::std::os::raw::c_char;
 
     pub fn descriptor_expand(
         node: *const DescriptorNode,
@@ -341,9 +622,19 @@ unsafe extern "C" {
     pub fn descriptor_get_address(
         node: *const DescriptorNode,
         pos: ::std::os::raw::c_int,
-        network: DescriptorNetwork,
     ) -> *mut ::std::os::raw::c_char;
 
+    pub fn descriptor_derive(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_script: *mut *mut u8,
+        out_script_len: *mut usize,
+        out_address: *mut *mut ::std::os::raw::c_char,
+        out_pubkeys: *mut *mut *mut u8,
+        out_lens: *mut *mut usize,
+        out_count: *mut usize,
+    ) -> bool;
+
     pub fn descriptor_get_pubkeys(
         node: *const DescriptorNode,
         pos: ::std::os::raw::c_int,
@@ -352,6 +643,24 @@ unsafe extern "C" {
         out_count: *mut usize,
     ) -> bool;
 
+    pub fn descriptor_get_pubkeys_range(
+        node: *const DescriptorNode,
+        start: ::std::os::raw::c_int,
+        count: usize,
+        out_pubkeys: *mut *mut *mut u8,
+        out_lens: *mut *mut usize,
+        out_total: *mut usize,
+        out_counts: *mut *mut i64,
+    ) -> bool;
+
+    pub fn descriptor_get_multisig_pubkeys(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_pubkeys: *mut *mut *mut u8,
+        out_lens: *mut *mut usize,
+        out_count: *mut usize,
+    ) -> bool;
+
     pub fn descriptor_get_script_size(
         node: *const DescriptorNode,
         out_size: *mut i64,
@@ -363,6 +672,71 @@ unsafe extern "C" {
         out_weight: *mut i64,
     ) -> bool;
 
+    pub fn descriptor_get_max_satisfaction_elems(
+        node: *const DescriptorNode,
+        out_elems: *mut i64,
+    ) -> bool;
+
+    pub fn descriptor_get_output_type(
+        node: *const DescriptorNode,
+        out_type: *mut DescriptorOutputType,
+    ) -> bool;
+
+    pub fn descriptor_get_taproot_output_key(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_key: *mut u8,
+    ) -> bool;
+
+    pub fn descriptor_get_taproot_merkle_root(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_root: *mut u8,
+    ) -> bool;
+
+    pub fn descriptor_get_origin_fingerprints(
+        node: *const DescriptorNode,
+        out_fingerprints: *mut *mut u8,
+        out_count: *mut usize,
+    ) -> bool;
+
+    pub fn descriptor_get_signing_requirements(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_infos: *mut *mut PubKeyInfo,
+        out_count: *mut usize,
+    ) -> bool;
+
+    pub fn descriptor_get_spend_scripts(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_redeem: *mut *mut u8,
+        out_redeem_len: *mut usize,
+        out_witness: *mut *mut u8,
+        out_witness_len: *mut usize,
+    ) -> bool;
+
+    pub fn descriptor_get_tap_leaves(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        out_scripts: *mut *mut *mut u8,
+        out_lens: *mut *mut usize,
+        out_versions: *mut *mut u8,
+        out_count: *mut usize,
+    ) -> bool;
+
+    pub fn descriptor_cache_new() -> *mut DescriptorCacheNode;
+
+    pub fn descriptor_expand_cached(
+        node: *const DescriptorNode,
+        pos: ::std::os::raw::c_int,
+        cache: *mut DescriptorCacheNode,
+        out_script: *mut *mut u8,
+        out_len: *mut usize,
+    ) -> bool;
+
+    pub fn descriptor_cache_free(cache: *mut DescriptorCacheNode);
+
     pub fn descriptor_get_checksum(
         descriptor_str: *const ::std::os::raw::c_char,
     ) -> *mut ::std::os::raw::c_char;
@@ -375,6 +749,22 @@ unsafe extern "C" {
 
     pub fn descriptor_free_pubkeys(pubkeys: *mut *mut u8, lens: *mut usize, count: usize);
 
+    pub fn descriptor_free_tap_leaves(
+        scripts: *mut *mut u8,
+        lens: *mut usize,
+        versions: *mut u8,
+        count: usize,
+    );
+
+    pub fn descriptor_free_pubkeys_range(
+        pubkeys: *mut *mut u8,
+        lens: *mut usize,
+        total: usize,
+        counts: *mut i64,
+    );
+
+    pub fn descriptor_free_pubkey_infos(infos: *mut PubKeyInfo, count: usize);
+
     pub fn descriptor_version() -> *const ::std::os::raw::c_char;
 }
 "#;
@@ -1,9 +1,14 @@
 #include "descriptor_wrapper.h"
+#include "miniscript_wrapper.h"
 
+#include <algorithm>
 #include <cstring>
 #include <memory>
+#include <optional>
 #include <string>
+#include <string_view>
 #include <vector>
+#include <map>
 #include <set>
 #include <mutex>
 
@@ -11,6 +16,7 @@
 #include <script/descriptor.h>
 #include <script/script.h>
 #include <script/signingprovider.h>
+#include <script/solver.h>
 #include <key_io.h>
 #include <pubkey.h>
 #include <key.h>
@@ -23,9 +29,43 @@ static const char* DESCRIPTOR_VERSION_STRING = "0.1.0";
 struct DescriptorNode {
     std::unique_ptr<Descriptor> descriptor;
     FlatSigningProvider provider;
+    // The network the descriptor was parsed for, used to encode its keys
+    // and addresses
+    DescriptorNetwork network;
+    // Hash of the descriptor string, computed once at parse time to tell
+    // descriptors apart
+    uint256 id;
+    // Whether the checksum and key origins had to be dropped to parse it
+    bool lenient;
 
-    DescriptorNode(std::unique_ptr<Descriptor>&& desc, FlatSigningProvider&& prov)
-        : descriptor(std::move(desc)), provider(std::move(prov)) {}
+    DescriptorNode(std::unique_ptr<Descriptor>&& desc, FlatSigningProvider&& prov, DescriptorNetwork net,
+                   const uint256& id, bool lenient)
+        : descriptor(std::move(desc)), provider(std::move(prov)), network(net), id(id), lenient(lenient) {}
+};
+
+// Wrapper struct to hold a derivation cache and the id of the descriptor it
+// belongs to. Core keys cached entries by key position, so entries are only
+// meaningful for the descriptor that produced them.
+struct DescriptorCacheNode {
+    DescriptorCache cache;
+    std::optional<uint256> descriptor;
+};
+
+// Forward declarations from stubs.cpp for thread-safe chain parameter selection
+void SelectParams(int network);
+std::mutex& GetParamsMutex();
+
+// Holds the chain parameters mutex with a network selected. Core parses and
+// encodes extended keys and addresses with the global chain parameters, so
+// every such call must be made while holding one of these.
+class NetworkParamsLock {
+public:
+    explicit NetworkParamsLock(DescriptorNetwork network) : m_lock(GetParamsMutex()) {
+        SelectParams(static_cast<int>(network));
+    }
+
+private:
+    std::lock_guard<std::mutex> m_lock;
 };
 
 static char* strdup_safe(const char* str) {
@@ -42,44 +82,239 @@ static char* strdup_safe(const std::string& str) {
     return strdup_safe(str.c_str());
 }
 
-// Forward declarations from stubs.cpp for thread-safe chain parameter selection
-void SelectParams(int network);
-std::mutex& GetParamsMutex();
+// Classify a descriptor that Bitcoin Core failed to parse. Core only reports
+// failures as prose, so every failure is a syntax error except a checksum
+// that does not match the descriptor, which is checked here directly.
+static int DescriptorErrorCode(const std::string& descriptor) {
+    size_t hash = descriptor.find('#');
+    if (hash != std::string::npos) {
+        // An empty checksum means the descriptor has invalid characters
+        std::string checksum = GetDescriptorChecksum(descriptor.substr(0, hash));
+        if (!checksum.empty() && descriptor.substr(hash + 1) != checksum) {
+            return MINISCRIPT_ERROR_CHECKSUM;
+        }
+    }
+    return MINISCRIPT_ERROR_SYNTAX;
+}
 
-extern "C" {
+// Collect the public keys of a descriptor expanded at a position.
+// Expansion only performs public key operations (BIP32 public derivation,
+// key parsing and tweaking), which Bitcoin Core runs against the built-in
+// secp256k1_context_static. No secp256k1 context is created per call.
+static void CollectPubKeys(const DescriptorNode* node, const FlatSigningProvider& out_provider,
+                           std::set<CPubKey>& pubkeys) {
+    // Get all pubkeys from the provider
+    std::set<CExtPubKey> ext_pubkeys;
+    node->descriptor->GetPubKeys(pubkeys, ext_pubkeys);
 
-/**
- * Parse a descriptor string with the specified network context.
- *
- * This function is thread-safe: it acquires a mutex to ensure that the global
- * chain parameters remain consistent throughout the entire parse operation.
- * This prevents race conditions when multiple threads parse descriptors with
- * different network contexts (e.g., one parsing xpub on mainnet while another
- * parses tpub on testnet).
- *
- * @param descriptor_str The descriptor string to parse (e.g., "wpkh(tpub...)")
- * @param network The network context for key validation and address encoding
- * @param out_node Output pointer for the parsed descriptor node
- * @return Result indicating success or failure with error message
- */
-DescriptorResult descriptor_parse_with_network(const char* descriptor_str, DescriptorNetwork network, DescriptorNode** out_node) {
-    DescriptorResult result = {false, nullptr};
+    // Also get pubkeys from the expanded provider
+    for (const auto& [keyid, pubkey] : out_provider.pubkeys) {
+        pubkeys.insert(pubkey);
+    }
+}
+
+static bool CollectPubKeys(const DescriptorNode* node, int pos, std::set<CPubKey>& pubkeys) {
+    std::vector<CScript> scripts;
+    FlatSigningProvider out_provider;
+    DescriptorCache cache;
+
+    if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+        return false;
+    }
+
+    CollectPubKeys(node, out_provider, pubkeys);
+    return true;
+}
+
+// Encode an expanded script of the descriptor as an address
+static std::optional<std::string> ScriptAddress(const DescriptorNode* node, const CScript& script) {
+    // Get the output type to determine address format
+    auto output_type = node->descriptor->GetOutputType();
+    if (!output_type) {
+        return std::nullopt;
+    }
+
+    CTxDestination dest;
+
+    // Extract destination from script
+    if (!ExtractDestination(script, dest)) {
+        // For P2WSH and other complex scripts, we need to handle differently
+        // Try to create a witness script hash address
+        if (script.IsPayToWitnessScriptHash()) {
+            // Extract the witness program
+            std::vector<unsigned char> witprog;
+            int version;
+            if (script.IsWitnessProgram(version, witprog) && version == 0 && witprog.size() == 32) {
+                WitnessV0ScriptHash hash;
+                std::copy(witprog.begin(), witprog.end(), hash.begin());
+                dest = hash;
+            } else {
+                return std::nullopt;
+            }
+        } else {
+            return std::nullopt;
+        }
+    }
+
+    // Encode for the descriptor's network
+    NetworkParamsLock lock(node->network);
+    return EncodeDestination(dest);
+}
+
+// Copy a set of pubkeys into malloc'd arrays, freed with descriptor_free_pubkeys()
+static bool ExportPubKeys(const std::set<CPubKey>& pubkeys, uint8_t*** out_pubkeys,
+                          size_t** out_lens, size_t* out_count) {
+    if (pubkeys.empty()) {
+        *out_count = 0;
+        *out_pubkeys = nullptr;
+        *out_lens = nullptr;
+        return true;
+    }
+
+    *out_count = pubkeys.size();
+    *out_pubkeys = static_cast<uint8_t**>(malloc(sizeof(uint8_t*) * *out_count));
+    *out_lens = static_cast<size_t*>(malloc(sizeof(size_t) * *out_count));
+
+    if (!*out_pubkeys || !*out_lens) {
+        if (*out_pubkeys) free(*out_pubkeys);
+        if (*out_lens) free(*out_lens);
+        return false;
+    }
+
+    size_t i = 0;
+    for (const auto& pubkey : pubkeys) {
+        (*out_lens)[i] = pubkey.size();
+        (*out_pubkeys)[i] = static_cast<uint8_t*>(malloc(pubkey.size()));
+        if ((*out_pubkeys)[i]) {
+            memcpy((*out_pubkeys)[i], pubkey.data(), pubkey.size());
+        }
+        i++;
+    }
+
+    return true;
+}
+
+// Remove the checksum and all key origin info ("[fingerprint/path]") from a
+// descriptor string. Origins only annotate keys, so the result has the same
+// scripts.
+static std::string StripOrigins(const std::string& descriptor) {
+    std::string result;
+    result.reserve(descriptor.size());
+    bool in_origin = false;
+    for (char c : descriptor) {
+        if (c == '#') break;
+        if (c == '[') {
+            in_origin = true;
+        } else if (c == ']') {
+            in_origin = false;
+        } else if (!in_origin) {
+            result.push_back(c);
+        }
+    }
+    return result;
+}
+
+// A key as written in a descriptor: its origin info without brackets (empty
+// if none), its encoding, the derivation steps that follow it and where it
+// appears in the descriptor string
+struct KeyExpression {
+    CKeyID id;
+    bool extended;
+    std::string origin;
+    std::string key;
+    std::string path;
+    size_t offset;
+};
+
+// Find every key of a descriptor in its string form, in the order they are
+// written, without deriving anything. Unlike Expand(), this works for
+// hardened steps after an xpub.
+static std::vector<KeyExpression> FindKeyExpressions(const DescriptorNode* node) {
+    std::set<CPubKey> pubkeys;
+    std::set<CExtPubKey> ext_pubkeys;
+    node->descriptor->GetPubKeys(pubkeys, ext_pubkeys);
+
+    std::string desc;
+    std::vector<std::pair<std::string, KeyExpression>> encodings;
+    {
+        NetworkParamsLock lock(node->network);
+        desc = node->descriptor->ToString();
+        for (const auto& ext_pubkey : ext_pubkeys) {
+            encodings.push_back({EncodeExtPubKey(ext_pubkey), {ext_pubkey.pubkey.GetID(), true, "", "", "", 0}});
+        }
+    }
+    for (const auto& pubkey : pubkeys) {
+        // Keys in tr() and tapscript are written x-only
+        std::string hex = HexStr(pubkey);
+        if (desc.find(hex) == std::string::npos && pubkey.IsCompressed()) {
+            hex = hex.substr(2);
+        }
+        encodings.push_back({hex, {pubkey.GetID(), false, "", "", "", 0}});
+    }
+
+    std::vector<KeyExpression> keys;
+    for (const auto& [encoding, key] : encodings) {
+        for (size_t pos = desc.find(encoding); pos != std::string::npos; pos = desc.find(encoding, pos + 1)) {
+            size_t end = pos + encoding.size();
+            // Only whole arguments, not part of a longer key or hash
+            if (pos == 0 || std::string_view("(,{]").find(desc[pos - 1]) == std::string_view::npos) continue;
+            if (end < desc.size() && std::string_view("/,)}#").find(desc[end]) == std::string_view::npos) continue;
+
+            KeyExpression found = key;
+            found.key = encoding;
+            found.offset = pos;
+            if (desc[pos - 1] == ']') {
+                size_t open = desc.rfind('[', pos - 1);
+                if (open == std::string::npos) continue;
+                found.origin = desc.substr(open + 1, pos - 2 - open);
+            }
+            found.path = desc.substr(end, desc.find_first_of(",)}#", end) - end);
+            keys.push_back(std::move(found));
+        }
+    }
+    std::sort(keys.begin(), keys.end(),
+              [](const KeyExpression& a, const KeyExpression& b) { return a.offset < b.offset; });
+    return keys;
+}
+
+// Check whether Core's descriptor parser accepts a string as a key, with any
+// key origin and derivation path, on mainnet or on the test networks (which
+// share their key encodings). Bare hex keys are left out: they are also
+// plain miniscript keys, and are how keys decoded from a script are named.
+// Used by the miniscript wrapper to reject descriptor keys.
+bool IsDescriptorKeyExpression(const std::string& key) {
+    if (key.empty() || IsHex(key)) return false;
+    for (DescriptorNetwork network : {DESCRIPTOR_NETWORK_MAINNET, DESCRIPTOR_NETWORK_TESTNET}) {
+        NetworkParamsLock lock(network);
+        // pk() takes any key outside Taproot, tr() also takes x-only keys
+        for (const char* wrapper : {"pk(", "tr("}) {
+            try {
+                FlatSigningProvider provider;
+                std::string error;
+                if (!Parse(wrapper + key + ")", provider, error, false).empty()) return true;
+            } catch (...) {
+            }
+        }
+    }
+    return false;
+}
+
+static DescriptorResult ParseDescriptor(const char* descriptor_str, DescriptorNetwork network,
+                                        DescriptorNode** out_node, bool lenient) {
+    DescriptorResult result = {false, nullptr, MINISCRIPT_ERROR_NONE};
 
     if (!descriptor_str || !out_node) {
         result.error_message = strdup_safe("Invalid arguments: null pointer");
+        result.error_code = MINISCRIPT_ERROR_INVALID_ARGUMENT;
         return result;
     }
 
     *out_node = nullptr;
 
     try {
-        // Acquire the params mutex for the entire parse operation.
-        // This ensures atomicity: SelectParams + Parse must complete together
-        // without another thread changing the global chain parameters.
-        std::lock_guard<std::mutex> lock(GetParamsMutex());
-
-        // Set the chain parameters for this network
-        SelectParams(static_cast<int>(network));
+        // Hold the params mutex for the entire parse operation, so that no
+        // other thread changes the global chain parameters meanwhile
+        NetworkParamsLock lock(network);
 
         FlatSigningProvider provider;
         std::string error;
@@ -87,25 +322,68 @@ DescriptorResult descriptor_parse_with_network(const char* descriptor_str, Descr
 
         // Parse the descriptor using Bitcoin Core's parser
         auto descriptors = Parse(desc_str, provider, error, false);
+        bool used_fallback = false;
+
+        if (descriptors.empty() && lenient) {
+            // Retry without the checksum and key origins, which legacy
+            // exports sometimes get wrong
+            FlatSigningProvider retry_provider;
+            std::string retry_error;
+            descriptors = Parse(StripOrigins(desc_str), retry_provider, retry_error, false);
+            if (!descriptors.empty()) {
+                provider = std::move(retry_provider);
+                used_fallback = true;
+            }
+        }
 
         if (descriptors.empty()) {
             result.error_message = strdup_safe(error.empty() ? "Failed to parse descriptor" : error);
+            result.error_code = DescriptorErrorCode(desc_str);
             return result;
         }
 
         // Take the first descriptor (Parse can return multiple for combo())
-        *out_node = new DescriptorNode(std::move(descriptors[0]), std::move(provider));
+        uint256 id = Hash(descriptors[0]->ToString());
+        *out_node = new DescriptorNode(std::move(descriptors[0]), std::move(provider), network, id,
+                                       used_fallback);
         result.success = true;
 
     } catch (const std::exception& e) {
         result.error_message = strdup_safe(e.what());
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     } catch (...) {
         result.error_message = strdup_safe("Unknown error during descriptor parsing");
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     }
 
     return result;
 }
 
+extern "C" {
+
+/**
+ * Parse a descriptor string with the specified network context.
+ *
+ * This function is thread-safe: it acquires a mutex to ensure that the global
+ * chain parameters remain consistent throughout the entire parse operation.
+ * This prevents race conditions when multiple threads parse descriptors with
+ * different network contexts (e.g., one parsing xpub on mainnet while another
+ * parses tpub on testnet).
+ *
+ * @param descriptor_str The descriptor string to parse (e.g., "wpkh(tpub...)")
+ * @param network The network context for key validation and address encoding
+ * @param out_node Output pointer for the parsed descriptor node
+ * @return Result indicating success or failure with error message
+ */
+DescriptorResult descriptor_parse_with_network(const char* descriptor_str, DescriptorNetwork network, DescriptorNode** out_node) {
+    return ParseDescriptor(descriptor_str, network, out_node, false);
+}
+
+DescriptorResult descriptor_parse_lenient(const char* descriptor_str, DescriptorNetwork network,
+                                          DescriptorNode** out_node) {
+    return ParseDescriptor(descriptor_str, network, out_node, true);
+}
+
 bool descriptor_is_range(const DescriptorNode* node) {
     if (!node || !node->descriptor) {
         return false;
@@ -120,12 +398,38 @@ bool descriptor_is_solvable(const DescriptorNode* node) {
     return node->descriptor->IsSolvable();
 }
 
+bool descriptor_is_lenient(const DescriptorNode* node) {
+    if (!node) {
+        return false;
+    }
+    return node->lenient;
+}
+
+bool descriptor_requires_private_key_to_expand(const DescriptorNode* node) {
+    if (!node || !node->descriptor) {
+        return false;
+    }
+
+    try {
+        // Only a hardened step after an extended key needs its private key
+        for (const KeyExpression& key : FindKeyExpressions(node)) {
+            if (key.extended && key.path.find_first_of("hH'") != std::string::npos) {
+                return true;
+            }
+        }
+        return false;
+    } catch (...) {
+        return false;
+    }
+}
+
 char* descriptor_to_string(const DescriptorNode* node) {
     if (!node || !node->descriptor) {
         return nullptr;
     }
 
     try {
+        NetworkParamsLock lock(node->network);
         std::string str = node->descriptor->ToString();
         return strdup_safe(str);
     } catch (...) {
@@ -133,6 +437,25 @@ char* descriptor_to_string(const DescriptorNode* node) {
     }
 }
 
+char* descriptor_checksum(const DescriptorNode* node) {
+    if (!node || !node->descriptor) {
+        return nullptr;
+    }
+
+    try {
+        // ToString() appends the checksum of the canonical form
+        NetworkParamsLock lock(node->network);
+        std::string str = node->descriptor->ToString();
+        size_t pos = str.rfind('#');
+        if (pos == std::string::npos) {
+            return nullptr;
+        }
+        return strdup_safe(str.substr(pos + 1));
+    } catch (...) {
+        return nullptr;
+    }
+}
+
 bool descriptor_expand(const DescriptorNode* node, int pos,
                        uint8_t** out_script, size_t* out_len) {
     if (!node || !node->descriptor || !out_script || !out_len) {
@@ -166,7 +489,62 @@ bool descriptor_expand(const DescriptorNode* node, int pos,
     return false;
 }
 
-char* descriptor_get_address(const DescriptorNode* node, int pos, DescriptorNetwork network) {
+DescriptorCacheNode* descriptor_cache_new(void) {
+    try {
+        return new DescriptorCacheNode();
+    } catch (...) {
+        return nullptr;
+    }
+}
+
+bool descriptor_expand_cached(const DescriptorNode* node, int pos, DescriptorCacheNode* cache,
+                              uint8_t** out_script, size_t* out_len) {
+    if (!node || !node->descriptor || !cache || !out_script || !out_len) {
+        return false;
+    }
+
+    try {
+        if (!cache->descriptor) {
+            cache->descriptor = node->id;
+        } else if (*cache->descriptor != node->id) {
+            return false;
+        }
+
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+
+        if (!node->descriptor->ExpandFromCache(pos, cache->cache, scripts, out_provider)) {
+            // Not cached yet: derive and remember the keys for next time
+            DescriptorCache new_entries;
+            scripts.clear();
+            if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &new_entries)) {
+                return false;
+            }
+            cache->cache.MergeAndDiff(new_entries);
+        }
+
+        if (scripts.empty()) {
+            return false;
+        }
+
+        const CScript& script = scripts[0];
+        *out_len = script.size();
+        *out_script = static_cast<uint8_t*>(malloc(*out_len));
+        if (*out_script) {
+            memcpy(*out_script, script.data(), *out_len);
+            return true;
+        }
+    } catch (...) {
+    }
+
+    return false;
+}
+
+void descriptor_cache_free(DescriptorCacheNode* cache) {
+    delete cache;
+}
+
+char* descriptor_get_address(const DescriptorNode* node, int pos) {
     if (!node || !node->descriptor) {
         return nullptr;
     }
@@ -184,43 +562,73 @@ char* descriptor_get_address(const DescriptorNode* node, int pos, DescriptorNetw
             return nullptr;
         }
 
-        // Get the output type to determine address format
-        auto output_type = node->descriptor->GetOutputType();
-        if (!output_type) {
+        // Create address from script
+        auto address = ScriptAddress(node, scripts[0]);
+        if (!address) {
             return nullptr;
         }
 
-        // Create address from script
+        return strdup_safe(*address);
+    } catch (...) {
+        return nullptr;
+    }
+}
+
+bool descriptor_derive(const DescriptorNode* node, int pos, uint8_t** out_script,
+                       size_t* out_script_len, char** out_address, uint8_t*** out_pubkeys,
+                       size_t** out_lens, size_t* out_count) {
+    if (!node || !node->descriptor || !out_script || !out_script_len || !out_address ||
+        !out_pubkeys || !out_lens || !out_count) {
+        return false;
+    }
+
+    *out_script = nullptr;
+    *out_address = nullptr;
+
+    try {
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        DescriptorCache cache;
+
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+            return false;
+        }
+
+        if (scripts.empty()) {
+            return false;
+        }
+
         const CScript& script = scripts[0];
-        CTxDestination dest;
-
-        // Extract destination from script
-        if (!ExtractDestination(script, dest)) {
-            // For P2WSH and other complex scripts, we need to handle differently
-            // Try to create a witness script hash address
-            if (script.IsPayToWitnessScriptHash()) {
-                // Extract the witness program
-                std::vector<unsigned char> witprog;
-                int version;
-                if (script.IsWitnessProgram(version, witprog) && version == 0 && witprog.size() == 32) {
-                    WitnessV0ScriptHash hash;
-                    std::copy(witprog.begin(), witprog.end(), hash.begin());
-                    dest = hash;
-                } else {
-                    return nullptr;
-                }
-            } else {
-                return nullptr;
-            }
+        std::set<CPubKey> pubkeys;
+        CollectPubKeys(node, out_provider, pubkeys);
+        auto address = ScriptAddress(node, script);
+
+        *out_script_len = script.size();
+        *out_script = static_cast<uint8_t*>(malloc(*out_script_len));
+        if (!*out_script) {
+            return false;
+        }
+        memcpy(*out_script, script.data(), *out_script_len);
+
+        if (address) {
+            *out_address = strdup_safe(*address);
         }
 
-        // Encode the address - Bitcoin Core's EncodeDestination uses global chain params
-        // For now, we just use the default encoding
-        std::string address = EncodeDestination(dest);
+        if (!ExportPubKeys(pubkeys, out_pubkeys, out_lens, out_count)) {
+            free(*out_script);
+            free(*out_address);
+            *out_script = nullptr;
+            *out_address = nullptr;
+            return false;
+        }
 
-        return strdup_safe(address);
+        return true;
     } catch (...) {
-        return nullptr;
+        free(*out_script);
+        free(*out_address);
+        *out_script = nullptr;
+        *out_address = nullptr;
+        return false;
     }
 }
 
@@ -230,6 +638,101 @@ bool descriptor_get_pubkeys(const DescriptorNode* node, int pos,
         return false;
     }
 
+    try {
+        std::set<CPubKey> pubkeys;
+        if (!CollectPubKeys(node, pos, pubkeys)) {
+            return false;
+        }
+
+        return ExportPubKeys(pubkeys, out_pubkeys, out_lens, out_count);
+    } catch (...) {
+        return false;
+    }
+}
+
+bool descriptor_get_pubkeys_range(const DescriptorNode* node, int start, size_t count,
+                                  uint8_t*** out_pubkeys, size_t** out_lens, size_t* out_total,
+                                  int64_t** out_counts) {
+    if (!node || !node->descriptor || !out_pubkeys || !out_lens || !out_total || !out_counts) {
+        return false;
+    }
+
+    *out_pubkeys = nullptr;
+    *out_lens = nullptr;
+    *out_total = 0;
+    *out_counts = nullptr;
+
+    if (count == 0) {
+        return true;
+    }
+
+    // Every index must be a valid unhardened derivation index
+    constexpr int64_t MAX_INDEX = 0x7fffffff;
+    if (start < 0 || count > static_cast<uint64_t>(MAX_INDEX) + 1 ||
+        start + static_cast<int64_t>(count) - 1 > MAX_INDEX) {
+        return false;
+    }
+
+    try {
+        std::vector<CPubKey> all_pubkeys;
+        std::vector<int64_t> counts;
+        counts.reserve(count);
+
+        for (size_t i = 0; i < count; i++) {
+            std::set<CPubKey> pubkeys;
+            int pos = static_cast<int>(start + static_cast<int64_t>(i));
+            if (!CollectPubKeys(node, pos, pubkeys)) {
+                counts.push_back(-1);
+                continue;
+            }
+            counts.push_back(static_cast<int64_t>(pubkeys.size()));
+            all_pubkeys.insert(all_pubkeys.end(), pubkeys.begin(), pubkeys.end());
+        }
+
+        *out_counts = static_cast<int64_t*>(malloc(sizeof(int64_t) * count));
+        if (!*out_counts) {
+            return false;
+        }
+        memcpy(*out_counts, counts.data(), sizeof(int64_t) * count);
+
+        if (all_pubkeys.empty()) {
+            return true;
+        }
+
+        *out_pubkeys = static_cast<uint8_t**>(malloc(sizeof(uint8_t*) * all_pubkeys.size()));
+        *out_lens = static_cast<size_t*>(malloc(sizeof(size_t) * all_pubkeys.size()));
+
+        if (!*out_pubkeys || !*out_lens) {
+            free(*out_pubkeys);
+            free(*out_lens);
+            free(*out_counts);
+            *out_pubkeys = nullptr;
+            *out_lens = nullptr;
+            *out_counts = nullptr;
+            return false;
+        }
+
+        *out_total = all_pubkeys.size();
+        for (size_t i = 0; i < all_pubkeys.size(); i++) {
+            (*out_lens)[i] = all_pubkeys[i].size();
+            (*out_pubkeys)[i] = static_cast<uint8_t*>(malloc(all_pubkeys[i].size()));
+            if ((*out_pubkeys)[i]) {
+                memcpy((*out_pubkeys)[i], all_pubkeys[i].data(), all_pubkeys[i].size());
+            }
+        }
+
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool descriptor_get_multisig_pubkeys(const DescriptorNode* node, int pos,
+                                     uint8_t*** out_pubkeys, size_t** out_lens, size_t* out_count) {
+    if (!node || !node->descriptor || !out_pubkeys || !out_lens || !out_count) {
+        return false;
+    }
+
     try {
         std::vector<CScript> scripts;
         FlatSigningProvider out_provider;
@@ -239,24 +742,28 @@ bool descriptor_get_pubkeys(const DescriptorNode* node, int pos,
             return false;
         }
 
-        // Get all pubkeys from the provider
-        std::set<CPubKey> pubkeys;
-        std::set<CExtPubKey> ext_pubkeys;
-        node->descriptor->GetPubKeys(pubkeys, ext_pubkeys);
+        // The multisig script is either the output script itself (bare) or
+        // one of the redeem/witness scripts produced by the expansion
+        std::vector<CScript> candidates(scripts.begin(), scripts.end());
+        for (const auto& [id, script] : out_provider.scripts) {
+            candidates.push_back(script);
+        }
 
-        // Also get pubkeys from the expanded provider
-        for (const auto& [keyid, pubkey] : out_provider.pubkeys) {
-            pubkeys.insert(pubkey);
+        std::vector<std::vector<unsigned char>> solutions;
+        for (const auto& script : candidates) {
+            solutions.clear();
+            if (Solver(script, solutions) == TxoutType::MULTISIG) {
+                break;
+            }
+            solutions.clear();
         }
 
-        if (pubkeys.empty()) {
-            *out_count = 0;
-            *out_pubkeys = nullptr;
-            *out_lens = nullptr;
-            return true;
+        // Solutions are [m, pubkey1, ..., pubkeyn, n]
+        if (solutions.size() < 3) {
+            return false;
         }
 
-        *out_count = pubkeys.size();
+        *out_count = solutions.size() - 2;
         *out_pubkeys = static_cast<uint8_t**>(malloc(sizeof(uint8_t*) * *out_count));
         *out_lens = static_cast<size_t*>(malloc(sizeof(size_t) * *out_count));
 
@@ -266,14 +773,13 @@ bool descriptor_get_pubkeys(const DescriptorNode* node, int pos,
             return false;
         }
 
-        size_t i = 0;
-        for (const auto& pubkey : pubkeys) {
+        for (size_t i = 0; i < *out_count; i++) {
+            const auto& pubkey = solutions[i + 1];
             (*out_lens)[i] = pubkey.size();
             (*out_pubkeys)[i] = static_cast<uint8_t*>(malloc(pubkey.size()));
             if ((*out_pubkeys)[i]) {
                 memcpy((*out_pubkeys)[i], pubkey.data(), pubkey.size());
             }
-            i++;
         }
 
         return true;
@@ -308,6 +814,391 @@ bool descriptor_get_max_satisfaction_weight(const DescriptorNode* node, bool use
     return false;
 }
 
+bool descriptor_get_max_satisfaction_elems(const DescriptorNode* node, int64_t* out_elems) {
+    if (!node || !node->descriptor || !out_elems) {
+        return false;
+    }
+
+    auto elems = node->descriptor->MaxSatisfactionElems();
+    if (elems) {
+        *out_elems = *elems;
+        return true;
+    }
+    return false;
+}
+
+bool descriptor_get_output_type(const DescriptorNode* node, DescriptorOutputType* out_type) {
+    if (!node || !node->descriptor || !out_type) {
+        return false;
+    }
+
+    auto output_type = node->descriptor->GetOutputType();
+    if (!output_type) {
+        return false;
+    }
+    switch (*output_type) {
+    case OutputType::LEGACY:
+        *out_type = DESCRIPTOR_OUTPUT_LEGACY;
+        return true;
+    case OutputType::P2SH_SEGWIT:
+        *out_type = DESCRIPTOR_OUTPUT_P2SH_SEGWIT;
+        return true;
+    case OutputType::BECH32:
+        *out_type = DESCRIPTOR_OUTPUT_BECH32;
+        return true;
+    case OutputType::BECH32M:
+        *out_type = DESCRIPTOR_OUTPUT_BECH32M;
+        return true;
+    default:
+        return false;
+    }
+}
+
+bool descriptor_get_taproot_output_key(const DescriptorNode* node, int pos, uint8_t* out_key) {
+    if (!node || !node->descriptor || !out_key) {
+        return false;
+    }
+
+    try {
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        DescriptorCache cache;
+
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+            return false;
+        }
+
+        if (scripts.empty()) {
+            return false;
+        }
+
+        // The output key is the witness program of the v1 scriptPubKey
+        int version;
+        std::vector<unsigned char> witprog;
+        if (!scripts[0].IsWitnessProgram(version, witprog) || version != 1 || witprog.size() != 32) {
+            return false;
+        }
+
+        memcpy(out_key, witprog.data(), witprog.size());
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool descriptor_get_taproot_merkle_root(const DescriptorNode* node, int pos, uint8_t* out_root) {
+    if (!node || !node->descriptor || !out_root) {
+        return false;
+    }
+
+    try {
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        DescriptorCache cache;
+
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+            return false;
+        }
+
+        // tr() descriptors record their TaprootBuilder keyed by output key
+        if (out_provider.tr_trees.empty()) {
+            return false;
+        }
+
+        TaprootSpendData spend_data = out_provider.tr_trees.begin()->second.GetSpendData();
+        if (spend_data.merkle_root.IsNull()) {
+            return false;
+        }
+
+        memcpy(out_root, spend_data.merkle_root.begin(), 32);
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool descriptor_get_origin_fingerprints(const DescriptorNode* node, uint8_t** out_fingerprints,
+                                        size_t* out_count) {
+    if (!node || !node->descriptor || !out_fingerprints || !out_count) {
+        return false;
+    }
+
+    *out_fingerprints = nullptr;
+    *out_count = 0;
+
+    try {
+        // Origins are read from the keys as written, so that keys which
+        // cannot be derived without a private key are included
+        std::vector<KeyExpression> keys = FindKeyExpressions(node);
+        if (keys.empty()) {
+            return true;
+        }
+
+        *out_fingerprints = static_cast<uint8_t*>(malloc(4 * keys.size()));
+        if (!*out_fingerprints) {
+            return false;
+        }
+        for (size_t i = 0; i < keys.size(); i++) {
+            // As in Core, a key without origin info is its own origin
+            std::vector<unsigned char> fingerprint = ParseHex(keys[i].origin.substr(0, 8));
+            if (fingerprint.size() != 4) {
+                fingerprint.assign(keys[i].id.begin(), keys[i].id.begin() + 4);
+            }
+            memcpy(*out_fingerprints + 4 * i, fingerprint.data(), 4);
+        }
+        *out_count = keys.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool descriptor_get_signing_requirements(const DescriptorNode* node, int pos, PubKeyInfo** out_infos,
+                                         size_t* out_count) {
+    if (!node || !node->descriptor || !out_infos || !out_count) {
+        return false;
+    }
+
+    *out_infos = nullptr;
+    *out_count = 0;
+
+    try {
+        // A descriptor that cannot be solved cannot be signed for either
+        if (!node->descriptor->IsSolvable()) {
+            return false;
+        }
+
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider)) {
+            return false;
+        }
+        // Private keys the descriptor already holds (xprv or WIF keys)
+        FlatSigningProvider private_provider;
+        node->descriptor->ExpandPrivate(pos, node->provider, private_provider);
+
+        // The origins are keyed by key id. To list the keys in the order they
+        // are written, derive each key expression on its own at pos.
+        std::map<CKeyID, size_t> key_order;
+        std::vector<KeyExpression> keys = FindKeyExpressions(node);
+        for (size_t i = 0; i < keys.size(); i++) {
+            std::string expression = keys[i].key + keys[i].path;
+            if (!keys[i].origin.empty()) {
+                expression = "[" + keys[i].origin + "]" + expression;
+            }
+            NetworkParamsLock lock(node->network);
+            // pk() takes any key outside Taproot, tr() also takes x-only keys
+            for (const char* wrapper : {"pk(", "tr("}) {
+                FlatSigningProvider key_provider;
+                std::string error;
+                auto parsed = Parse(wrapper + expression + ")", key_provider, error, false);
+                if (parsed.empty()) continue;
+
+                std::vector<CScript> key_scripts;
+                FlatSigningProvider key_out;
+                if (parsed[0]->Expand(pos, key_provider, key_scripts, key_out)) {
+                    for (const auto& entry : key_out.origins) {
+                        key_order.emplace(entry.first, i);
+                    }
+                }
+                break;
+            }
+        }
+
+        std::vector<std::pair<CPubKey, KeyOriginInfo>> missing;
+        for (const auto& [keyid, origin] : out_provider.origins) {
+            CKey key;
+            bool have_key = private_provider.GetKey(keyid, key);
+            // Taproot keys are stored under either parity of their x-only key
+            if (!have_key && origin.first.IsCompressed()) {
+                for (const CKeyID& id : XOnlyPubKey(origin.first).GetKeyIDs()) {
+                    have_key = have_key || private_provider.GetKey(id, key);
+                }
+            }
+            if (!have_key) {
+                missing.push_back(origin);
+            }
+        }
+        std::stable_sort(missing.begin(), missing.end(), [&](const auto& a, const auto& b) {
+            auto a_order = key_order.find(a.first.GetID());
+            auto b_order = key_order.find(b.first.GetID());
+            size_t a_pos = a_order == key_order.end() ? keys.size() : a_order->second;
+            size_t b_pos = b_order == key_order.end() ? keys.size() : b_order->second;
+            return a_pos < b_pos;
+        });
+        if (missing.empty()) {
+            return true;
+        }
+
+        *out_infos = static_cast<PubKeyInfo*>(calloc(missing.size(), sizeof(PubKeyInfo)));
+        if (!*out_infos) {
+            return false;
+        }
+        *out_count = missing.size();
+        for (size_t i = 0; i < missing.size(); i++) {
+            const auto& [pubkey, info] = missing[i];
+            PubKeyInfo& out = (*out_infos)[i];
+
+            std::string path;
+            for (uint32_t step : info.path) {
+                path += "/" + std::to_string(step & 0x7fffffffU);
+                if (step >> 31) {
+                    path += "h";
+                }
+            }
+            out.pubkey = static_cast<uint8_t*>(malloc(pubkey.size()));
+            out.origin_fingerprint = strdup_safe(HexStr(info.fingerprint));
+            out.origin_path = strdup_safe(path);
+            if (!out.pubkey || !out.origin_fingerprint || !out.origin_path) {
+                descriptor_free_pubkey_infos(*out_infos, *out_count);
+                *out_infos = nullptr;
+                *out_count = 0;
+                return false;
+            }
+            memcpy(out.pubkey, pubkey.data(), pubkey.size());
+            out.pubkey_len = pubkey.size();
+        }
+        return true;
+    } catch (...) {
+        descriptor_free_pubkey_infos(*out_infos, *out_count);
+        *out_infos = nullptr;
+        *out_count = 0;
+        return false;
+    }
+}
+
+bool descriptor_get_tap_leaves(const DescriptorNode* node, int pos, uint8_t*** out_scripts,
+                               size_t** out_lens, uint8_t** out_versions, size_t* out_count) {
+    if (!node || !node->descriptor || !out_scripts || !out_lens || !out_versions || !out_count) {
+        return false;
+    }
+
+    *out_scripts = nullptr;
+    *out_lens = nullptr;
+    *out_versions = nullptr;
+    *out_count = 0;
+
+    try {
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        DescriptorCache cache;
+
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+            return false;
+        }
+
+        // tr() descriptors record their TaprootBuilder keyed by output key
+        if (out_provider.tr_trees.empty()) {
+            return false;
+        }
+
+        // Leaves in depth-first order, as (depth, leaf version, script)
+        auto leaves = out_provider.tr_trees.begin()->second.GetTreeTuples();
+        if (leaves.empty()) {
+            return true;
+        }
+
+        *out_scripts = static_cast<uint8_t**>(calloc(leaves.size(), sizeof(uint8_t*)));
+        *out_lens = static_cast<size_t*>(malloc(sizeof(size_t) * leaves.size()));
+        *out_versions = static_cast<uint8_t*>(malloc(leaves.size()));
+        if (!*out_scripts || !*out_lens || !*out_versions) {
+            free(*out_scripts);
+            free(*out_lens);
+            free(*out_versions);
+            *out_scripts = nullptr;
+            *out_lens = nullptr;
+            *out_versions = nullptr;
+            return false;
+        }
+
+        for (size_t i = 0; i < leaves.size(); ++i) {
+            const auto& [depth, version, script] = leaves[i];
+            (*out_versions)[i] = version;
+            (*out_lens)[i] = script.size();
+            if (!script.empty()) {
+                (*out_scripts)[i] = static_cast<uint8_t*>(malloc(script.size()));
+                if ((*out_scripts)[i]) {
+                    memcpy((*out_scripts)[i], script.data(), script.size());
+                }
+            }
+        }
+        *out_count = leaves.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+// Copy a script into a malloc'd buffer
+static bool CopyScript(const CScript& script, uint8_t** out_script, size_t* out_len) {
+    *out_len = script.size();
+    *out_script = static_cast<uint8_t*>(malloc(script.empty() ? 1 : script.size()));
+    if (!*out_script) {
+        return false;
+    }
+    memcpy(*out_script, script.data(), script.size());
+    return true;
+}
+
+bool descriptor_get_spend_scripts(const DescriptorNode* node, int pos,
+                                  uint8_t** out_redeem, size_t* out_redeem_len,
+                                  uint8_t** out_witness, size_t* out_witness_len) {
+    if (!node || !node->descriptor || !out_redeem || !out_redeem_len || !out_witness ||
+        !out_witness_len) {
+        return false;
+    }
+
+    *out_redeem = nullptr;
+    *out_redeem_len = 0;
+    *out_witness = nullptr;
+    *out_witness_len = 0;
+
+    try {
+        std::vector<CScript> scripts;
+        FlatSigningProvider out_provider;
+        DescriptorCache cache;
+
+        if (!node->descriptor->Expand(pos, node->provider, scripts, out_provider, &cache)) {
+            return false;
+        }
+        if (scripts.empty()) {
+            return false;
+        }
+
+        // Look the scripts up the way SignStep does: redeem scripts by
+        // Hash160, witness scripts by RIPEMD160 of their SHA256
+        std::vector<std::vector<unsigned char>> solutions;
+        TxoutType type = Solver(scripts[0], solutions);
+        if (type == TxoutType::SCRIPTHASH) {
+            CScript redeem;
+            if (!out_provider.GetCScript(CScriptID(uint160(solutions[0])), redeem)) {
+                return false;
+            }
+            if (!CopyScript(redeem, out_redeem, out_redeem_len)) {
+                return false;
+            }
+            type = Solver(redeem, solutions);
+        }
+        if (type == TxoutType::WITNESS_V0_SCRIPTHASH) {
+            CScript witness_script;
+            if (!out_provider.GetCScript(CScriptID(RIPEMD160(solutions[0])), witness_script) ||
+                !CopyScript(witness_script, out_witness, out_witness_len)) {
+                free(*out_redeem);
+                *out_redeem = nullptr;
+                *out_redeem_len = 0;
+                return false;
+            }
+        }
+        return true;
+    } catch (...) {
+        free(*out_redeem);
+        free(*out_witness);
+        *out_redeem = nullptr;
+        *out_witness = nullptr;
+        return false;
+    }
+}
+
 char* descriptor_get_checksum(const char* descriptor_str) {
     if (!descriptor_str) {
         return nullptr;
@@ -350,6 +1241,30 @@ void descriptor_free_pubkeys(uint8_t** pubkeys, size_t* lens, size_t count) {
     }
 }
 
+void descriptor_free_tap_leaves(uint8_t** scripts, size_t* lens, uint8_t* versions, size_t count) {
+    descriptor_free_pubkeys(scripts, lens, count);
+    free(versions);
+}
+
+void descriptor_free_pubkeys_range(uint8_t** pubkeys, size_t* lens, size_t total,
+                                   int64_t* counts) {
+    descriptor_free_pubkeys(pubkeys, lens, total);
+    if (counts) {
+        free(counts);
+    }
+}
+
+void descriptor_free_pubkey_infos(PubKeyInfo* infos, size_t count) {
+    if (infos) {
+        for (size_t i = 0; i < count; i++) {
+            free(infos[i].pubkey);
+            free(infos[i].origin_fingerprint);
+            free(infos[i].origin_path);
+        }
+        free(infos);
+    }
+}
+
 const char* descriptor_version(void) {
     return DESCRIPTOR_VERSION_STRING;
 }
@@ -12,10 +12,14 @@ extern "C" {
 // Opaque descriptor node type
 typedef struct DescriptorNode DescriptorNode;
 
+// Opaque cache of derived keys, bound to the first descriptor it is used with
+typedef struct DescriptorCacheNode DescriptorCacheNode;
+
 // Result type for descriptor operations
 typedef struct {
     bool success;
     char* error_message;
+    int error_code;  // MiniscriptErrorCode (see miniscript_wrapper.h)
 } DescriptorResult;
 
 // Network type for address generation
@@ -26,6 +30,14 @@ typedef enum {
     DESCRIPTOR_NETWORK_REGTEST = 3,
 } DescriptorNetwork;
 
+// Output type of a descriptor, as in Bitcoin Core's OutputType
+typedef enum {
+    DESCRIPTOR_OUTPUT_LEGACY = 0,
+    DESCRIPTOR_OUTPUT_P2SH_SEGWIT = 1,
+    DESCRIPTOR_OUTPUT_BECH32 = 2,
+    DESCRIPTOR_OUTPUT_BECH32M = 3,
+} DescriptorOutputType;
+
 // Expanded script output
 typedef struct {
     uint8_t* script;
@@ -50,6 +62,21 @@ typedef struct {
  */
 DescriptorResult descriptor_parse_with_network(const char* descriptor_str, DescriptorNetwork network, DescriptorNode** out_node);
 
+/**
+ * Parse a descriptor string like descriptor_parse_with_network(), but if
+ * that fails, retry with the checksum and all key origin info removed.
+ *
+ * Useful for legacy exports with malformed key origins. The resulting
+ * descriptor has no origin info.
+ *
+ * @param descriptor_str The descriptor string to parse
+ * @param network The network to use for key parsing (determines xpub vs tpub)
+ * @param out_node Output pointer for the parsed descriptor
+ * @return Result indicating success or failure with the error of the strict parse
+ */
+DescriptorResult descriptor_parse_lenient(const char* descriptor_str, DescriptorNetwork network,
+                                          DescriptorNode** out_node);
+
 /**
  * Check if the descriptor is ranged (contains wildcards).
  */
@@ -60,12 +87,32 @@ bool descriptor_is_range(const DescriptorNode* node);
  */
 bool descriptor_is_solvable(const DescriptorNode* node);
 
+/**
+ * Check if the descriptor was parsed by descriptor_parse_lenient() only
+ * after dropping its checksum and key origins.
+ */
+bool descriptor_is_lenient(const DescriptorNode* node);
+
+/**
+ * Check if expanding the descriptor needs private keys: whether any
+ * extended key is followed by a hardened derivation step. Such descriptors
+ * fail to expand unless the corresponding xprv was given. The derivation
+ * paths are read directly; nothing is derived.
+ */
+bool descriptor_requires_private_key_to_expand(const DescriptorNode* node);
+
 /**
  * Convert descriptor back to string.
  * Caller must free the returned string with descriptor_free_string().
  */
 char* descriptor_to_string(const DescriptorNode* node);
 
+/**
+ * Get the checksum of the descriptor's canonical string form.
+ * Caller must free the returned string with descriptor_free_string().
+ */
+char* descriptor_checksum(const DescriptorNode* node);
+
 /**
  * Expand a descriptor at a specific position to get the actual script.
  * For non-ranged descriptors, pos is ignored.
@@ -80,14 +127,64 @@ bool descriptor_expand(const DescriptorNode* node, int pos,
                        uint8_t** out_script, size_t* out_len);
 
 /**
- * Get the address for a descriptor at a specific position.
+ * Create an empty derivation cache for descriptor_expand_cached().
+ * Free with descriptor_cache_free().
+ */
+DescriptorCacheNode* descriptor_cache_new(void);
+
+/**
+ * Expand a descriptor like descriptor_expand(), reading and storing derived
+ * keys in a cache so that later expansions skip repeated BIP32 derivation.
+ *
+ * A cache is bound to the descriptor it is first used with; using it with a
+ * different descriptor fails.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index (0, 1, 2, ...)
+ * @param cache The cache to read from and update
+ * @param out_script Output pointer for script bytes
+ * @param out_len Output pointer for script length
+ * @return true on success
+ */
+bool descriptor_expand_cached(const DescriptorNode* node, int pos, DescriptorCacheNode* cache,
+                              uint8_t** out_script, size_t* out_len);
+
+/**
+ * Free a cache created with descriptor_cache_new().
+ */
+void descriptor_cache_free(DescriptorCacheNode* cache);
+
+/**
+ * Get the address for a descriptor at a specific position, encoded for the
+ * network the descriptor was parsed with.
  *
  * @param node The descriptor
  * @param pos The derivation index
- * @param network The network (mainnet, testnet, etc.)
  * @return The address string, or NULL on error. Caller must free with descriptor_free_string().
  */
-char* descriptor_get_address(const DescriptorNode* node, int pos, DescriptorNetwork network);
+char* descriptor_get_address(const DescriptorNode* node, int pos);
+
+/**
+ * Expand a descriptor once and return its script, address and public keys.
+ *
+ * Equivalent to descriptor_expand(), descriptor_get_address() and
+ * descriptor_get_pubkeys() at the same position, sharing one key derivation.
+ * The address is encoded for the network the descriptor was parsed with.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_script Output pointer for script bytes. Free with descriptor_free_bytes().
+ * @param out_script_len Output pointer for script length
+ * @param out_address Output address string, or NULL if the script has no
+ *                    address. Free with descriptor_free_string().
+ * @param out_pubkeys Output array of public key bytes
+ * @param out_lens Output array of public key lengths
+ * @param out_count Number of public keys. Free with descriptor_free_pubkeys().
+ * @return true on success
+ */
+bool descriptor_derive(const DescriptorNode* node, int pos, uint8_t** out_script,
+                       size_t* out_script_len, char** out_address, uint8_t*** out_pubkeys,
+                       size_t** out_lens, size_t* out_count);
 
 /**
  * Get all public keys from the descriptor at a specific position.
@@ -101,6 +198,42 @@ char* descriptor_get_address(const DescriptorNode* node, int pos, DescriptorNetw
 bool descriptor_get_pubkeys(const DescriptorNode* node, int pos,
                             uint8_t*** out_pubkeys, size_t** out_lens, size_t* out_count);
 
+/**
+ * Get the public keys of the descriptor for a range of derivation indices.
+ *
+ * The keys of all indices are returned concatenated in index order;
+ * out_counts[i] is the number of keys at index start + i, or -1 if the
+ * descriptor could not be expanded at that index.
+ *
+ * @param node The descriptor
+ * @param start The first derivation index
+ * @param count Number of indices
+ * @param out_pubkeys Output array of public key bytes
+ * @param out_lens Output array of public key lengths
+ * @param out_total Total number of public keys
+ * @param out_counts Output array of per-index key counts (count entries)
+ * @return true on success, false if start is negative or the range goes
+ *         past index 2^31 - 1. Free with descriptor_free_pubkeys_range().
+ */
+bool descriptor_get_pubkeys_range(const DescriptorNode* node, int start, size_t count,
+                                  uint8_t*** out_pubkeys, size_t** out_lens, size_t* out_total,
+                                  int64_t** out_counts);
+
+/**
+ * Get the public keys of a multisig descriptor (multi/sortedmulti) in the
+ * order they appear in the expanded script. For sortedmulti this is the
+ * lexicographically sorted order.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_pubkeys Output array of public key bytes
+ * @param out_lens Output array of public key lengths
+ * @param out_count Number of public keys
+ * @return true on success, false if no multisig script is found
+ */
+bool descriptor_get_multisig_pubkeys(const DescriptorNode* node, int pos,
+                                     uint8_t*** out_pubkeys, size_t** out_lens, size_t* out_count);
+
 /**
  * Get the script size for this descriptor.
  */
@@ -111,6 +244,106 @@ bool descriptor_get_script_size(const DescriptorNode* node, int64_t* out_size);
  */
 bool descriptor_get_max_satisfaction_weight(const DescriptorNode* node, bool use_max_sig, int64_t* out_weight);
 
+/**
+ * Get the maximum number of stack elements needed to satisfy this descriptor.
+ */
+bool descriptor_get_max_satisfaction_elems(const DescriptorNode* node, int64_t* out_elems);
+
+/**
+ * Get the output type of this descriptor. For sh(wpkh()) and sh(wsh()) this
+ * is DESCRIPTOR_OUTPUT_P2SH_SEGWIT.
+ *
+ * @return true on success, false if the descriptor has no output type
+ *         (e.g. raw() scripts without an address)
+ */
+bool descriptor_get_output_type(const DescriptorNode* node, DescriptorOutputType* out_type);
+
+/**
+ * Get the 32-byte tweaked Taproot output key of a tr() or rawtr() descriptor.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_key Output buffer for the 32-byte x-only output key
+ * @return true on success, false if the descriptor is not a Taproot descriptor
+ */
+bool descriptor_get_taproot_output_key(const DescriptorNode* node, int pos, uint8_t* out_key);
+
+/**
+ * Get the 32-byte Merkle root of the script tree of a tr() descriptor.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_root Output buffer for the 32-byte Merkle root
+ * @return true on success, false if the descriptor has no script tree
+ */
+bool descriptor_get_taproot_merkle_root(const DescriptorNode* node, int pos, uint8_t* out_root);
+
+/**
+ * Get the key origin fingerprints of every key in a descriptor, in no
+ * particular order. Keys without origin info report their own fingerprint.
+ * Keys are not derived, so this also works for hardened derivation from an
+ * xpub.
+ *
+ * @param node The descriptor
+ * @param out_fingerprints Output pointer for 4 * out_count bytes, or NULL if
+ *                         there are no keys. Free with descriptor_free_bytes().
+ * @param out_count Number of fingerprints
+ * @return true on success
+ */
+bool descriptor_get_origin_fingerprints(const DescriptorNode* node, uint8_t** out_fingerprints,
+                                        size_t* out_count);
+
+/**
+ * Get the keys a descriptor needs private keys for before it can be signed,
+ * in the order they are written in the descriptor. Keys whose private key
+ * the descriptor already holds are left out.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_infos Output array with each key, its origin fingerprint (hex)
+ *                  and origin path (e.g. "/48h/0h/0h/2h/0/0"), or NULL if no
+ *                  key is needed. Free with descriptor_free_pubkey_infos().
+ * @param out_count Number of keys
+ * @return true on success, false if the descriptor is not solvable or cannot
+ *         be expanded at pos (e.g. a hardened step after an xpub)
+ */
+bool descriptor_get_signing_requirements(const DescriptorNode* node, int pos, PubKeyInfo** out_infos,
+                                         size_t* out_count);
+
+/**
+ * Get the leaves of the script tree of a tr() descriptor, in depth-first
+ * order from left to right.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_scripts Output pointer for the leaf scripts
+ * @param out_lens Output pointer for the script lengths
+ * @param out_versions Output pointer for the leaf versions
+ * @param out_count Number of leaves (0 for key-path only descriptors).
+ *                  Free with descriptor_free_tap_leaves().
+ * @return true on success, false if the descriptor is not a tr() descriptor
+ */
+bool descriptor_get_tap_leaves(const DescriptorNode* node, int pos, uint8_t*** out_scripts,
+                               size_t** out_lens, uint8_t** out_versions, size_t* out_count);
+
+/**
+ * Get the scripts needed to spend the output of a descriptor: the P2SH
+ * redeem script and the P2WSH witness script, where the descriptor has them.
+ *
+ * @param node The descriptor
+ * @param pos The derivation index
+ * @param out_redeem Output pointer for the redeem script, or NULL if the
+ *                   output is not P2SH. Free with descriptor_free_bytes().
+ * @param out_redeem_len Output pointer for the redeem script length
+ * @param out_witness Output pointer for the witness script, or NULL if the
+ *                    output is not (P2SH-wrapped) P2WSH. Free with descriptor_free_bytes().
+ * @param out_witness_len Output pointer for the witness script length
+ * @return true on success
+ */
+bool descriptor_get_spend_scripts(const DescriptorNode* node, int pos,
+                                  uint8_t** out_redeem, size_t* out_redeem_len,
+                                  uint8_t** out_witness, size_t* out_witness_len);
+
 /**
  * Get the checksum for a descriptor string.
  * Returns the descriptor with checksum appended, or empty string on error.
@@ -137,6 +370,22 @@ void descriptor_free_bytes(uint8_t* bytes);
  */
 void descriptor_free_pubkeys(uint8_t** pubkeys, size_t* lens, size_t count);
 
+/**
+ * Free the arrays returned by descriptor_get_tap_leaves().
+ */
+void descriptor_free_tap_leaves(uint8_t** scripts, size_t* lens, uint8_t* versions, size_t count);
+
+/**
+ * Free the arrays returned by descriptor_get_pubkeys_range().
+ */
+void descriptor_free_pubkeys_range(uint8_t** pubkeys, size_t* lens, size_t total,
+                                   int64_t* counts);
+
+/**
+ * Free the array returned by descriptor_get_signing_requirements().
+ */
+void descriptor_free_pubkey_infos(PubKeyInfo* infos, size_t count);
+
 /**
  * Get the descriptor wrapper version.
  */
@@ -1,15 +1,37 @@
 #include "miniscript_wrapper.h"
 
+#include <algorithm>
+#include <cstdint>
 #include <cstring>
+#include <iterator>
 #include <memory>
+#include <optional>
+#include <set>
 #include <string>
+#include <utility>
 #include <vector>
 
+#include <crypto/sha256.h>
+#include <hash.h>
+#include <policy/policy.h>
+#include <pubkey.h>
+#include <script/interpreter.h>
 #include <script/miniscript.h>
 #include <script/script.h>
+#include <serialize.h>
+#include <support/cleanse.h>
+#include <util/strencodings.h>
 
 static const char* VERSION_STRING = "0.3.0";
 
+// Bitcoin Core version and commit, defined by CMakeLists.txt at build time
+#ifndef BITCOIN_CORE_VERSION_STRING
+#define BITCOIN_CORE_VERSION_STRING "unknown"
+#endif
+#ifndef BITCOIN_CORE_COMMIT_STRING
+#define BITCOIN_CORE_COMMIT_STRING ""
+#endif
+
 struct StringKey {
     std::string str;
 
@@ -18,9 +40,67 @@ struct StringKey {
     StringKey(std::string&& s) : str(std::move(s)) {}
 };
 
+// Decode a key string as hex if it has exactly the expected length, so that
+// scripts decoded by FromScript (which names keys by their hex) round-trip.
+static std::optional<std::vector<unsigned char>> ParseHexKey(const std::string& str, size_t expected_len) {
+    auto bytes = TryParseHex<unsigned char>(str);
+    if (!bytes || bytes->size() != expected_len) {
+        return std::nullopt;
+    }
+    return bytes;
+}
+
+// Derive the public key standing in for a key name that is not a hex key:
+// the first SHA256(name || counter), with a 4-byte little-endian counter
+// starting at 0, that is a valid x-only key. Outside Tapscript it is
+// prefixed with 0x02 to form a compressed key.
+static std::vector<unsigned char> DummyPubKey(const std::string& name, miniscript::MiniscriptContext ctx) {
+    unsigned char x[CSHA256::OUTPUT_SIZE];
+    for (uint32_t counter = 0;; ++counter) {
+        unsigned char le[4] = {
+            static_cast<unsigned char>(counter), static_cast<unsigned char>(counter >> 8),
+            static_cast<unsigned char>(counter >> 16), static_cast<unsigned char>(counter >> 24)};
+        CSHA256()
+            .Write(reinterpret_cast<const unsigned char*>(name.data()), name.size())
+            .Write(le, sizeof(le))
+            .Finalize(x);
+        if (XOnlyPubKey(std::span<const unsigned char>(x, sizeof(x))).IsFullyValid()) break;
+    }
+
+    std::vector<unsigned char> key;
+    if (ctx != miniscript::MiniscriptContext::TAPSCRIPT) key.push_back(0x02);
+    key.insert(key.end(), x, x + sizeof(x));
+    return key;
+}
+
+// The public key bytes a key name stands for in scripts: hex keys of the
+// context's key length decode as themselves, other names get a DummyPubKey
+static std::vector<unsigned char> KeyBytes(const std::string& str, miniscript::MiniscriptContext ctx) {
+    size_t len = ctx == miniscript::MiniscriptContext::TAPSCRIPT ? 32 : 33;
+    if (auto bytes = ParseHexKey(str, len)) {
+        return *bytes;
+    }
+    return DummyPubKey(str, ctx);
+}
+
+// The key hash a key name stands for: 20-byte hex strings are hashes
+// decoded from a script, other names hash their KeyBytes
+static std::vector<unsigned char> KeyHashBytes(const std::string& str, miniscript::MiniscriptContext ctx) {
+    if (auto hash = ParseHexKey(str, 20)) {
+        return *hash;
+    }
+    uint160 hash = Hash160(KeyBytes(str, ctx));
+    return std::vector<unsigned char>(hash.begin(), hash.end());
+}
+
+// Defined in descriptor_wrapper.cpp
+bool IsDescriptorKeyExpression(const std::string& key);
+
 struct StringKeyContext {
     using Key = StringKey;
     miniscript::MiniscriptContext ms_ctx;
+    // First key rejected by FromString, reported in the parse error
+    mutable std::optional<std::string> unsupported_key;
 
     StringKeyContext(miniscript::MiniscriptContext ctx) : ms_ctx(ctx) {}
 
@@ -28,7 +108,14 @@ struct StringKeyContext {
 
     template<typename I>
     std::optional<StringKey> FromString(I begin, I end) const {
-        return StringKey(std::string(begin, end));
+        std::string str(begin, end);
+        // Descriptor keys (extended keys, WIF keys, key origins and
+        // derivation paths) need the Descriptor API; other keys are names
+        if (IsDescriptorKeyExpression(str)) {
+            if (!unsupported_key) unsupported_key = str;
+            return std::nullopt;
+        }
+        return StringKey(std::move(str));
     }
 
     std::optional<std::string> ToString(const StringKey& key) const {
@@ -40,32 +127,39 @@ struct StringKeyContext {
     }
 
     std::vector<unsigned char> ToPKBytes(const StringKey& key) const {
-        if (ms_ctx == miniscript::MiniscriptContext::TAPSCRIPT) {
-            return std::vector<unsigned char>(32, 0);
-        }
-        return std::vector<unsigned char>(33, 0);
+        return KeyBytes(key.str, ms_ctx);
     }
 
     std::vector<unsigned char> ToPKHBytes(const StringKey& key) const {
-        return std::vector<unsigned char>(20, 0);
+        return KeyHashBytes(key.str, ms_ctx);
     }
 
+    // Keys decoded from a script are named by their hex encoding
     template<typename I>
     std::optional<StringKey> FromPKBytes(I first, I last) const {
-        return StringKey("decoded_key");
+        return StringKey(HexStr(std::vector<unsigned char>(first, last)));
     }
 
     template<typename I>
     std::optional<StringKey> FromPKHBytes(I first, I last) const {
-        return StringKey("decoded_pkh_key");
+        return StringKey(HexStr(std::vector<unsigned char>(first, last)));
     }
 };
 
-// Satisfier context that uses callbacks to Rust
+// The keys, hashes and timelocks used by one spending path
+struct SpendPath {
+    std::set<std::string> keys;
+    std::set<std::pair<miniscript::Fragment, std::vector<unsigned char>>> hashes;
+    std::set<uint32_t> after;
+    std::set<uint32_t> older;
+};
+
 struct CallbackSatisfier {
     using Key = StringKey;
     const SatisfierCallbacks* callbacks;
     miniscript::MiniscriptContext ms_ctx;
+    // When set, only data used by this path is requested from the callbacks
+    const SpendPath* path = nullptr;
 
     CallbackSatisfier(const SatisfierCallbacks* cb, miniscript::MiniscriptContext ctx)
         : callbacks(cb), ms_ctx(ctx) {}
@@ -85,34 +179,14 @@ struct CallbackSatisfier {
         return a.str < b.str;
     }
 
+    // Keys are passed to the callbacks as the bytes they encode to in the
+    // script, so signatures are looked up by the same key
     std::vector<unsigned char> ToPKBytes(const StringKey& key) const {
-        // Convert key string to bytes - for string keys, we use the string bytes
-        std::vector<unsigned char> result;
-        // Try to parse as hex if it looks like hex
-        if (key.str.size() >= 2) {
-            result.reserve(key.str.size() / 2);
-            for (size_t i = 0; i < key.str.size(); i += 2) {
-                unsigned int byte;
-                if (sscanf(key.str.c_str() + i, "%02x", &byte) == 1) {
-                    result.push_back(static_cast<unsigned char>(byte));
-                } else {
-                    // Not hex, return placeholder
-                    if (ms_ctx == miniscript::MiniscriptContext::TAPSCRIPT) {
-                        return std::vector<unsigned char>(32, 0);
-                    }
-                    return std::vector<unsigned char>(33, 0);
-                }
-            }
-            return result;
-        }
-        if (ms_ctx == miniscript::MiniscriptContext::TAPSCRIPT) {
-            return std::vector<unsigned char>(32, 0);
-        }
-        return std::vector<unsigned char>(33, 0);
+        return KeyBytes(key.str, ms_ctx);
     }
 
     std::vector<unsigned char> ToPKHBytes(const StringKey& key) const {
-        return std::vector<unsigned char>(20, 0);
+        return KeyHashBytes(key.str, ms_ctx);
     }
 
     template<typename I>
@@ -130,6 +204,9 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->sign_callback) {
             return miniscript::Availability::NO;
         }
+        if (path && !path->keys.count(key.str)) {
+            return miniscript::Availability::NO;
+        }
 
         std::vector<unsigned char> key_bytes = ToPKBytes(key);
         uint8_t* sig_out = nullptr;
@@ -172,6 +249,9 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->check_after_callback) {
             return false;
         }
+        if (path && !path->after.count(value)) {
+            return false;
+        }
         return callbacks->check_after_callback(callbacks->rust_context, value);
     }
 
@@ -179,14 +259,27 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->check_older_callback) {
             return false;
         }
+        if (path && !path->older.count(value)) {
+            return false;
+        }
         return callbacks->check_older_callback(callbacks->rust_context, value);
     }
 
+    // Preimages are secret: wipe the Rust-allocated copy before freeing it
+    static void FreePreimage(uint8_t* preimage, size_t len) {
+        if (!preimage) return;
+        memory_cleanse(preimage, len);
+        free(preimage);
+    }
+
     // Hash preimage callbacks
     miniscript::Availability SatSHA256(const std::vector<unsigned char>& hash, std::vector<unsigned char>& preimage) const {
         if (!callbacks || !callbacks->sat_sha256_callback) {
             return miniscript::Availability::NO;
         }
+        if (path && !path->hashes.count({miniscript::Fragment::SHA256, hash})) {
+            return miniscript::Availability::NO;
+        }
 
         uint8_t* preimage_out = nullptr;
         size_t preimage_len = 0;
@@ -201,14 +294,14 @@ struct CallbackSatisfier {
 
         if (avail == MINISCRIPT_AVAILABILITY_YES && preimage_out && preimage_len > 0) {
             preimage.assign(preimage_out, preimage_out + preimage_len);
-            free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::YES;
         } else if (avail == MINISCRIPT_AVAILABILITY_MAYBE) {
-            if (preimage_out) free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::MAYBE;
         }
 
-        if (preimage_out) free(preimage_out);
+        FreePreimage(preimage_out, preimage_len);
         return miniscript::Availability::NO;
     }
 
@@ -216,6 +309,9 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->sat_ripemd160_callback) {
             return miniscript::Availability::NO;
         }
+        if (path && !path->hashes.count({miniscript::Fragment::RIPEMD160, hash})) {
+            return miniscript::Availability::NO;
+        }
 
         uint8_t* preimage_out = nullptr;
         size_t preimage_len = 0;
@@ -230,14 +326,14 @@ struct CallbackSatisfier {
 
         if (avail == MINISCRIPT_AVAILABILITY_YES && preimage_out && preimage_len > 0) {
             preimage.assign(preimage_out, preimage_out + preimage_len);
-            free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::YES;
         } else if (avail == MINISCRIPT_AVAILABILITY_MAYBE) {
-            if (preimage_out) free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::MAYBE;
         }
 
-        if (preimage_out) free(preimage_out);
+        FreePreimage(preimage_out, preimage_len);
         return miniscript::Availability::NO;
     }
 
@@ -245,6 +341,9 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->sat_hash256_callback) {
             return miniscript::Availability::NO;
         }
+        if (path && !path->hashes.count({miniscript::Fragment::HASH256, hash})) {
+            return miniscript::Availability::NO;
+        }
 
         uint8_t* preimage_out = nullptr;
         size_t preimage_len = 0;
@@ -259,14 +358,14 @@ struct CallbackSatisfier {
 
         if (avail == MINISCRIPT_AVAILABILITY_YES && preimage_out && preimage_len > 0) {
             preimage.assign(preimage_out, preimage_out + preimage_len);
-            free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::YES;
         } else if (avail == MINISCRIPT_AVAILABILITY_MAYBE) {
-            if (preimage_out) free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::MAYBE;
         }
 
-        if (preimage_out) free(preimage_out);
+        FreePreimage(preimage_out, preimage_len);
         return miniscript::Availability::NO;
     }
 
@@ -274,6 +373,9 @@ struct CallbackSatisfier {
         if (!callbacks || !callbacks->sat_hash160_callback) {
             return miniscript::Availability::NO;
         }
+        if (path && !path->hashes.count({miniscript::Fragment::HASH160, hash})) {
+            return miniscript::Availability::NO;
+        }
 
         uint8_t* preimage_out = nullptr;
         size_t preimage_len = 0;
@@ -288,18 +390,76 @@ struct CallbackSatisfier {
 
         if (avail == MINISCRIPT_AVAILABILITY_YES && preimage_out && preimage_len > 0) {
             preimage.assign(preimage_out, preimage_out + preimage_len);
-            free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::YES;
         } else if (avail == MINISCRIPT_AVAILABILITY_MAYBE) {
-            if (preimage_out) free(preimage_out);
+            FreePreimage(preimage_out, preimage_len);
             return miniscript::Availability::MAYBE;
         }
 
-        if (preimage_out) free(preimage_out);
+        FreePreimage(preimage_out, preimage_len);
         return miniscript::Availability::NO;
     }
 };
 
+// Satisfier that reports a signature as possibly available (MAYBE) for a
+// fixed set of keys and nothing else. Keys match by the name written in the
+// miniscript or by the bytes passed to the sign callback.
+struct KeyAvailabilitySatisfier : CallbackSatisfier {
+    std::set<std::vector<unsigned char>> keys;
+
+    KeyAvailabilitySatisfier(std::set<std::vector<unsigned char>> available, miniscript::MiniscriptContext ctx)
+        : CallbackSatisfier(nullptr, ctx), keys(std::move(available)) {}
+
+    miniscript::Availability Sign(const StringKey& key, std::vector<unsigned char>& sig) const {
+        std::vector<unsigned char> name(key.str.begin(), key.str.end());
+        if (!keys.count(name) && !keys.count(ToPKBytes(key))) {
+            return miniscript::Availability::NO;
+        }
+        // Dummy signature of typical size, as for MAYBE in CallbackSatisfier
+        sig.assign(72, 0x30);
+        return miniscript::Availability::MAYBE;
+    }
+};
+
+// Satisfier that reports exactly the data of one spend path as possibly
+// available (MAYBE): signatures for its keys, preimages for its hashes and
+// its timelocks as satisfied
+struct PathDataSatisfier : CallbackSatisfier {
+    PathDataSatisfier(const SpendPath& spend_path, miniscript::MiniscriptContext ctx)
+        : CallbackSatisfier(nullptr, ctx) {
+        path = &spend_path;
+    }
+
+    miniscript::Availability Sign(const StringKey& key, std::vector<unsigned char>& sig) const {
+        if (!path->keys.count(key.str)) return miniscript::Availability::NO;
+        sig.assign(72, 0x30);
+        return miniscript::Availability::MAYBE;
+    }
+
+    bool CheckAfter(uint32_t value) const { return path->after.count(value) > 0; }
+    bool CheckOlder(uint32_t value) const { return path->older.count(value) > 0; }
+
+    miniscript::Availability SatHash(miniscript::Fragment fragment, const std::vector<unsigned char>& hash,
+                                     std::vector<unsigned char>& preimage) const {
+        if (!path->hashes.count({fragment, hash})) return miniscript::Availability::NO;
+        preimage.assign(32, 0);
+        return miniscript::Availability::MAYBE;
+    }
+    miniscript::Availability SatSHA256(const std::vector<unsigned char>& hash, std::vector<unsigned char>& preimage) const {
+        return SatHash(miniscript::Fragment::SHA256, hash, preimage);
+    }
+    miniscript::Availability SatRIPEMD160(const std::vector<unsigned char>& hash, std::vector<unsigned char>& preimage) const {
+        return SatHash(miniscript::Fragment::RIPEMD160, hash, preimage);
+    }
+    miniscript::Availability SatHASH256(const std::vector<unsigned char>& hash, std::vector<unsigned char>& preimage) const {
+        return SatHash(miniscript::Fragment::HASH256, hash, preimage);
+    }
+    miniscript::Availability SatHASH160(const std::vector<unsigned char>& hash, std::vector<unsigned char>& preimage) const {
+        return SatHash(miniscript::Fragment::HASH160, hash, preimage);
+    }
+};
+
 struct MiniscriptNode {
     miniscript::NodeRef<StringKey> node;
     miniscript::MiniscriptContext ctx;
@@ -322,15 +482,500 @@ static char* strdup_safe(const std::string& str) {
     return strdup_safe(str.c_str());
 }
 
+using StringNode = miniscript::Node<StringKey>;
+
+static MiniscriptFragment ToFfiFragment(miniscript::Fragment fragment) {
+    using miniscript::Fragment;
+    switch (fragment) {
+        case Fragment::JUST_0: return MINISCRIPT_FRAGMENT_JUST_0;
+        case Fragment::JUST_1: return MINISCRIPT_FRAGMENT_JUST_1;
+        case Fragment::PK_K: return MINISCRIPT_FRAGMENT_PK_K;
+        case Fragment::PK_H: return MINISCRIPT_FRAGMENT_PK_H;
+        case Fragment::OLDER: return MINISCRIPT_FRAGMENT_OLDER;
+        case Fragment::AFTER: return MINISCRIPT_FRAGMENT_AFTER;
+        case Fragment::SHA256: return MINISCRIPT_FRAGMENT_SHA256;
+        case Fragment::HASH256: return MINISCRIPT_FRAGMENT_HASH256;
+        case Fragment::RIPEMD160: return MINISCRIPT_FRAGMENT_RIPEMD160;
+        case Fragment::HASH160: return MINISCRIPT_FRAGMENT_HASH160;
+        case Fragment::WRAP_A: return MINISCRIPT_FRAGMENT_WRAP_A;
+        case Fragment::WRAP_S: return MINISCRIPT_FRAGMENT_WRAP_S;
+        case Fragment::WRAP_C: return MINISCRIPT_FRAGMENT_WRAP_C;
+        case Fragment::WRAP_D: return MINISCRIPT_FRAGMENT_WRAP_D;
+        case Fragment::WRAP_V: return MINISCRIPT_FRAGMENT_WRAP_V;
+        case Fragment::WRAP_J: return MINISCRIPT_FRAGMENT_WRAP_J;
+        case Fragment::WRAP_N: return MINISCRIPT_FRAGMENT_WRAP_N;
+        case Fragment::AND_V: return MINISCRIPT_FRAGMENT_AND_V;
+        case Fragment::AND_B: return MINISCRIPT_FRAGMENT_AND_B;
+        case Fragment::OR_B: return MINISCRIPT_FRAGMENT_OR_B;
+        case Fragment::OR_C: return MINISCRIPT_FRAGMENT_OR_C;
+        case Fragment::OR_D: return MINISCRIPT_FRAGMENT_OR_D;
+        case Fragment::OR_I: return MINISCRIPT_FRAGMENT_OR_I;
+        case Fragment::ANDOR: return MINISCRIPT_FRAGMENT_ANDOR;
+        case Fragment::THRESH: return MINISCRIPT_FRAGMENT_THRESH;
+        case Fragment::MULTI: return MINISCRIPT_FRAGMENT_MULTI;
+        case Fragment::MULTI_A: return MINISCRIPT_FRAGMENT_MULTI_A;
+    }
+    return MINISCRIPT_FRAGMENT_JUST_0;
+}
+
+// Classify why a parsed node is not valid: a type error, or (when it does
+// type-check) a script exceeding the size limit for its context
+static int InvalidNodeErrorCode(const StringNode& node) {
+    using namespace miniscript;
+    if (node.GetType() == ""_mst) {
+        return MINISCRIPT_ERROR_TYPE;
+    }
+    return MINISCRIPT_ERROR_RESOURCE_LIMIT;
+}
+
+// Collect the nodes of a miniscript tree in pre-order. Uses an explicit
+// stack, as a parsed tree can be deeper than the native stack allows.
+static void FlattenTree(const StringNode& root, std::vector<const StringNode*>& out) {
+    std::vector<const StringNode*> stack{&root};
+    while (!stack.empty()) {
+        const StringNode* node = stack.back();
+        stack.pop_back();
+        out.push_back(node);
+        // Pushed in reverse, so the first sub-expression is visited first
+        for (auto sub = node->subs.rbegin(); sub != node->subs.rend(); ++sub) {
+            stack.push_back(sub->get());
+        }
+    }
+}
+
+// Overflow-checked arithmetic for SpendPathCount
+static std::optional<size_t> CheckedAdd(size_t a, size_t b) {
+    if (a > SIZE_MAX - b) return std::nullopt;
+    return a + b;
+}
+
+static std::optional<size_t> CheckedMul(size_t a, size_t b) {
+    if (a != 0 && b > SIZE_MAX / a) return std::nullopt;
+    return a * b;
+}
+
+// Number of ways to pick k of the given sub-expressions, weighted by the
+// number of spending paths of each (the k-th elementary symmetric sum)
+static std::optional<size_t> ThresholdPathCount(const std::vector<size_t>& counts, uint32_t k) {
+    std::vector<size_t> ways(k + 1, 0);
+    ways[0] = 1;
+    for (size_t count : counts) {
+        for (size_t j = k; j > 0; --j) {
+            auto extended = CheckedMul(ways[j - 1], count);
+            if (!extended) return std::nullopt;
+            auto total = CheckedAdd(ways[j], *extended);
+            if (!total) return std::nullopt;
+            ways[j] = *total;
+        }
+    }
+    return ways[k];
+}
+
+// Count the spending paths of a node from those of its sub-expressions
+static std::optional<size_t> NodePathCount(const StringNode& node, const std::vector<size_t>& subs) {
+    using miniscript::Fragment;
+
+    switch (node.fragment) {
+        case Fragment::JUST_0:
+            return 0;
+        case Fragment::JUST_1:
+        case Fragment::PK_K:
+        case Fragment::PK_H:
+        case Fragment::OLDER:
+        case Fragment::AFTER:
+        case Fragment::SHA256:
+        case Fragment::HASH256:
+        case Fragment::RIPEMD160:
+        case Fragment::HASH160:
+            return 1;
+        case Fragment::WRAP_A:
+        case Fragment::WRAP_S:
+        case Fragment::WRAP_C:
+        case Fragment::WRAP_D:
+        case Fragment::WRAP_V:
+        case Fragment::WRAP_J:
+        case Fragment::WRAP_N:
+            return subs[0];
+        case Fragment::AND_V:
+        case Fragment::AND_B:
+            return CheckedMul(subs[0], subs[1]);
+        case Fragment::OR_B:
+        case Fragment::OR_C:
+        case Fragment::OR_D:
+        case Fragment::OR_I:
+            return CheckedAdd(subs[0], subs[1]);
+        case Fragment::ANDOR: {
+            auto both = CheckedMul(subs[0], subs[1]);
+            if (!both) return std::nullopt;
+            return CheckedAdd(*both, subs[2]);
+        }
+        case Fragment::THRESH:
+            return ThresholdPathCount(subs, node.k);
+        case Fragment::MULTI:
+        case Fragment::MULTI_A:
+            return ThresholdPathCount(std::vector<size_t>(node.keys.size(), 1), node.k);
+    }
+    return std::nullopt;
+}
+
+// Count the distinct spending paths of a node: disjunctions add the paths
+// of their branches, conjunctions multiply them, and thresholds count every
+// combination of k satisfied sub-expressions. Returns nullopt on overflow.
+static std::optional<size_t> SpendPathCount(const StringNode& root) {
+    // Post-order walk with an explicit stack, as a parsed tree can be deeper
+    // than the native stack allows. Each entry holds a node and the number
+    // of its sub-expressions visited so far; their counts are on `counts`.
+    std::vector<std::pair<const StringNode*, size_t>> stack{{&root, 0}};
+    std::vector<size_t> counts;
+    while (!stack.empty()) {
+        const StringNode* node = stack.back().first;
+        size_t& visited = stack.back().second;
+        if (visited < node->subs.size()) {
+            stack.emplace_back(node->subs[visited++].get(), 0);
+            continue;
+        }
+        std::vector<size_t> subs(counts.end() - node->subs.size(), counts.end());
+        counts.resize(counts.size() - node->subs.size());
+        auto count = NodePathCount(*node, subs);
+        if (!count) return std::nullopt;
+        counts.push_back(*count);
+        stack.pop_back();
+    }
+    return counts.back();
+}
+
+// An optional witness size: the maximum of two sizes is valid if either is,
+// the sum only if both are (mirrors miniscript::internal::MaxInt)
+struct WitnessBound {
+    bool valid = false;
+    uint64_t value = 0;
+
+    WitnessBound() = default;
+    WitnessBound(uint64_t v) : valid(true), value(v) {}
+
+    friend WitnessBound operator+(const WitnessBound& a, const WitnessBound& b) {
+        if (!a.valid || !b.valid) return {};
+        return a.value + b.value;
+    }
+    friend WitnessBound operator|(const WitnessBound& a, const WitnessBound& b) {
+        if (!a.valid) return b;
+        if (!b.valid) return a;
+        return std::max(a.value, b.value);
+    }
+};
+
+// Maximum satisfaction and dissatisfaction witness sizes of a node
+using WitnessSizes = std::pair<WitnessBound, WitnessBound>;
+
+// The witness sizes of a node given those of its sub-expressions
+static WitnessSizes NodeWitnessSize(const StringNode& node, const std::vector<WitnessSizes>& subs,
+                                    uint64_t sig_size) {
+    using miniscript::Fragment;
+
+    const uint64_t pubkey_size = miniscript::IsTapscript(node.m_script_ctx) ? 1 + 32 : 1 + 33;
+    const uint64_t k = node.k;
+    const uint64_t n_keys = node.keys.size();
+
+    switch (node.fragment) {
+        case Fragment::JUST_0: return {{}, 0};
+        case Fragment::JUST_1:
+        case Fragment::OLDER:
+        case Fragment::AFTER: return {0, {}};
+        case Fragment::PK_K: return {sig_size, 1};
+        case Fragment::PK_H: return {sig_size + pubkey_size, 1 + pubkey_size};
+        case Fragment::SHA256:
+        case Fragment::RIPEMD160:
+        case Fragment::HASH256:
+        case Fragment::HASH160: return {1 + 32, {}};
+        case Fragment::ANDOR:
+            return {(subs[0].first + subs[1].first) | (subs[0].second + subs[2].first),
+                    subs[0].second + subs[2].second};
+        case Fragment::AND_V: return {subs[0].first + subs[1].first, {}};
+        case Fragment::AND_B: return {subs[0].first + subs[1].first, subs[0].second + subs[1].second};
+        case Fragment::OR_B:
+            return {(subs[0].second + subs[1].first) | (subs[0].first + subs[1].second),
+                    subs[0].second + subs[1].second};
+        case Fragment::OR_C: return {subs[0].first | (subs[0].second + subs[1].first), {}};
+        case Fragment::OR_D:
+            return {subs[0].first | (subs[0].second + subs[1].first), subs[0].second + subs[1].second};
+        case Fragment::OR_I:
+            return {(subs[0].first + 1 + 1) | (subs[1].first + 1), (subs[0].second + 1 + 1) | (subs[1].second + 1)};
+        case Fragment::MULTI: return {k * sig_size + 1, k + 1};
+        case Fragment::MULTI_A: return {k * sig_size + n_keys - k, n_keys};
+        case Fragment::WRAP_A:
+        case Fragment::WRAP_N:
+        case Fragment::WRAP_S:
+        case Fragment::WRAP_C: return subs[0];
+        case Fragment::WRAP_D: return {1 + 1 + subs[0].first, 1};
+        case Fragment::WRAP_V: return {subs[0].first, {}};
+        case Fragment::WRAP_J: return {subs[0].first, 1};
+        case Fragment::THRESH: {
+            // sats[j]: largest witness with exactly j of the subs satisfied
+            std::vector<WitnessBound> sats{WitnessBound{0}};
+            for (const auto& sub : subs) {
+                std::vector<WitnessBound> next{sats[0] + sub.second};
+                for (size_t j = 1; j < sats.size(); ++j) {
+                    next.push_back((sats[j] + sub.second) | (sats[j - 1] + sub.first));
+                }
+                next.push_back(sats.back() + sub.first);
+                sats = std::move(next);
+            }
+            if (k >= sats.size()) return {{}, {}};
+            return {sats[k], sats[0]};
+        }
+    }
+    return {{}, {}};
+}
+
+// Maximum satisfaction and dissatisfaction witness sizes of a node, as
+// computed by Bitcoin Core's CalcWitnessSize but with each signature taking
+// `sig_size` bytes (including its length prefix) instead of the standard size
+static WitnessSizes WitnessSizeWithSig(const StringNode& root, uint64_t sig_size) {
+    // Post-order walk with an explicit stack, as in SpendPathCount. The
+    // sizes of the sub-expressions visited so far are on `sizes`.
+    std::vector<std::pair<const StringNode*, size_t>> stack{{&root, 0}};
+    std::vector<WitnessSizes> sizes;
+    while (!stack.empty()) {
+        const StringNode* node = stack.back().first;
+        size_t& visited = stack.back().second;
+        if (visited < node->subs.size()) {
+            stack.emplace_back(node->subs[visited++].get(), 0);
+            continue;
+        }
+        std::vector<WitnessSizes> subs(sizes.end() - node->subs.size(), sizes.end());
+        sizes.resize(sizes.size() - node->subs.size());
+        sizes.push_back(NodeWitnessSize(*node, subs, sig_size));
+        stack.pop_back();
+    }
+    return sizes.back();
+}
+
+// Sub-expressions still to be visited by SelectSpendPath, each with the
+// index of the path to take through it
+using PathSelections = std::vector<std::pair<const StringNode*, size_t>>;
+
+// Select path `branch` of a conjunction of nodes; the first node's path
+// varies slowest
+static bool SelectProductPath(const std::vector<const StringNode*>& nodes, size_t branch, PathSelections& todo) {
+    std::vector<size_t> counts;
+    for (const StringNode* sub : nodes) {
+        auto count = SpendPathCount(*sub);
+        if (!count || *count == 0) return false;
+        counts.push_back(*count);
+    }
+    std::vector<size_t> indices(nodes.size());
+    for (size_t i = nodes.size(); i > 0; --i) {
+        indices[i - 1] = branch % counts[i - 1];
+        branch /= counts[i - 1];
+    }
+    if (branch != 0) return false;
+    for (size_t i = 0; i < nodes.size(); ++i) {
+        todo.emplace_back(nodes[i], indices[i]);
+    }
+    return true;
+}
+
+// Advance to the next k-combination of 0..n-1 in lexicographic order
+static bool NextCombination(std::vector<size_t>& indices, size_t n) {
+    size_t k = indices.size();
+    for (size_t i = k; i > 0; --i) {
+        if (indices[i - 1] < n - k + i - 1) {
+            ++indices[i - 1];
+            for (size_t j = i; j < k; ++j) indices[j] = indices[j - 1] + 1;
+            return true;
+        }
+    }
+    return false;
+}
+
+// Select path `branch` of a threshold: combinations of k sub-expressions in
+// lexicographic order, then the paths of the chosen sub-expressions
+static bool SelectThresholdPath(const std::vector<const StringNode*>& nodes, uint32_t k, size_t branch,
+                                PathSelections& todo) {
+    if (k == 0 || k > nodes.size()) return false;
+    std::vector<size_t> indices(k);
+    for (size_t i = 0; i < k; ++i) indices[i] = i;
+    do {
+        std::vector<const StringNode*> chosen;
+        size_t count = 1;
+        for (size_t i : indices) {
+            chosen.push_back(nodes[i]);
+            auto sub_count = SpendPathCount(*nodes[i]);
+            auto product = sub_count ? CheckedMul(count, *sub_count) : std::nullopt;
+            if (!product) return false;
+            count = *product;
+        }
+        if (branch < count) return SelectProductPath(chosen, branch, todo);
+        branch -= count;
+    } while (NextCombination(indices, nodes.size()));
+    return false;
+}
+
+// Collect what path `branch` of a node uses itself, and queue the paths to
+// take through its sub-expressions. Returns false if the index is out of
+// range.
+static bool SelectNodePath(const StringNode& node, size_t branch, SpendPath& path, PathSelections& todo) {
+    using miniscript::Fragment;
+
+    std::vector<const StringNode*> subs;
+    for (const auto& sub : node.subs) subs.push_back(sub.get());
+
+    switch (node.fragment) {
+        case Fragment::JUST_0:
+            return false;
+        case Fragment::JUST_1:
+            return branch == 0;
+        case Fragment::PK_K:
+        case Fragment::PK_H:
+            path.keys.insert(node.keys[0].str);
+            return branch == 0;
+        case Fragment::OLDER:
+            path.older.insert(node.k);
+            return branch == 0;
+        case Fragment::AFTER:
+            path.after.insert(node.k);
+            return branch == 0;
+        case Fragment::SHA256:
+        case Fragment::HASH256:
+        case Fragment::RIPEMD160:
+        case Fragment::HASH160:
+            path.hashes.emplace(node.fragment, node.data);
+            return branch == 0;
+        case Fragment::WRAP_A:
+        case Fragment::WRAP_S:
+        case Fragment::WRAP_C:
+        case Fragment::WRAP_D:
+        case Fragment::WRAP_V:
+        case Fragment::WRAP_J:
+        case Fragment::WRAP_N:
+            todo.emplace_back(subs[0], branch);
+            return true;
+        case Fragment::AND_V:
+        case Fragment::AND_B:
+            return SelectProductPath(subs, branch, todo);
+        case Fragment::OR_B:
+        case Fragment::OR_C:
+        case Fragment::OR_D:
+        case Fragment::OR_I: {
+            auto left = SpendPathCount(*subs[0]);
+            if (!left) return false;
+            if (branch < *left) {
+                todo.emplace_back(subs[0], branch);
+            } else {
+                todo.emplace_back(subs[1], branch - *left);
+            }
+            return true;
+        }
+        case Fragment::ANDOR: {
+            std::vector<const StringNode*> both{subs[0], subs[1]};
+            auto x = SpendPathCount(*subs[0]);
+            auto y = SpendPathCount(*subs[1]);
+            auto count = (x && y) ? CheckedMul(*x, *y) : std::nullopt;
+            if (!count) return false;
+            if (branch < *count) return SelectProductPath(both, branch, todo);
+            todo.emplace_back(subs[2], branch - *count);
+            return true;
+        }
+        case Fragment::THRESH:
+            return SelectThresholdPath(subs, node.k, branch, todo);
+        case Fragment::MULTI:
+        case Fragment::MULTI_A: {
+            size_t n = node.keys.size();
+            if (node.k == 0 || node.k > n) return false;
+            std::vector<size_t> indices(node.k);
+            for (size_t i = 0; i < node.k; ++i) indices[i] = i;
+            for (; branch > 0; --branch) {
+                if (!NextCombination(indices, n)) return false;
+            }
+            for (size_t i : indices) path.keys.insert(node.keys[i].str);
+            return true;
+        }
+    }
+    return false;
+}
+
+// Collect the keys, hashes and timelocks used by spend path `branch`,
+// numbering paths as SpendPathCount counts them: left disjunction branches
+// first, and the first sub-expression varying slowest in conjunctions and
+// thresholds. Returns false if the index is out of range.
+static bool SelectSpendPath(const StringNode& root, size_t branch, SpendPath& path) {
+    // A work list rather than recursion, as a parsed tree can be deeper than
+    // the native stack allows. What a path uses does not depend on the order
+    // its sub-expressions are visited in.
+    PathSelections todo{{&root, branch}};
+    while (!todo.empty()) {
+        auto [node, node_branch] = todo.back();
+        todo.pop_back();
+        if (!SelectNodePath(*node, node_branch, path, todo)) return false;
+    }
+    return true;
+}
+
+// Run a satisfaction and copy the witness stack into a SatisfactionResult
+static SatisfactionResult RunSatisfy(const MiniscriptNode* node, const CallbackSatisfier& satisfier,
+                                     bool nonmalleable) {
+    SatisfactionResult result = {MINISCRIPT_AVAILABILITY_NO, nullptr, nullptr, 0, nullptr};
+
+    try {
+        std::vector<std::vector<unsigned char>> stack;
+
+        miniscript::Availability avail = node->node->Satisfy(satisfier, stack, nonmalleable);
+
+        if (avail == miniscript::Availability::YES) {
+            result.availability = MINISCRIPT_AVAILABILITY_YES;
+        } else if (avail == miniscript::Availability::MAYBE) {
+            result.availability = MINISCRIPT_AVAILABILITY_MAYBE;
+        } else {
+            result.availability = MINISCRIPT_AVAILABILITY_NO;
+        }
+
+        // Copy the stack to the result
+        if (!stack.empty()) {
+            result.stack_count = stack.size();
+            result.stack = static_cast<uint8_t**>(malloc(sizeof(uint8_t*) * result.stack_count));
+            result.stack_sizes = static_cast<size_t*>(malloc(sizeof(size_t) * result.stack_count));
+
+            if (!result.stack || !result.stack_sizes) {
+                if (result.stack) free(result.stack);
+                if (result.stack_sizes) free(result.stack_sizes);
+                result.stack = nullptr;
+                result.stack_sizes = nullptr;
+                result.stack_count = 0;
+                result.error_message = strdup_safe("Memory allocation failed");
+                return result;
+            }
+
+            for (size_t i = 0; i < stack.size(); ++i) {
+                result.stack_sizes[i] = stack[i].size();
+                if (stack[i].empty()) {
+                    result.stack[i] = nullptr;
+                } else {
+                    result.stack[i] = static_cast<uint8_t*>(malloc(stack[i].size()));
+                    if (result.stack[i]) {
+                        memcpy(result.stack[i], stack[i].data(), stack[i].size());
+                    }
+                }
+            }
+        }
+
+    } catch (const std::exception& e) {
+        result.error_message = strdup_safe(e.what());
+    } catch (...) {
+        result.error_message = strdup_safe("Unknown error during satisfaction");
+    }
+
+    return result;
+}
+
 extern "C" {
 
-MiniscriptResult miniscript_from_string(const char* input,
-                                        MiniscriptContext ctx,
-                                        MiniscriptNode** out_node) {
-    MiniscriptResult result = {false, nullptr};
+static MiniscriptResult ParseString(const char* input, MiniscriptContext ctx,
+                                    MiniscriptNode** out_node, bool require_valid) {
+    MiniscriptResult result = {false, nullptr, MINISCRIPT_ERROR_NONE};
 
     if (!input || !out_node) {
         result.error_message = strdup_safe("Invalid arguments: null pointer");
+        result.error_code = MINISCRIPT_ERROR_INVALID_ARGUMENT;
         return result;
     }
 
@@ -346,6 +991,7 @@ MiniscriptResult miniscript_from_string(const char* input,
             break;
         default:
             result.error_message = strdup_safe("Invalid context");
+            result.error_code = MINISCRIPT_ERROR_INVALID_ARGUMENT;
             return result;
     }
 
@@ -356,12 +1002,20 @@ MiniscriptResult miniscript_from_string(const char* input,
         auto node = miniscript::FromString(input_str, key_ctx);
 
         if (!node) {
+            if (key_ctx.unsupported_key) {
+                // The message is the key itself, see MINISCRIPT_ERROR_UNKNOWN_KEY_FORMAT
+                result.error_message = strdup_safe(*key_ctx.unsupported_key);
+                result.error_code = MINISCRIPT_ERROR_UNKNOWN_KEY_FORMAT;
+                return result;
+            }
             result.error_message = strdup_safe("Failed to parse miniscript");
+            result.error_code = MINISCRIPT_ERROR_SYNTAX;
             return result;
         }
 
-        if (!node->IsValid()) {
+        if (require_valid && !node->IsValid()) {
             result.error_message = strdup_safe("Parsed miniscript is not valid");
+            result.error_code = InvalidNodeErrorCode(*node);
             return result;
         }
 
@@ -370,21 +1024,152 @@ MiniscriptResult miniscript_from_string(const char* input,
 
     } catch (const std::exception& e) {
         result.error_message = strdup_safe(e.what());
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     } catch (...) {
         result.error_message = strdup_safe("Unknown error during parsing");
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     }
 
     return result;
 }
 
+MiniscriptResult miniscript_from_string(const char* input,
+                                        MiniscriptContext ctx,
+                                        MiniscriptNode** out_node) {
+    return ParseString(input, ctx, out_node, true);
+}
+
+MiniscriptResult miniscript_from_string_permissive(const char* input,
+                                                   MiniscriptContext ctx,
+                                                   MiniscriptNode** out_node) {
+    return ParseString(input, ctx, out_node, false);
+}
+
+// Print one node without syntactic sugar, given its sub-expressions
+// already printed
+static std::optional<std::string> ExpandedBody(const StringNode& n, const std::vector<std::string>& subs,
+                                               const StringKeyContext& ctx) {
+    using miniscript::Fragment;
+
+    auto key = [&](const StringKey& k) { return ctx.ToString(k); };
+    // Write name(prefix,sub,sub,...), with prefix only if non-empty
+    auto call = [&](const char* name, const std::string& prefix = "") {
+        std::string s = std::string(name) + "(" + prefix;
+        for (size_t i = 0; i < subs.size(); ++i) {
+            if (i > 0 || !prefix.empty()) s += ",";
+            s += subs[i];
+        }
+        return s + ")";
+    };
+
+    switch (n.fragment) {
+        case Fragment::JUST_0: return "0";
+        case Fragment::JUST_1: return "1";
+        case Fragment::PK_K:
+        case Fragment::PK_H: {
+            auto k = key(n.keys[0]);
+            if (!k) return std::nullopt;
+            return std::string(n.fragment == Fragment::PK_K ? "pk_k(" : "pk_h(") + *k + ")";
+        }
+        case Fragment::OLDER: return "older(" + std::to_string(n.k) + ")";
+        case Fragment::AFTER: return "after(" + std::to_string(n.k) + ")";
+        case Fragment::SHA256: return "sha256(" + HexStr(n.data) + ")";
+        case Fragment::HASH256: return "hash256(" + HexStr(n.data) + ")";
+        case Fragment::RIPEMD160: return "ripemd160(" + HexStr(n.data) + ")";
+        case Fragment::HASH160: return "hash160(" + HexStr(n.data) + ")";
+        case Fragment::AND_V: return call("and_v");
+        case Fragment::AND_B: return call("and_b");
+        case Fragment::OR_B: return call("or_b");
+        case Fragment::OR_C: return call("or_c");
+        case Fragment::OR_D: return call("or_d");
+        case Fragment::OR_I: return call("or_i");
+        case Fragment::ANDOR: return call("andor");
+        case Fragment::THRESH: return call("thresh", std::to_string(n.k));
+        case Fragment::MULTI:
+        case Fragment::MULTI_A: {
+            std::string s = std::string(n.fragment == Fragment::MULTI ? "multi(" : "multi_a(") + std::to_string(n.k);
+            for (const auto& k : n.keys) {
+                auto str = key(k);
+                if (!str) return std::nullopt;
+                s += "," + *str;
+            }
+            return s + ")";
+        }
+        default: break;
+    }
+    return std::nullopt;
+}
+
+// Print a node without the syntactic sugar of Core's ToString: pk/pkh stay
+// c:pk_k/c:pk_h, t:/l:/u: stay and_v(X,1)/or_i(0,X)/or_i(X,0), and and_n
+// stays andor(X,Y,0). Consecutive wrappers are still written together.
+static std::optional<std::string> ExpandedString(const StringNode& root, const StringKeyContext& ctx) {
+    using miniscript::Fragment;
+
+    // A node below its run of wrappers, the "wrappers:" prefix of that run,
+    // and the number of its sub-expressions printed so far
+    struct Frame {
+        const StringNode* node;
+        std::string prefix;
+        size_t visited;
+    };
+    auto enter = [](const StringNode& node) {
+        std::string wrappers;
+        const StringNode* n = &node;
+        for (;;) {
+            char wrapper = 0;
+            switch (n->fragment) {
+                case Fragment::WRAP_A: wrapper = 'a'; break;
+                case Fragment::WRAP_S: wrapper = 's'; break;
+                case Fragment::WRAP_C: wrapper = 'c'; break;
+                case Fragment::WRAP_D: wrapper = 'd'; break;
+                case Fragment::WRAP_V: wrapper = 'v'; break;
+                case Fragment::WRAP_J: wrapper = 'j'; break;
+                case Fragment::WRAP_N: wrapper = 'n'; break;
+                default: break;
+            }
+            if (!wrapper) break;
+            wrappers += wrapper;
+            n = n->subs[0].get();
+        }
+        return Frame{n, wrappers.empty() ? "" : wrappers + ":", 0};
+    };
+
+    // Post-order walk with an explicit stack, as a parsed tree can be deeper
+    // than the native stack allows; printed sub-expressions are on `printed`
+    std::vector<Frame> stack{enter(root)};
+    std::vector<std::string> printed;
+    while (!stack.empty()) {
+        Frame& frame = stack.back();
+        if (frame.visited < frame.node->subs.size()) {
+            const StringNode& sub = *frame.node->subs[frame.visited++];
+            stack.push_back(enter(sub));
+            continue;
+        }
+        size_t count = frame.node->subs.size();
+        std::vector<std::string> subs(std::make_move_iterator(printed.end() - count),
+                                      std::make_move_iterator(printed.end()));
+        printed.resize(printed.size() - count);
+        auto body = ExpandedBody(*frame.node, subs, ctx);
+        if (!body) return std::nullopt;
+        printed.push_back(frame.prefix + *body);
+        stack.pop_back();
+    }
+    return printed.back();
+}
+
 char* miniscript_to_string(const MiniscriptNode* node) {
+    return miniscript_to_string_ex(node, false);
+}
+
+char* miniscript_to_string_ex(const MiniscriptNode* node, bool expand_sugar) {
     if (!node || !node->node) {
         return nullptr;
     }
 
     try {
         StringKeyContext key_ctx(node->ctx);
-        auto str = node->node->ToString(key_ctx);
+        auto str = expand_sugar ? ExpandedString(*node->node, key_ctx) : node->node->ToString(key_ctx);
         if (str) {
             return strdup_safe(*str);
         }
@@ -474,6 +1259,25 @@ bool miniscript_max_satisfaction_size(const MiniscriptNode* node, size_t* out_si
     return false;
 }
 
+bool miniscript_satisfaction_size_with_sig(const MiniscriptNode* node, size_t sig_len,
+                                           size_t* out_size) {
+    if (!node || !node->node || !out_size) {
+        return false;
+    }
+
+    try {
+        // Signatures are pushed with a one-byte length prefix
+        auto size = WitnessSizeWithSig(*node->node, uint64_t{sig_len} + 1).first;
+        if (!size.valid || size.value > SIZE_MAX) {
+            return false;
+        }
+        *out_size = static_cast<size_t>(size.value);
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
 bool miniscript_is_non_malleable(const MiniscriptNode* node) {
     if (!node || !node->node) {
         return false;
@@ -497,22 +1301,243 @@ bool miniscript_has_timelock_mix(const MiniscriptNode* node) {
     return !(node->node->GetType() << "k"_mst);
 }
 
-bool miniscript_is_valid_top_level(const MiniscriptNode* node) {
-    if (!node || !node->node) {
-        return false;
-    }
-    return node->node->IsValidTopLevel();
+// Check whether a conjunction of a and b mixes heights and times
+static void CheckTimelockPair(const StringNode& a, const StringNode& b, bool& mix_after, bool& mix_older) {
+    using namespace miniscript;
+    Type x = a.GetType();
+    Type y = b.GetType();
+    // 'i'/'j': contains an absolute time/height lock; 'g'/'h': relative time/height
+    if ((x << "i"_mst && y << "j"_mst) || (x << "j"_mst && y << "i"_mst)) mix_after = true;
+    if ((x << "g"_mst && y << "h"_mst) || (x << "h"_mst && y << "g"_mst)) mix_older = true;
 }
 
-bool miniscript_check_ops_limit(const MiniscriptNode* node) {
-    if (!node || !node->node) {
+bool miniscript_can_satisfy_with_keys(const MiniscriptNode* node, const uint8_t* const* keys,
+                                      const size_t* key_lens, size_t key_count) {
+    if (!node || !node->node || (key_count > 0 && (!keys || !key_lens))) {
         return false;
     }
-    return node->node->CheckOpsLimit();
-}
 
-bool miniscript_check_stack_size(const MiniscriptNode* node) {
-    if (!node || !node->node) {
+    try {
+        std::set<std::vector<unsigned char>> available;
+        for (size_t i = 0; i < key_count; ++i) {
+            if (keys[i]) available.emplace(keys[i], keys[i] + key_lens[i]);
+        }
+
+        KeyAvailabilitySatisfier satisfier(std::move(available), node->ctx);
+        std::vector<std::vector<unsigned char>> stack;
+        return node->node->Satisfy(satisfier, stack, false) != miniscript::Availability::NO;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool miniscript_spend_path_count(const MiniscriptNode* node, size_t* out_count) {
+    if (!node || !node->node || !out_count) {
+        return false;
+    }
+
+    try {
+        auto count = SpendPathCount(*node->node);
+        if (!count) {
+            return false;
+        }
+        *out_count = *count;
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool miniscript_required_signers(const MiniscriptNode* node, size_t max_paths, char*** out_keys,
+                                 size_t** out_set_sizes, size_t* out_set_count, bool* out_truncated) {
+    if (!node || !node->node || !out_keys || !out_set_sizes || !out_set_count || !out_truncated) {
+        return false;
+    }
+
+    *out_keys = nullptr;
+    *out_set_sizes = nullptr;
+    *out_set_count = 0;
+    *out_truncated = false;
+
+    try {
+        auto count = SpendPathCount(*node->node);
+        size_t paths = count ? *count : max_paths;
+        if (!count || paths > max_paths) {
+            paths = max_paths;
+            *out_truncated = true;
+        }
+
+        // Key sets of the paths that can be satisfied non-malleably with
+        // only their own data, in path order and without repeats
+        std::vector<std::set<std::string>> sets;
+        size_t key_count = 0;
+        for (size_t branch = 0; branch < paths; ++branch) {
+            SpendPath path;
+            if (!SelectSpendPath(*node->node, branch, path)) continue;
+
+            PathDataSatisfier satisfier(path, node->ctx);
+            std::vector<std::vector<unsigned char>> stack;
+            if (node->node->Satisfy(satisfier, stack, true) == miniscript::Availability::NO) continue;
+
+            if (std::find(sets.begin(), sets.end(), path.keys) == sets.end()) {
+                key_count += path.keys.size();
+                sets.push_back(std::move(path.keys));
+            }
+        }
+
+        if (sets.empty()) {
+            return true;
+        }
+
+        *out_set_sizes = static_cast<size_t*>(malloc(sizeof(size_t) * sets.size()));
+        if (key_count > 0) {
+            *out_keys = static_cast<char**>(calloc(key_count, sizeof(char*)));
+        }
+        if (!*out_set_sizes || (key_count > 0 && !*out_keys)) {
+            free(*out_set_sizes);
+            free(*out_keys);
+            *out_set_sizes = nullptr;
+            *out_keys = nullptr;
+            return false;
+        }
+
+        size_t k = 0;
+        for (size_t i = 0; i < sets.size(); ++i) {
+            (*out_set_sizes)[i] = sets[i].size();
+            for (const auto& key : sets[i]) (*out_keys)[k++] = strdup_safe(key);
+        }
+        *out_set_count = sets.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+void miniscript_free_signer_sets(char** keys, size_t* set_sizes, size_t set_count) {
+    if (keys && set_sizes) {
+        size_t k = 0;
+        for (size_t i = 0; i < set_count; ++i) {
+            for (size_t j = 0; j < set_sizes[i]; ++j) free(keys[k++]);
+        }
+    }
+    free(keys);
+    free(set_sizes);
+}
+
+bool miniscript_all_branch_timelocks(const MiniscriptNode* node, size_t max_paths,
+                                     MiniscriptBranchTimelock** out_locks, size_t* out_count) {
+    if (!node || !node->node || !out_locks || !out_count) {
+        return false;
+    }
+
+    *out_locks = nullptr;
+    *out_count = 0;
+
+    try {
+        auto paths = SpendPathCount(*node->node);
+        if (!paths || *paths > max_paths) {
+            return false;
+        }
+        if (*paths == 0) {
+            return true;
+        }
+
+        *out_locks = static_cast<MiniscriptBranchTimelock*>(calloc(*paths, sizeof(MiniscriptBranchTimelock)));
+        if (!*out_locks) {
+            return false;
+        }
+        for (size_t branch = 0; branch < *paths; ++branch) {
+            SpendPath path;
+            if (!SelectSpendPath(*node->node, branch, path)) {
+                free(*out_locks);
+                *out_locks = nullptr;
+                return false;
+            }
+
+            // The strictest lock of each kind is the one that must be met
+            MiniscriptBranchTimelock& locks = (*out_locks)[branch];
+            for (uint32_t k : path.after) {
+                uint32_t& out = k < LOCKTIME_THRESHOLD ? locks.after_height : locks.after_time;
+                out = std::max(out, k);
+            }
+            for (uint32_t k : path.older) {
+                uint32_t value = k & CTxIn::SEQUENCE_LOCKTIME_MASK;
+                uint32_t& out = (k & CTxIn::SEQUENCE_LOCKTIME_TYPE_FLAG) ? locks.older_time : locks.older_blocks;
+                out = std::max(out, value);
+            }
+        }
+        *out_count = *paths;
+        return true;
+    } catch (...) {
+        free(*out_locks);
+        *out_locks = nullptr;
+        return false;
+    }
+}
+
+void miniscript_free_branch_timelocks(MiniscriptBranchTimelock* locks) {
+    free(locks);
+}
+
+bool miniscript_timelock_mix_detail(const MiniscriptNode* node, bool* out_after, bool* out_older) {
+    if (!node || !node->node || !out_after || !out_older) {
+        return false;
+    }
+
+    try {
+        bool mix_after = false;
+        bool mix_older = false;
+
+        std::vector<const StringNode*> flat;
+        FlattenTree(*node->node, flat);
+
+        // Only conjunctions can mix timelocks: and_v, and_b, the X and Y of
+        // andor, and thresh with k > 1
+        for (const StringNode* n : flat) {
+            switch (n->fragment) {
+                case miniscript::Fragment::AND_V:
+                case miniscript::Fragment::AND_B:
+                case miniscript::Fragment::ANDOR:
+                    CheckTimelockPair(*n->subs[0], *n->subs[1], mix_after, mix_older);
+                    break;
+                case miniscript::Fragment::THRESH:
+                    if (n->k > 1) {
+                        for (size_t i = 0; i < n->subs.size(); ++i) {
+                            for (size_t j = i + 1; j < n->subs.size(); ++j) {
+                                CheckTimelockPair(*n->subs[i], *n->subs[j], mix_after, mix_older);
+                            }
+                        }
+                    }
+                    break;
+                default:
+                    break;
+            }
+        }
+
+        *out_after = mix_after;
+        *out_older = mix_older;
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+bool miniscript_is_valid_top_level(const MiniscriptNode* node) {
+    if (!node || !node->node) {
+        return false;
+    }
+    return node->node->IsValidTopLevel();
+}
+
+bool miniscript_check_ops_limit(const MiniscriptNode* node) {
+    if (!node || !node->node) {
+        return false;
+    }
+    return node->node->CheckOpsLimit();
+}
+
+bool miniscript_check_stack_size(const MiniscriptNode* node) {
+    if (!node || !node->node) {
         return false;
     }
     return node->node->CheckStackSize();
@@ -569,13 +1594,58 @@ bool miniscript_get_script_size(const MiniscriptNode* node, size_t* out_size) {
     return true;
 }
 
+uint32_t miniscript_max_script_size(MiniscriptContext ctx) {
+    if (ctx == MINISCRIPT_CONTEXT_TAPSCRIPT) {
+        return miniscript::internal::MaxScriptSize(miniscript::MiniscriptContext::TAPSCRIPT);
+    }
+    return miniscript::internal::MaxScriptSize(miniscript::MiniscriptContext::P2WSH);
+}
+
+bool miniscript_context_limits(MiniscriptContext ctx, MiniscriptContextLimits* out_limits) {
+    if (!out_limits) {
+        return false;
+    }
+    out_limits->max_script_size = miniscript_max_script_size(ctx);
+    if (ctx == MINISCRIPT_CONTEXT_TAPSCRIPT) {
+        // Tapscript sigops are bounded by the witness size instead of a count
+        out_limits->max_ops = 0;
+        out_limits->max_witness_items = 0;
+        out_limits->max_exec_stack_size = MAX_STACK_SIZE;
+    } else {
+        // As in Core's miniscript, the execution stack is only checked in Tapscript
+        out_limits->max_ops = MAX_OPS_PER_SCRIPT;
+        out_limits->max_witness_items = MAX_STANDARD_P2WSH_STACK_ITEMS;
+        out_limits->max_exec_stack_size = 0;
+    }
+    return true;
+}
+
+bool miniscript_key_bytes(const char* name, size_t name_len, MiniscriptContext ctx, uint8_t* out_key,
+                          size_t* out_len) {
+    if ((!name && name_len > 0) || !out_key || !out_len) {
+        return false;
+    }
+
+    try {
+        auto ms_ctx = ctx == MINISCRIPT_CONTEXT_TAPSCRIPT ? miniscript::MiniscriptContext::TAPSCRIPT
+                                                          : miniscript::MiniscriptContext::P2WSH;
+        auto key = KeyBytes(std::string(name ? name : "", name_len), ms_ctx);
+        memcpy(out_key, key.data(), key.size());
+        *out_len = key.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
 MiniscriptResult miniscript_from_script(const uint8_t* script, size_t script_len,
                                         MiniscriptContext ctx,
                                         MiniscriptNode** out_node) {
-    MiniscriptResult result = {false, nullptr};
+    MiniscriptResult result = {false, nullptr, MINISCRIPT_ERROR_NONE};
 
     if (!script || !out_node) {
         result.error_message = strdup_safe("Invalid arguments: null pointer");
+        result.error_code = MINISCRIPT_ERROR_INVALID_ARGUMENT;
         return result;
     }
 
@@ -591,6 +1661,7 @@ MiniscriptResult miniscript_from_script(const uint8_t* script, size_t script_len
             break;
         default:
             result.error_message = strdup_safe("Invalid context");
+            result.error_code = MINISCRIPT_ERROR_INVALID_ARGUMENT;
             return result;
     }
 
@@ -602,11 +1673,13 @@ MiniscriptResult miniscript_from_script(const uint8_t* script, size_t script_len
 
         if (!node) {
             result.error_message = strdup_safe("Failed to parse script as miniscript");
+            result.error_code = MINISCRIPT_ERROR_SCRIPT_DECODE;
             return result;
         }
 
         if (!node->IsValid()) {
             result.error_message = strdup_safe("Parsed miniscript is not valid");
+            result.error_code = InvalidNodeErrorCode(*node);
             return result;
         }
 
@@ -615,13 +1688,103 @@ MiniscriptResult miniscript_from_script(const uint8_t* script, size_t script_len
 
     } catch (const std::exception& e) {
         result.error_message = strdup_safe(e.what());
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     } catch (...) {
         result.error_message = strdup_safe("Unknown error during script parsing");
+        result.error_code = MINISCRIPT_ERROR_UNKNOWN;
     }
 
     return result;
 }
 
+bool miniscript_subexpressions(const MiniscriptNode* node, MiniscriptNode*** out_nodes, size_t* out_count) {
+    if (!node || !node->node || !out_nodes || !out_count) {
+        return false;
+    }
+
+    *out_nodes = nullptr;
+    *out_count = 0;
+
+    try {
+        std::vector<const StringNode*> flat;
+        FlattenTree(*node->node, flat);
+
+        // Copy each subtree by printing and reparsing it, since nodes own
+        // their children
+        StringKeyContext key_ctx(node->ctx);
+        std::vector<std::unique_ptr<MiniscriptNode>> copies;
+        for (const StringNode* sub : flat) {
+            auto str = sub->ToString(key_ctx);
+            if (!str) return false;
+            auto copy = miniscript::FromString(*str, key_ctx);
+            if (!copy) return false;
+            copies.push_back(std::make_unique<MiniscriptNode>(std::move(copy), node->ctx));
+        }
+
+        *out_nodes = static_cast<MiniscriptNode**>(malloc(sizeof(MiniscriptNode*) * copies.size()));
+        if (!*out_nodes) {
+            return false;
+        }
+        for (size_t i = 0; i < copies.size(); ++i) {
+            (*out_nodes)[i] = copies[i].release();
+        }
+        *out_count = copies.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+void miniscript_free_node_array(MiniscriptNode** nodes) {
+    free(nodes);
+}
+
+bool miniscript_subtree_strings(const MiniscriptNode* node, const size_t* positions, size_t count,
+                                char*** out_strings) {
+    if (!node || !node->node || (!positions && count > 0) || !out_strings) {
+        return false;
+    }
+
+    *out_strings = nullptr;
+    if (count == 0) {
+        return true;
+    }
+
+    try {
+        std::vector<const StringNode*> flat;
+        FlattenTree(*node->node, flat);
+
+        StringKeyContext key_ctx(node->ctx);
+        std::vector<std::string> strings;
+        strings.reserve(count);
+        for (size_t i = 0; i < count; ++i) {
+            if (positions[i] >= flat.size()) return false;
+            auto str = flat[positions[i]]->ToString(key_ctx);
+            if (!str) return false;
+            strings.push_back(std::move(*str));
+        }
+
+        *out_strings = static_cast<char**>(calloc(count, sizeof(char*)));
+        if (!*out_strings) {
+            return false;
+        }
+        for (size_t i = 0; i < count; ++i) {
+            (*out_strings)[i] = strdup_safe(strings[i]);
+        }
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+void miniscript_free_strings(char** strings, size_t count) {
+    if (!strings) return;
+    for (size_t i = 0; i < count; ++i) {
+        free(strings[i]);
+    }
+    free(strings);
+}
+
 MiniscriptNode* miniscript_find_insane_sub(const MiniscriptNode* node) {
     if (!node || !node->node) {
         return nullptr;
@@ -656,6 +1819,78 @@ bool miniscript_get_static_ops(const MiniscriptNode* node, uint32_t* out_ops) {
     return true;
 }
 
+bool miniscript_get_tree(const MiniscriptNode* node, MiniscriptTreeNode** out_nodes, size_t* out_count) {
+    if (!node || !node->node || !out_nodes || !out_count) {
+        return false;
+    }
+
+    *out_nodes = nullptr;
+    *out_count = 0;
+
+    try {
+        std::vector<const StringNode*> flat;
+        FlattenTree(*node->node, flat);
+
+        auto* nodes = static_cast<MiniscriptTreeNode*>(calloc(flat.size(), sizeof(MiniscriptTreeNode)));
+        if (!nodes) {
+            return false;
+        }
+
+        for (size_t i = 0; i < flat.size(); ++i) {
+            const StringNode& n = *flat[i];
+            MiniscriptTreeNode& out = nodes[i];
+            out.fragment = ToFfiFragment(n.fragment);
+            out.k = n.k;
+            out.child_count = n.subs.size();
+
+            if (!n.keys.empty()) {
+                out.keys = static_cast<char**>(calloc(n.keys.size(), sizeof(char*)));
+                if (!out.keys) {
+                    miniscript_free_tree(nodes, flat.size());
+                    return false;
+                }
+                out.key_count = n.keys.size();
+                for (size_t j = 0; j < n.keys.size(); ++j) {
+                    out.keys[j] = strdup_safe(n.keys[j].str);
+                }
+            }
+
+            if (!n.data.empty()) {
+                out.data = static_cast<uint8_t*>(malloc(n.data.size()));
+                if (!out.data) {
+                    miniscript_free_tree(nodes, flat.size());
+                    return false;
+                }
+                memcpy(out.data, n.data.data(), n.data.size());
+                out.data_len = n.data.size();
+            }
+        }
+
+        *out_nodes = nodes;
+        *out_count = flat.size();
+        return true;
+    } catch (...) {
+        return false;
+    }
+}
+
+void miniscript_free_tree(MiniscriptTreeNode* nodes, size_t count) {
+    if (!nodes) return;
+
+    for (size_t i = 0; i < count; ++i) {
+        if (nodes[i].keys) {
+            for (size_t j = 0; j < nodes[i].key_count; ++j) {
+                free(nodes[i].keys[j]);
+            }
+            free(nodes[i].keys);
+        }
+        if (nodes[i].data) {
+            free(nodes[i].data);
+        }
+    }
+    free(nodes);
+}
+
 SatisfactionResult miniscript_satisfy(
     const MiniscriptNode* node,
     const SatisfierCallbacks* callbacks,
@@ -673,56 +1908,128 @@ SatisfactionResult miniscript_satisfy(
         return result;
     }
 
+    return RunSatisfy(node, CallbackSatisfier(callbacks, node->ctx), nonmalleable);
+}
+
+SatisfactionResult miniscript_satisfy_branch(
+    const MiniscriptNode* node,
+    size_t branch,
+    const SatisfierCallbacks* callbacks,
+    bool nonmalleable
+) {
+    SatisfactionResult result = {MINISCRIPT_AVAILABILITY_NO, nullptr, nullptr, 0, nullptr};
+
+    if (!node || !node->node) {
+        result.error_message = strdup_safe("Invalid node: null pointer");
+        return result;
+    }
+
+    if (!callbacks) {
+        result.error_message = strdup_safe("Invalid callbacks: null pointer");
+        return result;
+    }
+
     try {
+        // Check the index up front: selecting a path walks the combinations
+        // of multi and thresh one at a time
+        auto count = SpendPathCount(*node->node);
+        SpendPath path;
+        if ((count && branch >= *count) || !SelectSpendPath(*node->node, branch, path)) {
+            result.error_message = strdup_safe("Spend path index out of range: " + std::to_string(branch));
+            return result;
+        }
+
         CallbackSatisfier satisfier(callbacks, node->ctx);
-        std::vector<std::vector<unsigned char>> stack;
+        satisfier.path = &path;
+        return RunSatisfy(node, satisfier, nonmalleable);
+    } catch (...) {
+        result.error_message = strdup_safe("Unknown error during satisfaction");
+    }
 
-        miniscript::Availability avail = node->node->Satisfy(satisfier, stack, nonmalleable);
+    return result;
+}
 
-        if (avail == miniscript::Availability::YES) {
-            result.availability = MINISCRIPT_AVAILABILITY_YES;
-        } else if (avail == miniscript::Availability::MAYBE) {
-            result.availability = MINISCRIPT_AVAILABILITY_MAYBE;
-        } else {
-            result.availability = MINISCRIPT_AVAILABILITY_NO;
+// Signature checker for running a witness without a spending transaction.
+// Signatures cannot be verified, so any signature of a length a real one can
+// have is accepted (a DER signature of 8 to 72 bytes plus the sighash byte,
+// or a 64-byte Schnorr signature with an optional sighash byte), and every
+// timelock is satisfied.
+class WitnessOnlyChecker final : public BaseSignatureChecker {
+public:
+    bool CheckECDSASignature(const std::vector<unsigned char>& sig, const std::vector<unsigned char>&,
+                             const CScript&, SigVersion) const override {
+        return sig.size() >= 9 && sig.size() <= 73;
+    }
+
+    bool CheckSchnorrSignature(std::span<const unsigned char> sig, std::span<const unsigned char>,
+                               SigVersion, ScriptExecutionData&, ScriptError*) const override {
+        return sig.size() == 64 || sig.size() == 65;
+    }
+
+    bool CheckLockTime(const CScriptNum&) const override { return true; }
+    bool CheckSequence(const CScriptNum&) const override { return true; }
+};
+
+bool miniscript_witness_executes(const MiniscriptNode* node, const uint8_t* const* elems,
+                                 const size_t* lens, size_t count, bool* out_valid) {
+    if (!node || !node->node || !out_valid || (count > 0 && (!elems || !lens))) {
+        return false;
+    }
+
+    try {
+        StringKeyContext key_ctx(node->ctx);
+        CScript script = node->node->ToScript(key_ctx);
+
+        std::vector<std::vector<unsigned char>> stack;
+        stack.reserve(count);
+        for (size_t i = 0; i < count; ++i) {
+            if (lens[i] > 0 && !elems[i]) return false;
+            stack.emplace_back(elems[i], elems[i] + lens[i]);
         }
 
-        // Copy the stack to the result
-        if (!stack.empty()) {
-            result.stack_count = stack.size();
-            result.stack = static_cast<uint8_t**>(malloc(sizeof(uint8_t*) * result.stack_count));
-            result.stack_sizes = static_cast<size_t*>(malloc(sizeof(size_t) * result.stack_count));
+        *out_valid = false;
+        for (const auto& elem : stack) {
+            if (elem.size() > MAX_SCRIPT_ELEMENT_SIZE) return true;
+        }
 
-            if (!result.stack || !result.stack_sizes) {
-                if (result.stack) free(result.stack);
-                if (result.stack_sizes) free(result.stack_sizes);
-                result.stack = nullptr;
-                result.stack_sizes = nullptr;
-                result.stack_count = 0;
-                result.error_message = strdup_safe("Memory allocation failed");
-                return result;
+        const bool tapscript = node->ctx == miniscript::MiniscriptContext::TAPSCRIPT;
+        unsigned int flags = SCRIPT_VERIFY_P2SH | SCRIPT_VERIFY_WITNESS | SCRIPT_VERIFY_MINIMALDATA |
+                             SCRIPT_VERIFY_MINIMALIF | SCRIPT_VERIFY_NULLFAIL |
+                             SCRIPT_VERIFY_CHECKLOCKTIMEVERIFY | SCRIPT_VERIFY_CHECKSEQUENCEVERIFY;
+        ScriptExecutionData execdata;
+        if (tapscript) {
+            flags |= SCRIPT_VERIFY_TAPROOT;
+            execdata.m_codeseparator_pos_init = true;
+            execdata.m_codeseparator_pos = 0xFFFFFFFF;
+            execdata.m_annex_init = true;
+            execdata.m_annex_present = false;
+            execdata.m_tapleaf_hash_init = true;
+            execdata.m_tapleaf_hash = ComputeTapleafHash(TAPROOT_LEAF_TAPSCRIPT, script);
+            // The signature budget of BIP 342 scales with the witness size:
+            // the stack, the script and the smallest control block
+            int64_t witness_size = GetSizeOfCompactSize(stack.size() + 2);
+            for (const auto& elem : stack) {
+                witness_size += GetSizeOfCompactSize(elem.size()) + elem.size();
             }
+            witness_size += GetSizeOfCompactSize(script.size()) + script.size();
+            witness_size += GetSizeOfCompactSize(TAPROOT_CONTROL_BASE_SIZE) + TAPROOT_CONTROL_BASE_SIZE;
+            execdata.m_validation_weight_left_init = true;
+            execdata.m_validation_weight_left = witness_size + VALIDATION_WEIGHT_OFFSET;
+        }
 
-            for (size_t i = 0; i < stack.size(); ++i) {
-                result.stack_sizes[i] = stack[i].size();
-                if (stack[i].empty()) {
-                    result.stack[i] = nullptr;
-                } else {
-                    result.stack[i] = static_cast<uint8_t*>(malloc(stack[i].size()));
-                    if (result.stack[i]) {
-                        memcpy(result.stack[i], stack[i].data(), stack[i].size());
-                    }
-                }
-            }
+        const SigVersion sigversion = tapscript ? SigVersion::TAPSCRIPT : SigVersion::WITNESS_V0;
+        WitnessOnlyChecker checker;
+        ScriptError error;
+        if (!EvalScript(stack, script, flags, checker, sigversion, execdata, &error)) {
+            return true;
         }
 
-    } catch (const std::exception& e) {
-        result.error_message = strdup_safe(e.what());
+        // Witness scripts must leave exactly one true element (BIP 141)
+        *out_valid = stack.size() == 1 && CastToBool(stack.back());
+        return true;
     } catch (...) {
-        result.error_message = strdup_safe("Unknown error during satisfaction");
+        return false;
     }
-
-    return result;
 }
 
 void miniscript_satisfaction_result_free(SatisfactionResult* result) {
@@ -731,6 +2038,8 @@ void miniscript_satisfaction_result_free(SatisfactionResult* result) {
     if (result->stack) {
         for (size_t i = 0; i < result->stack_count; ++i) {
             if (result->stack[i]) {
+                // Witness elements may hold preimages
+                if (result->stack_sizes) memory_cleanse(result->stack[i], result->stack_sizes[i]);
                 free(result->stack[i]);
             }
         }
@@ -767,4 +2076,13 @@ const char* miniscript_version(void) {
     return VERSION_STRING;
 }
 
+const char* miniscript_bitcoin_core_version(void) {
+    return BITCOIN_CORE_VERSION_STRING;
+}
+
+const char* miniscript_bitcoin_core_commit(void) {
+    const char* commit = BITCOIN_CORE_COMMIT_STRING;
+    return commit[0] ? commit : nullptr;
+}
+
 }
@@ -20,11 +20,58 @@ typedef enum {
   MINISCRIPT_AVAILABILITY_MAYBE = 2
 } MiniscriptAvailability;
 
+// Fragment types, mirroring miniscript::Fragment
+typedef enum {
+  MINISCRIPT_FRAGMENT_JUST_0 = 0,
+  MINISCRIPT_FRAGMENT_JUST_1 = 1,
+  MINISCRIPT_FRAGMENT_PK_K = 2,
+  MINISCRIPT_FRAGMENT_PK_H = 3,
+  MINISCRIPT_FRAGMENT_OLDER = 4,
+  MINISCRIPT_FRAGMENT_AFTER = 5,
+  MINISCRIPT_FRAGMENT_SHA256 = 6,
+  MINISCRIPT_FRAGMENT_HASH256 = 7,
+  MINISCRIPT_FRAGMENT_RIPEMD160 = 8,
+  MINISCRIPT_FRAGMENT_HASH160 = 9,
+  MINISCRIPT_FRAGMENT_WRAP_A = 10,
+  MINISCRIPT_FRAGMENT_WRAP_S = 11,
+  MINISCRIPT_FRAGMENT_WRAP_C = 12,
+  MINISCRIPT_FRAGMENT_WRAP_D = 13,
+  MINISCRIPT_FRAGMENT_WRAP_V = 14,
+  MINISCRIPT_FRAGMENT_WRAP_J = 15,
+  MINISCRIPT_FRAGMENT_WRAP_N = 16,
+  MINISCRIPT_FRAGMENT_AND_V = 17,
+  MINISCRIPT_FRAGMENT_AND_B = 18,
+  MINISCRIPT_FRAGMENT_OR_B = 19,
+  MINISCRIPT_FRAGMENT_OR_C = 20,
+  MINISCRIPT_FRAGMENT_OR_D = 21,
+  MINISCRIPT_FRAGMENT_OR_I = 22,
+  MINISCRIPT_FRAGMENT_ANDOR = 23,
+  MINISCRIPT_FRAGMENT_THRESH = 24,
+  MINISCRIPT_FRAGMENT_MULTI = 25,
+  MINISCRIPT_FRAGMENT_MULTI_A = 26
+} MiniscriptFragment;
+
+// Stable error codes reported in MiniscriptResult and DescriptorResult.
+// Values never change meaning across releases; new codes may be added.
+typedef enum {
+  MINISCRIPT_ERROR_NONE = 0,               // No error
+  MINISCRIPT_ERROR_UNKNOWN = 1,            // Unclassified failure
+  MINISCRIPT_ERROR_INVALID_ARGUMENT = 2,   // Null pointer or invalid context
+  MINISCRIPT_ERROR_SYNTAX = 3,             // Input could not be parsed
+  MINISCRIPT_ERROR_TYPE = 4,               // Parsed but fails type checking
+  MINISCRIPT_ERROR_RESOURCE_LIMIT = 5,     // Exceeds script size limits
+  MINISCRIPT_ERROR_UNKNOWN_KEY_FORMAT = 6, // A descriptor key in a bare miniscript; the
+                                           // error message is the key as written
+  MINISCRIPT_ERROR_CHECKSUM = 7,           // Descriptor checksum mismatch
+  MINISCRIPT_ERROR_SCRIPT_DECODE = 8       // Script is not a miniscript encoding
+} MiniscriptErrorCode;
+
 typedef struct MiniscriptNode MiniscriptNode;
 
 typedef struct {
   bool success;
   char *error_message;
+  int error_code;  // MiniscriptErrorCode
 } MiniscriptResult;
 
 // Satisfaction result structure
@@ -36,6 +83,33 @@ typedef struct {
   char *error_message;  // Error message if any
 } SatisfactionResult;
 
+// A single node of a flattened miniscript tree (see miniscript_get_tree)
+typedef struct {
+  MiniscriptFragment fragment;
+  uint32_t k;           // Threshold (THRESH/MULTI/MULTI_A) or timelock (OLDER/AFTER)
+  size_t child_count;   // Number of direct children, which follow in pre-order
+  char **keys;          // Key names (PK_K/PK_H/MULTI/MULTI_A)
+  size_t key_count;     // Number of keys
+  uint8_t *data;        // Hash digest (SHA256/HASH256/RIPEMD160/HASH160)
+  size_t data_len;      // Length of the hash digest
+} MiniscriptTreeNode;
+
+// Consensus and standardness limits of a context (0 means no such limit)
+typedef struct {
+  uint32_t max_script_size;       // Maximum script size in bytes
+  uint32_t max_ops;               // Maximum non-push opcodes (P2WSH only)
+  uint32_t max_witness_items;     // Standard witness stack items (P2WSH only)
+  uint32_t max_exec_stack_size;   // Execution stack elements (Tapscript only)
+} MiniscriptContextLimits;
+
+// Strictest timelocks of a spend path (0 means the path has none of that kind)
+typedef struct {
+  uint32_t after_height;  // Absolute lock by block height
+  uint32_t after_time;    // Absolute lock by time
+  uint32_t older_blocks;  // Relative lock in blocks
+  uint32_t older_time;    // Relative lock in 512-second intervals
+} MiniscriptBranchTimelock;
+
 // Callback function types for the Satisfier
 // Returns MiniscriptAvailability and fills sig with signature bytes
 typedef MiniscriptAvailability (*SignCallback)(
@@ -74,8 +148,19 @@ typedef struct {
 MiniscriptResult miniscript_from_string(const char *input,
                                         MiniscriptContext ctx,
                                         MiniscriptNode **out_node);
+// Like miniscript_from_string(), but return the node even if it fails type
+// checking. Such nodes are for inspection only and must not be turned into
+// scripts or satisfied
+MiniscriptResult miniscript_from_string_permissive(const char *input,
+                                                   MiniscriptContext ctx,
+                                                   MiniscriptNode **out_node);
 
 char *miniscript_to_string(const MiniscriptNode *node);
+// Like miniscript_to_string(), but with expand_sugar set the string keeps
+// the underlying fragments: c:pk_k(A) instead of pk(A), c:pk_h(A) instead of
+// pkh(A), and_v(X,1), or_i(0,X), or_i(X,0) and andor(X,Y,0) instead of t:X,
+// l:X, u:X and and_n(X,Y)
+char *miniscript_to_string_ex(const MiniscriptNode *node, bool expand_sugar);
 
 bool miniscript_to_script(const MiniscriptNode *node, uint8_t **out_script,
                           size_t *out_len);
@@ -89,10 +174,43 @@ char *miniscript_get_type(const MiniscriptNode *node);
 bool miniscript_max_satisfaction_size(const MiniscriptNode *node,
                                       size_t *out_size);
 
+// Like miniscript_max_satisfaction_size(), but with every signature taking
+// sig_len bytes instead of the standard ECDSA (72) or Schnorr (65) size
+bool miniscript_satisfaction_size_with_sig(const MiniscriptNode *node, size_t sig_len,
+                                           size_t *out_size);
+
 // Additional property accessors
 bool miniscript_is_non_malleable(const MiniscriptNode *node);
 bool miniscript_needs_signature(const MiniscriptNode *node);
 bool miniscript_has_timelock_mix(const MiniscriptNode *node);
+// Report where heightlocks and timelocks are mixed: within after() (absolute)
+// and/or within older() (relative) conjunctions
+bool miniscript_timelock_mix_detail(const MiniscriptNode *node, bool *out_after,
+                                    bool *out_older);
+// Check whether any spending path could be satisfied holding only the given
+// keys (matched by name or public key bytes), without signing. Timelocks and
+// hash preimages are treated as unavailable.
+bool miniscript_can_satisfy_with_keys(const MiniscriptNode *node, const uint8_t *const *keys,
+                                      const size_t *key_lens, size_t key_count);
+// Count the distinct spending paths (satisfaction branch combinations).
+// Returns false if the count overflows.
+bool miniscript_spend_path_count(const MiniscriptNode *node, size_t *out_count);
+// List the key sets of the spend paths that can be satisfied non-malleably
+// with only their own keys, hashes and timelocks, examining at most
+// max_paths paths (out_truncated is set if there are more). Key names of all
+// sets are concatenated in out_keys; out_set_sizes holds the size of each of
+// the out_set_count sets. Free with miniscript_free_signer_sets().
+bool miniscript_required_signers(const MiniscriptNode *node, size_t max_paths, char ***out_keys,
+                                 size_t **out_set_sizes, size_t *out_set_count,
+                                 bool *out_truncated);
+void miniscript_free_signer_sets(char **keys, size_t *set_sizes, size_t set_count);
+// Get the strictest timelocks of every spend path, in the order of
+// miniscript_spend_path_count(). out_locks is NULL if there are no paths.
+// Returns false if there are more than max_paths paths. Free with
+// miniscript_free_branch_timelocks().
+bool miniscript_all_branch_timelocks(const MiniscriptNode *node, size_t max_paths,
+                                     MiniscriptBranchTimelock **out_locks, size_t *out_count);
+void miniscript_free_branch_timelocks(MiniscriptBranchTimelock *locks);
 bool miniscript_is_valid_top_level(const MiniscriptNode *node);
 bool miniscript_check_ops_limit(const MiniscriptNode *node);
 bool miniscript_check_stack_size(const MiniscriptNode *node);
@@ -104,11 +222,38 @@ bool miniscript_get_stack_size(const MiniscriptNode *node, uint32_t *out_size);
 bool miniscript_get_exec_stack_size(const MiniscriptNode *node, uint32_t *out_size);
 bool miniscript_get_script_size(const MiniscriptNode *node, size_t *out_size);
 
+// Maximum miniscript script size for a context (P2WSH standardness limit, or
+// the implicit Tapscript bound derived from the maximum standard tx weight)
+uint32_t miniscript_max_script_size(MiniscriptContext ctx);
+// Get the limits Bitcoin Core checks for scripts of a context
+bool miniscript_context_limits(MiniscriptContext ctx, MiniscriptContextLimits *out_limits);
+// Get the public key a key name stands for in scripts of the context (33
+// bytes for P2WSH, 32 for Tapscript; out_key must hold 33). Hex keys of that
+// length decode as themselves. Any other name maps to the first
+// SHA256(name || counter), with a 4-byte little-endian counter from 0, that is
+// a valid x-only key, prefixed with 0x02 outside Tapscript.
+bool miniscript_key_bytes(const char *name, size_t name_len, MiniscriptContext ctx,
+                          uint8_t *out_key, size_t *out_len);
+
 // Parse from script
 MiniscriptResult miniscript_from_script(const uint8_t *script, size_t script_len,
                                         MiniscriptContext ctx,
                                         MiniscriptNode **out_node);
 
+// Copy every subtree, in pre-order starting with the node itself. Each
+// returned node is owned by the caller and freed with miniscript_node_free();
+// the array itself is freed with miniscript_free_node_array().
+bool miniscript_subexpressions(const MiniscriptNode *node, MiniscriptNode ***out_nodes,
+                               size_t *out_count);
+void miniscript_free_node_array(MiniscriptNode **nodes);
+
+// Print the subtrees at the given pre-order positions (as numbered by
+// miniscript_get_tree), in the order given, without copying them. The
+// array is freed with miniscript_free_strings().
+bool miniscript_subtree_strings(const MiniscriptNode *node, const size_t *positions,
+                                size_t count, char ***out_strings);
+void miniscript_free_strings(char **strings, size_t count);
+
 // Find the first insane sub-expression (returns null if none found or if node is sane)
 MiniscriptNode* miniscript_find_insane_sub(const MiniscriptNode *node);
 
@@ -118,6 +263,15 @@ bool miniscript_valid_satisfactions(const MiniscriptNode *node);
 // Get the static ops count (for Tapscript)
 bool miniscript_get_static_ops(const MiniscriptNode *node, uint32_t *out_ops);
 
+// Flatten the miniscript tree into an array of nodes in pre-order
+// (each node is followed by its children, left to right).
+// The array must be freed with miniscript_free_tree().
+bool miniscript_get_tree(const MiniscriptNode *node, MiniscriptTreeNode **out_nodes,
+                         size_t *out_count);
+
+// Free a tree returned by miniscript_get_tree()
+void miniscript_free_tree(MiniscriptTreeNode *nodes, size_t count);
+
 // Satisfaction function - produces a witness stack
 // Parameters:
 //   node: The miniscript node to satisfy
@@ -130,6 +284,26 @@ SatisfactionResult miniscript_satisfy(
     bool nonmalleable
 );
 
+// Satisfy using only spend path `branch` (numbered as counted by
+// miniscript_spend_path_count()): the callbacks are only asked for the keys,
+// hashes and timelocks that path uses. Fails if the index is out of range.
+SatisfactionResult miniscript_satisfy_branch(
+    const MiniscriptNode *node,
+    size_t branch,
+    const SatisfierCallbacks *callbacks,
+    bool nonmalleable
+);
+
+// Check whether a witness stack executes the miniscript successfully, by
+// running Bitcoin Core's script interpreter on it in the miniscript's
+// context. There is no spending transaction, so signatures are not verified:
+// any signature of a plausible length (9 to 73 bytes for ECDSA, 64 or 65 for
+// Schnorr) is accepted, and every timelock counts as satisfied. Sets
+// out_valid and returns true if the interpreter ran, false on invalid
+// arguments or if the script cannot be built.
+bool miniscript_witness_executes(const MiniscriptNode *node, const uint8_t *const *elems,
+                                 const size_t *lens, size_t count, bool *out_valid);
+
 // Free the satisfaction result
 void miniscript_satisfaction_result_free(SatisfactionResult *result);
 
@@ -141,6 +315,13 @@ void miniscript_free_bytes(uint8_t *bytes);
 
 const char *miniscript_version(void);
 
+// Version of the Bitcoin Core source the wrapper was built against (e.g. "v30.2"),
+// read from its CLIENT_VERSION_* settings
+const char *miniscript_bitcoin_core_version(void);
+
+// Git commit of that source, or NULL if it was not built from a git checkout
+const char *miniscript_bitcoin_core_commit(void);
+
 #ifdef __cplusplus
 }
 #endif
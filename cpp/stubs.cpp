@@ -204,7 +204,8 @@ static RegtestChainParams g_regtest_params;
 //
 // To prevent this, we use a mutex that must be held for the entire duration
 // of the parse operation (SelectParams + Parse), not just during SelectParams.
-// The mutex is acquired in descriptor_parse_with_network() in descriptor_wrapper.cpp.
+// The same holds for encoding keys and addresses (ToString, EncodeDestination).
+// The mutex is acquired through NetworkParamsLock in descriptor_wrapper.cpp.
 //
 // =============================================================================
 
@@ -266,22 +267,10 @@ void SelectParams(int network) {
 
 // Exported function to select chain parameters from Rust
 extern "C" void descriptor_select_params(DescriptorNetwork network) {
+    std::lock_guard<std::mutex> lock(g_params_mutex);
     SelectParams(static_cast<int>(network));
 }
 
-// Taproot hash stubs
-#include <uint256.h>
-
-uint256 ComputeTapbranchHash(std::span<const unsigned char> a, std::span<const unsigned char> b) {
-    // Stub - returns zero hash
-    return uint256();
-}
-
-uint256 ComputeTapleafHash(unsigned char leaf_version, std::span<const unsigned char> script) {
-    // Stub - returns zero hash
-    return uint256();
-}
-
 // Additional stubs for descriptor layer
 #include <pubkey.h>
 #include <addresstype.h>
@@ -0,0 +1,213 @@
+//! Typed construction of miniscript expressions.
+//!
+//! A [`MiniscriptBuilder`] is assembled from fragment constructors and then
+//! parsed with [`Miniscript::from_str()`](crate::Miniscript::from_str), so the
+//! result is checked by Bitcoin Core like any other input. Only the common
+//! fragments are covered; anything else can still be written as a string.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use miniscript_core_ffi::{Context, MiniscriptBuilder as B};
+//!
+//! let ms = B::and_v(B::v(B::pk("A")), B::older(100))
+//!     .build(Context::Wsh)
+//!     .unwrap();
+//! assert_eq!(ms.to_string().unwrap(), "and_v(v:pk(A),older(100))");
+//! ```
+
+use crate::{Context, Error, Miniscript};
+use std::fmt;
+
+/// A miniscript expression under construction.
+///
+/// Each constructor returns a builder for one fragment; combine them and
+/// call [`build()`](Self::build). Wrappers applied to an already wrapped
+/// expression are merged, so `v(s(pk("A")))` gives `vs:pk(A)`. Keys are
+/// written as given, without any checks until the expression is built.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MiniscriptBuilder {
+    expr: String,
+}
+
+impl MiniscriptBuilder {
+    fn leaf(name: &str, args: &[&str]) -> Self {
+        Self {
+            expr: format!("{name}({})", args.join(",")),
+        }
+    }
+
+    fn node(name: &str, subs: &[&Self]) -> Self {
+        let args: Vec<&str> = subs.iter().map(|sub| sub.expr.as_str()).collect();
+        Self::leaf(name, &args)
+    }
+
+    fn wrap(letter: char, sub: Self) -> Self {
+        // An existing wrapper prefix ends at the first ':', before any '('
+        let wrapped = match (sub.expr.find(':'), sub.expr.find('(')) {
+            (Some(colon), Some(paren)) => colon < paren,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        let expr = if wrapped {
+            format!("{letter}{}", sub.expr)
+        } else {
+            format!("{letter}:{}", sub.expr)
+        };
+        Self { expr }
+    }
+
+    /// `pk(key)`: a signature for `key`.
+    #[must_use]
+    pub fn pk(key: &str) -> Self {
+        Self::leaf("pk", &[key])
+    }
+
+    /// `pkh(key)`: a signature for `key`, committing only to its hash.
+    #[must_use]
+    pub fn pkh(key: &str) -> Self {
+        Self::leaf("pkh", &[key])
+    }
+
+    /// `multi(k,keys...)`: `k` of the keys sign (P2WSH only).
+    #[must_use]
+    pub fn multi(k: u32, keys: &[&str]) -> Self {
+        let k = k.to_string();
+        Self::leaf("multi", &[&[k.as_str()], keys].concat())
+    }
+
+    /// `multi_a(k,keys...)`: `k` of the keys sign (Tapscript only).
+    #[must_use]
+    pub fn multi_a(k: u32, keys: &[&str]) -> Self {
+        let k = k.to_string();
+        Self::leaf("multi_a", &[&[k.as_str()], keys].concat())
+    }
+
+    /// `after(n)`: an absolute timelock.
+    #[must_use]
+    pub fn after(n: u32) -> Self {
+        Self::leaf("after", &[&n.to_string()])
+    }
+
+    /// `older(n)`: a relative timelock.
+    #[must_use]
+    pub fn older(n: u32) -> Self {
+        Self::leaf("older", &[&n.to_string()])
+    }
+
+    /// `and_v(x,y)`
+    #[must_use]
+    pub fn and_v(x: Self, y: Self) -> Self {
+        Self::node("and_v", &[&x, &y])
+    }
+
+    /// `and_b(x,y)`
+    #[must_use]
+    pub fn and_b(x: Self, y: Self) -> Self {
+        Self::node("and_b", &[&x, &y])
+    }
+
+    /// `or_b(x,z)`
+    #[must_use]
+    pub fn or_b(x: Self, z: Self) -> Self {
+        Self::node("or_b", &[&x, &z])
+    }
+
+    /// `or_c(x,z)`
+    #[must_use]
+    pub fn or_c(x: Self, z: Self) -> Self {
+        Self::node("or_c", &[&x, &z])
+    }
+
+    /// `or_d(x,z)`
+    #[must_use]
+    pub fn or_d(x: Self, z: Self) -> Self {
+        Self::node("or_d", &[&x, &z])
+    }
+
+    /// `or_i(x,z)`
+    #[must_use]
+    pub fn or_i(x: Self, z: Self) -> Self {
+        Self::node("or_i", &[&x, &z])
+    }
+
+    /// `andor(x,y,z)`
+    #[must_use]
+    pub fn andor(x: Self, y: Self, z: Self) -> Self {
+        Self::node("andor", &[&x, &y, &z])
+    }
+
+    /// `thresh(k,subs...)`: `k` of the sub-expressions are satisfied.
+    #[must_use]
+    pub fn thresh(k: u32, subs: &[Self]) -> Self {
+        let k = Self {
+            expr: k.to_string(),
+        };
+        let args: Vec<&Self> = std::iter::once(&k).chain(subs).collect();
+        Self::node("thresh", &args)
+    }
+
+    /// `a:x`
+    #[must_use]
+    pub fn a(x: Self) -> Self {
+        Self::wrap('a', x)
+    }
+
+    /// `s:x`
+    #[must_use]
+    pub fn s(x: Self) -> Self {
+        Self::wrap('s', x)
+    }
+
+    /// `c:x`
+    #[must_use]
+    pub fn c(x: Self) -> Self {
+        Self::wrap('c', x)
+    }
+
+    /// `d:x`
+    #[must_use]
+    pub fn d(x: Self) -> Self {
+        Self::wrap('d', x)
+    }
+
+    /// `v:x`
+    #[must_use]
+    pub fn v(x: Self) -> Self {
+        Self::wrap('v', x)
+    }
+
+    /// `j:x`
+    #[must_use]
+    pub fn j(x: Self) -> Self {
+        Self::wrap('j', x)
+    }
+
+    /// `n:x`
+    #[must_use]
+    pub fn n(x: Self) -> Self {
+        Self::wrap('n', x)
+    }
+
+    /// Get the expression built so far.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    /// Parse the expression as a miniscript in `context`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Miniscript::from_str()`], e.g. when the
+    /// fragments do not type-check together or a key is invalid.
+    pub fn build(&self, context: Context) -> Result<Miniscript, Error> {
+        Miniscript::from_str(&self.expr, context)
+    }
+}
+
+impl fmt::Display for MiniscriptBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
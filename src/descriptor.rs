@@ -41,7 +41,9 @@
 //! }
 //! ```
 
-use crate::ffi;
+use crate::trace::ffi_span;
+use crate::{Availability, Context, Error, Miniscript, Satisfier, ffi};
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::ptr;
 
@@ -65,7 +67,10 @@ use std::ptr;
 /// // Use mainnet for production (xpub keys)
 /// let network = DescriptorNetwork::Mainnet;
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Networks are ordered in declaration order:
+/// `Mainnet < Testnet < Testnet4 < Signet < Regtest`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Network {
     /// Bitcoin mainnet.
     ///
@@ -98,7 +103,23 @@ pub enum Network {
 }
 
 impl Network {
+    /// Every network, in declaration order.
+    #[must_use]
+    pub const fn all() -> [Self; 5] {
+        [
+            Self::Mainnet,
+            Self::Testnet,
+            Self::Testnet4,
+            Self::Signet,
+            Self::Regtest,
+        ]
+    }
+
     /// Convert to the FFI network type.
+    ///
+    /// Testnet4 shares testnet3's key and address prefixes, so both select the
+    /// same chain parameters. The Rust-side [`Network`] is kept on the
+    /// descriptor, so this does not affect [`Descriptor::network`].
     const fn to_ffi(self) -> ffi::DescriptorNetwork {
         match self {
             Self::Mainnet => ffi::DescriptorNetwork::DESCRIPTOR_NETWORK_MAINNET,
@@ -109,6 +130,25 @@ impl Network {
     }
 }
 
+impl std::str::FromStr for Network {
+    type Err = String;
+
+    /// Parse a network from `"mainnet"`, `"testnet"`, `"testnet4"`, `"signet"`
+    /// or `"regtest"` (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "testnet4" => Ok(Self::Testnet4),
+            "signet" => Ok(Self::Signet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(format!(
+                "unknown network '{s}', expected one of mainnet, testnet, testnet4, signet, regtest"
+            )),
+        }
+    }
+}
+
 impl From<bitcoin::Network> for Network {
     fn from(network: bitcoin::Network) -> Self {
         match network {
@@ -182,17 +222,69 @@ impl DescriptorBuilder {
     ///     .parse("wpkh(xpub68NZiKmJWnxxS.../0/*)")?;
     /// ```
     pub fn parse(self, descriptor: &str) -> Result<Descriptor, String> {
+        self.parse_with(descriptor, false)
+    }
+
+    /// Parse a descriptor string, tolerating malformed key origins.
+    ///
+    /// Some older wallets export descriptors whose key origin info
+    /// (`[fingerprint/path]`) or checksum Bitcoin Core rejects. This first
+    /// tries a normal [`parse()`](Self::parse); if that fails, it retries
+    /// with the checksum and all key origins removed. A descriptor from the
+    /// retry reports [`Descriptor::is_lenient()`].
+    ///
+    /// Without origin info, signers cannot match the keys to their own, so
+    /// a lenient descriptor is meant for reading structure and scripts and
+    /// may not be solvable for signing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the normal parse if the retry fails as well.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse_lenient("wpkh([d34db33f/84'/0'/x]xpub.../0/*)")?;
+    /// assert!(desc.is_lenient());
+    /// ```
+    pub fn parse_lenient(self, descriptor: &str) -> Result<Descriptor, String> {
+        self.parse_with(descriptor, true)
+    }
+
+    fn parse_with(self, descriptor: &str, lenient: bool) -> Result<Descriptor, String> {
+        if let Some(position) = crate::invalid_input_position(descriptor) {
+            return Err(format!(
+                "descriptor contains a non-ASCII or null byte at position {position}"
+            ));
+        }
         let c_str = CString::new(descriptor).map_err(|e| e.to_string())?;
         let mut node: *mut ffi::DescriptorNode = ptr::null_mut();
 
+        let span = ffi_span!(
+            "Descriptor::parse",
+            input_len = descriptor.len(),
+            network = ?self.network,
+            lenient
+        );
         let result = unsafe {
-            ffi::descriptor_parse_with_network(c_str.as_ptr(), self.network.to_ffi(), &raw mut node)
+            if lenient {
+                ffi::descriptor_parse_lenient(c_str.as_ptr(), self.network.to_ffi(), &raw mut node)
+            } else {
+                ffi::descriptor_parse_with_network(
+                    c_str.as_ptr(),
+                    self.network.to_ffi(),
+                    &raw mut node,
+                )
+            }
         };
+        span.record(result.success);
 
         if result.success {
             Ok(Descriptor {
                 node,
                 network: self.network,
+                lenient: unsafe { ffi::descriptor_is_lenient(node) },
             })
         } else {
             let error = if result.error_message.is_null() {
@@ -243,9 +335,9 @@ impl DescriptorBuilder {
 ///
 /// # Thread Safety
 ///
-/// `Descriptor` implements `Send`, making it safe to transfer between threads.
-/// However, it does not implement `Sync` - use appropriate synchronization
-/// if you need to share a descriptor across threads.
+/// `Descriptor` implements `Send` and `Sync`: it can be moved to another
+/// thread, and a shared reference can be used from several threads at once.
+/// [`DescriptorCache`] is `Send` only.
 ///
 /// # Memory Management
 ///
@@ -275,11 +367,20 @@ pub struct Descriptor {
     node: *mut ffi::DescriptorNode,
     /// The network this descriptor was parsed with.
     network: Network,
+    /// Whether [`DescriptorBuilder::parse_lenient()`] had to drop the key
+    /// origins to parse this descriptor.
+    lenient: bool,
 }
 
 // Safety: DescriptorNode is only accessed through FFI calls which are thread-safe
 unsafe impl Send for Descriptor {}
 
+// Safety: the C++ descriptor and its signing provider are never modified
+// after parsing; methods taking &self only call const member functions, and
+// those that encode keys or addresses select the descriptor's network under
+// the chain parameters mutex
+unsafe impl Sync for Descriptor {}
+
 impl Descriptor {
     /// Create a builder for parsing descriptors with the specified network.
     ///
@@ -324,6 +425,42 @@ impl Descriptor {
         self.network
     }
 
+    /// Get the underlying C `DescriptorNode` for use with other C functions.
+    ///
+    /// The node stays owned by this `Descriptor`.
+    ///
+    /// # Safety
+    ///
+    /// The pointer is only valid while `self` is alive and must not be freed
+    /// by the caller. The node must not be modified through it, since
+    /// `Descriptor` is shared between threads.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Descriptor, DescriptorNetwork, DescriptorNode};
+    ///
+    /// let desc = Descriptor::for_network(DescriptorNetwork::Mainnet)
+    ///     .parse("pk(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)")
+    ///     .unwrap();
+    /// // SAFETY: the pointer is not used after `desc` is dropped
+    /// let node: *const DescriptorNode = unsafe { desc.as_raw() };
+    /// assert!(!node.is_null());
+    /// ```
+    #[must_use]
+    pub const unsafe fn as_raw(&self) -> *const ffi::DescriptorNode {
+        self.node
+    }
+
+    /// Check if [`DescriptorBuilder::parse_lenient()`] had to drop the
+    /// checksum and key origins to parse this descriptor.
+    ///
+    /// This is `false` for a descriptor that `parse_lenient()` parsed as is.
+    #[must_use]
+    pub const fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
     /// Check if the descriptor is ranged (contains wildcards like `/*`).
     ///
     /// Ranged descriptors can derive multiple addresses by specifying
@@ -363,6 +500,27 @@ impl Descriptor {
         unsafe { ffi::descriptor_is_solvable(self.node) }
     }
 
+    /// Check if expanding the descriptor requires private keys.
+    ///
+    /// Hardened derivation steps after an extended public key (such as
+    /// `xpub.../0/*'`) parse fine but cannot be derived without the
+    /// corresponding private key, so [`expand()`](Self::expand) returns
+    /// `None` for them. Use this to warn before attempting expansion.
+    ///
+    /// The derivation paths after each extended key are checked directly,
+    /// so a descriptor that fails to expand for another reason is not
+    /// reported, and one given as an xprv is reported even though it can
+    /// expand.
+    ///
+    /// # Returns
+    ///
+    /// `true` if any extended key is followed by a hardened step, `false`
+    /// otherwise.
+    #[must_use]
+    pub fn requires_private_key_to_expand(&self) -> bool {
+        unsafe { ffi::descriptor_requires_private_key_to_expand(self.node) }
+    }
+
     /// Convert the descriptor back to a string.
     ///
     /// Returns the canonical string representation of the descriptor.
@@ -383,6 +541,131 @@ impl Descriptor {
         Some(s)
     }
 
+    /// Get the checksum of the descriptor's canonical form.
+    ///
+    /// This is the 8-character suffix of [`to_string()`](Self::to_string),
+    /// computed from the parsed descriptor without re-parsing a string.
+    ///
+    /// # Returns
+    ///
+    /// The checksum, or `None` if conversion fails.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wpkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)")?;
+    /// assert_eq!(desc.checksum().unwrap().len(), 8);
+    /// ```
+    #[must_use]
+    pub fn checksum(&self) -> Option<String> {
+        let ptr = unsafe { ffi::descriptor_checksum(self.node) };
+        if ptr.is_null() {
+            return None;
+        }
+        let s = unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned();
+        unsafe { ffi::descriptor_free_string(ptr) };
+        Some(s)
+    }
+
+    /// Get the type of address this descriptor produces.
+    ///
+    /// The type is determined by the top-level descriptor function, so no
+    /// keys are derived and the result is the same for every index. For
+    /// `addr()` and `raw()` descriptors the fixed script is classified instead.
+    ///
+    /// # Returns
+    ///
+    /// `None` for descriptors without a single address type: `combo()`
+    /// (which yields several), bare `pk()` and `multi()`, and `raw()` scripts
+    /// that are not a standard address type.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use bitcoin::AddressType;
+    ///
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("sh(wpkh(xpub.../0/*))")?;
+    /// assert_eq!(desc.address_type(), Some(AddressType::P2sh));
+    /// ```
+    #[must_use]
+    pub fn address_type(&self) -> Option<bitcoin::AddressType> {
+        use bitcoin::AddressType;
+
+        match self.kind()?.as_str() {
+            "pkh" => Some(AddressType::P2pkh),
+            "sh" => Some(AddressType::P2sh),
+            "wpkh" => Some(AddressType::P2wpkh),
+            "wsh" => Some(AddressType::P2wsh),
+            "tr" | "rawtr" => Some(AddressType::P2tr),
+            "addr" | "raw" => {
+                let script = bitcoin::ScriptBuf::from_bytes(self.expand(0)?);
+                bitcoin::Address::from_script(&script, bitcoin::Network::from(self.network))
+                    .ok()?
+                    .address_type()
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the address of an `addr()` descriptor.
+    ///
+    /// The address is encoded for the descriptor's network from its script,
+    /// so it does not depend on how the address was written.
+    ///
+    /// # Returns
+    ///
+    /// `None` if this is not an `addr()` descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("addr(bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4)")?;
+    /// assert!(!desc.is_solvable());
+    /// assert_eq!(desc.addr_target().unwrap(), "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+    /// ```
+    #[must_use]
+    pub fn addr_target(&self) -> Option<String> {
+        if self.kind()? != "addr" {
+            return None;
+        }
+        let script = bitcoin::ScriptBuf::from_bytes(self.expand(0)?);
+        bitcoin::Address::from_script(&script, bitcoin::Network::from(self.network))
+            .ok()
+            .map(|address| address.to_string())
+    }
+
+    /// Get the script of a `raw()` descriptor.
+    ///
+    /// # Returns
+    ///
+    /// `None` if this is not a `raw()` descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("raw(0014751e76e8199196d454941c45d1b3a323f1433bd6)")?;
+    /// assert_eq!(desc.raw_script().unwrap().len(), 22);
+    /// ```
+    #[must_use]
+    pub fn raw_script(&self) -> Option<Vec<u8>> {
+        if self.kind()? != "raw" {
+            return None;
+        }
+        self.expand(0)
+    }
+
+    /// Get the top-level descriptor function, e.g. `"wsh"`.
+    fn kind(&self) -> Option<String> {
+        let descriptor = self.to_string()?;
+        descriptor.split('(').next().map(str::to_string)
+    }
+
     /// Expand the descriptor at a specific index to get the actual script.
     ///
     /// For ranged descriptors, this derives the keys at the given index
@@ -416,6 +699,7 @@ impl Descriptor {
         let mut script_ptr: *mut u8 = ptr::null_mut();
         let mut script_len: usize = 0;
 
+        let span = ffi_span!("Descriptor::expand", index);
         let success = unsafe {
             ffi::descriptor_expand(
                 self.node,
@@ -424,6 +708,53 @@ impl Descriptor {
                 &raw mut script_len,
             )
         };
+        span.record(success);
+
+        if success && !script_ptr.is_null() && script_len > 0 {
+            let script = unsafe { std::slice::from_raw_parts(script_ptr, script_len) }.to_vec();
+            unsafe { ffi::descriptor_free_bytes(script_ptr) };
+            Some(script)
+        } else {
+            None
+        }
+    }
+
+    /// Expand the descriptor at a specific index, reusing derived keys.
+    ///
+    /// Behaves like [`expand()`](Self::expand), but derived keys are stored
+    /// in `cache` so that later expansions of the same descriptor skip the
+    /// repeated BIP32 derivation. For a ranged descriptor only the last
+    /// derivation step is performed for each new index.
+    ///
+    /// A cache is bound to the first descriptor it is used with; expanding a
+    /// different descriptor with it returns `None`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wpkh(xpub.../0/*)")?;
+    ///
+    /// let mut cache = DescriptorCache::new();
+    /// let scripts: Vec<_> = (0..1000)
+    ///     .filter_map(|i| desc.expand_cached(i, &mut cache))
+    ///     .collect();
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn expand_cached(&self, index: u32, cache: &mut DescriptorCache) -> Option<Vec<u8>> {
+        let mut script_ptr: *mut u8 = ptr::null_mut();
+        let mut script_len: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_expand_cached(
+                self.node,
+                index as i32,
+                cache.ptr,
+                &raw mut script_ptr,
+                &raw mut script_len,
+            )
+        };
 
         if success && !script_ptr.is_null() && script_len > 0 {
             let script = unsafe { std::slice::from_raw_parts(script_ptr, script_len) }.to_vec();
@@ -434,6 +765,66 @@ impl Descriptor {
         }
     }
 
+    /// Check whether two descriptors produce the same scripts over a range.
+    ///
+    /// This is a semantic comparison: descriptors that differ only
+    /// cosmetically, such as `'` versus `h` hardened derivation markers,
+    /// compare equal. The comparison stops at the first mismatch.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The descriptor to compare against
+    /// * `range` - The derivation indices to compare
+    ///
+    /// # Returns
+    ///
+    /// `true` if both descriptors expand to the same script at every index
+    /// in the range. An index at which either descriptor fails to expand
+    /// counts as a mismatch.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let a = Descriptor::for_network(Network::Mainnet).parse("wpkh([d34db33f/84'/0'/0']xpub.../0/*)")?;
+    /// let b = Descriptor::for_network(Network::Mainnet).parse("wpkh([d34db33f/84h/0h/0h]xpub.../0/*)")?;
+    ///
+    /// assert!(a.scripts_equal(&b, 0..20));
+    /// ```
+    #[must_use]
+    pub fn scripts_equal(&self, other: &Self, range: std::ops::Range<u32>) -> bool {
+        range.into_iter().all(|index| match self.expand(index) {
+            Some(script) => other.expand(index) == Some(script),
+            None => false,
+        })
+    }
+
+    /// Check whether two descriptors have the same structure, ignoring keys.
+    ///
+    /// Compares the descriptor functions, miniscript fragments, thresholds,
+    /// timelocks and hashes, with every key (including its origin info and
+    /// derivation path) replaced by a placeholder in order of first
+    /// appearance. Unlike [`scripts_equal()`](Self::scripts_equal) this
+    /// classifies descriptors by wallet kind rather than by the scripts they
+    /// produce.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let a = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wsh(sortedmulti(2,xpub1.../0/*,xpub2.../0/*,xpub3.../0/*))")?;
+    /// let b = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]xpub4.../0/*,xpub5.../0/*,xpub6.../0/*))")?;
+    ///
+    /// assert!(a.structural_eq(&b));
+    /// ```
+    #[must_use]
+    pub fn structural_eq(&self, other: &Self) -> bool {
+        match (self.to_string(), other.to_string()) {
+            (Some(a), Some(b)) => structure_template(&a) == structure_template(&b),
+            _ => false,
+        }
+    }
+
     /// Get the address for the descriptor at a specific index.
     ///
     /// This expands the descriptor and encodes the resulting script
@@ -462,8 +853,7 @@ impl Descriptor {
     #[must_use]
     #[allow(clippy::cast_possible_wrap)]
     pub fn get_address(&self, index: u32) -> Option<String> {
-        let ptr =
-            unsafe { ffi::descriptor_get_address(self.node, index as i32, self.network.to_ffi()) };
+        let ptr = unsafe { ffi::descriptor_get_address(self.node, index as i32) };
 
         if ptr.is_null() {
             return None;
@@ -476,10 +866,12 @@ impl Descriptor {
         Some(address)
     }
 
-    /// Get all public keys from the descriptor at a specific index.
+    /// Derive the script, address and public keys at a specific index.
     ///
-    /// This expands the descriptor and extracts all derived public keys.
-    /// Each key is returned as a 33-byte compressed public key.
+    /// Equivalent to calling [`expand()`](Self::expand),
+    /// [`get_address()`](Self::get_address) and
+    /// [`get_pubkeys()`](Self::get_pubkeys) with the same index, but the keys
+    /// are derived only once.
     ///
     /// # Arguments
     ///
@@ -487,32 +879,38 @@ impl Descriptor {
     ///
     /// # Returns
     ///
-    /// A vector of public key bytes on success, or `None` on failure.
+    /// The derived output on success, or `None` if the descriptor cannot be
+    /// expanded at `index`.
     ///
     /// # Example
     ///
     /// ```ignore
     /// let desc = Descriptor::for_network(Network::Testnet)
-    ///     .parse("wsh(multi(2,tpub1.../0/*,tpub2.../0/*))")?;
+    ///     .parse("wpkh(tpub.../0/*)")?;
     ///
-    /// if let Some(keys) = desc.get_pubkeys(0) {
-    ///     println!("Found {} public keys", keys.len());
-    ///     for key in &keys {
-    ///         println!("Key: {} bytes", key.len());
+    /// for i in 0..20 {
+    ///     if let Some(output) = desc.derive(i) {
+    ///         println!("{i}: {:?} {}", output.address, output.script);
     ///     }
     /// }
     /// ```
     #[must_use]
     #[allow(clippy::cast_possible_wrap)]
-    pub fn get_pubkeys(&self, index: u32) -> Option<Vec<Vec<u8>>> {
+    pub fn derive(&self, index: u32) -> Option<DerivedOutput> {
+        let mut script_ptr: *mut u8 = ptr::null_mut();
+        let mut script_len: usize = 0;
+        let mut address_ptr: *mut std::os::raw::c_char = ptr::null_mut();
         let mut pubkeys_ptr: *mut *mut u8 = ptr::null_mut();
         let mut lens_ptr: *mut usize = ptr::null_mut();
         let mut count: usize = 0;
 
         let success = unsafe {
-            ffi::descriptor_get_pubkeys(
+            ffi::descriptor_derive(
                 self.node,
                 index as i32,
+                &raw mut script_ptr,
+                &raw mut script_len,
+                &raw mut address_ptr,
                 &raw mut pubkeys_ptr,
                 &raw mut lens_ptr,
                 &raw mut count,
@@ -523,73 +921,985 @@ impl Descriptor {
             return None;
         }
 
-        if count == 0 {
-            return Some(Vec::new());
-        }
-
-        let mut result = Vec::with_capacity(count);
-
-        unsafe {
-            let pubkeys = std::slice::from_raw_parts(pubkeys_ptr, count);
-            let lens = std::slice::from_raw_parts(lens_ptr, count);
+        let script = if script_ptr.is_null() {
+            Vec::new()
+        } else {
+            let script = unsafe { std::slice::from_raw_parts(script_ptr, script_len) }.to_vec();
+            unsafe { ffi::descriptor_free_bytes(script_ptr) };
+            script
+        };
 
-            for i in 0..count {
-                if !pubkeys[i].is_null() && lens[i] > 0 {
-                    let key = std::slice::from_raw_parts(pubkeys[i], lens[i]).to_vec();
-                    result.push(key);
-                }
-            }
+        let address = if address_ptr.is_null() {
+            None
+        } else {
+            let address = unsafe { CStr::from_ptr(address_ptr) }
+                .to_string_lossy()
+                .into_owned();
+            unsafe { ffi::descriptor_free_string(address_ptr) };
+            Some(address)
+        };
 
-            ffi::descriptor_free_pubkeys(pubkeys_ptr, lens_ptr, count);
-        }
+        // SAFETY: the arrays were returned by a successful descriptor_derive call
+        let pubkeys = unsafe { take_pubkeys(pubkeys_ptr, lens_ptr, count) };
 
-        Some(result)
+        Some(DerivedOutput {
+            script: bitcoin::ScriptBuf::from_bytes(script),
+            address,
+            pubkeys,
+        })
     }
 
-    /// Get the script size for this descriptor.
+    /// Derive the outputs for a range of indices on several threads.
     ///
-    /// Returns the size of the output script in bytes.
+    /// The range is split into contiguous chunks, one per available CPU,
+    /// and each chunk is derived with [`derive()`](Self::derive) on its own
+    /// thread. This pays off for rescans over large ranges, where BIP32
+    /// derivation dominates.
     ///
     /// # Returns
     ///
-    /// The script size, or `None` if it cannot be determined.
+    /// The derived outputs in index order. Indices at which the descriptor
+    /// cannot be expanded are skipped, so the result equals
+    /// `range.filter_map(|i| self.derive(i))`.
+    ///
+    /// # Panics
+    ///
+    /// Re-raises the panic of a worker thread, which only happens if
+    /// allocation fails.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet).parse("wpkh(xpub.../0/*)")?;
+    ///
+    /// for output in desc.scan_parallel(0..1000) {
+    ///     println!("{:?}", output.address);
+    /// }
+    /// ```
+    #[cfg(feature = "parallel")]
     #[must_use]
-    pub fn script_size(&self) -> Option<i64> {
-        let mut size: i64 = 0;
-        if unsafe { ffi::descriptor_get_script_size(self.node, &raw mut size) } {
-            Some(size)
-        } else {
-            None
+    pub fn scan_parallel(&self, range: std::ops::Range<u32>) -> Vec<DerivedOutput> {
+        let indices: Vec<u32> = range.collect();
+        if indices.is_empty() {
+            return Vec::new();
         }
+        let workers = std::thread::available_parallelism()
+            .map_or(1, std::num::NonZeroUsize::get)
+            .min(indices.len());
+        let chunk_len = indices.len().div_ceil(workers);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = indices
+                .chunks(chunk_len)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .filter_map(|&index| self.derive(index))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            // Joining in spawn order keeps the chunks, and so the outputs,
+            // in index order
+            let mut outputs = Vec::with_capacity(indices.len());
+            for handle in handles {
+                match handle.join() {
+                    Ok(chunk) => outputs.extend(chunk),
+                    Err(panic) => std::panic::resume_unwind(panic),
+                }
+            }
+            outputs
+        })
     }
 
-    /// Get the maximum satisfaction weight for this descriptor.
+    /// Get all public keys from the descriptor at a specific index.
     ///
-    /// Returns the maximum weight units needed to satisfy this descriptor.
-    /// This is useful for fee estimation.
+    /// This expands the descriptor and extracts all derived public keys.
+    /// Each key is returned as a 33-byte compressed public key.
     ///
     /// # Arguments
     ///
-    /// * `use_max_sig` - Whether to assume ECDSA signatures will have a high-r
-    ///   value (worst case for size estimation)
+    /// * `index` - The derivation index
     ///
     /// # Returns
     ///
-    /// The maximum satisfaction weight, or `None` if it cannot be determined.
-    #[must_use]
-    pub fn max_satisfaction_weight(&self, use_max_sig: bool) -> Option<i64> {
-        let mut weight: i64 = 0;
-        if unsafe {
-            ffi::descriptor_get_max_satisfaction_weight(self.node, use_max_sig, &raw mut weight)
-        } {
-            Some(weight)
-        } else {
-            None
-        }
-    }
-}
-
-impl Drop for Descriptor {
+    /// A vector of public key bytes on success, or `None` on failure.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Testnet)
+    ///     .parse("wsh(multi(2,tpub1.../0/*,tpub2.../0/*))")?;
+    ///
+    /// if let Some(keys) = desc.get_pubkeys(0) {
+    ///     println!("Found {} public keys", keys.len());
+    ///     for key in &keys {
+    ///         println!("Key: {} bytes", key.len());
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn get_pubkeys(&self, index: u32) -> Option<Vec<Vec<u8>>> {
+        let mut pubkeys_ptr: *mut *mut u8 = ptr::null_mut();
+        let mut lens_ptr: *mut usize = ptr::null_mut();
+        let mut count: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_get_pubkeys(
+                self.node,
+                index as i32,
+                &raw mut pubkeys_ptr,
+                &raw mut lens_ptr,
+                &raw mut count,
+            )
+        };
+
+        if !success {
+            return None;
+        }
+
+        // SAFETY: the arrays were returned by a successful descriptor_get_pubkeys call
+        Some(unsafe { take_pubkeys(pubkeys_ptr, lens_ptr, count) })
+    }
+
+    /// Get the public keys for a range of derivation indices.
+    ///
+    /// This is equivalent to calling [`get_pubkeys()`](Self::get_pubkeys)
+    /// for each index from `start` to `start + count - 1`, but crosses the
+    /// FFI boundary only once, which makes gap-limit scanning cheaper.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first derivation index
+    /// * `count` - The number of indices
+    ///
+    /// # Returns
+    ///
+    /// One entry per index, in index order, each `None` if the descriptor
+    /// could not be expanded at that index. Derivation indices stop at
+    /// 2^31 - 1, so a range reaching past it has `None` at every index.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Testnet)
+    ///     .parse("wpkh(tpub.../0/*)")?;
+    ///
+    /// for (i, keys) in desc.pubkeys_range(0, 20).into_iter().enumerate() {
+    ///     println!("Index {i}: {:?}", keys);
+    /// }
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn pubkeys_range(&self, start: u32, count: u32) -> Vec<Option<Vec<Vec<u8>>>> {
+        let mut pubkeys_ptr: *mut *mut u8 = ptr::null_mut();
+        let mut lens_ptr: *mut usize = ptr::null_mut();
+        let mut total: usize = 0;
+        let mut counts_ptr: *mut i64 = ptr::null_mut();
+
+        let success = unsafe {
+            ffi::descriptor_get_pubkeys_range(
+                self.node,
+                start as i32,
+                count as usize,
+                &raw mut pubkeys_ptr,
+                &raw mut lens_ptr,
+                &raw mut total,
+                &raw mut counts_ptr,
+            )
+        };
+
+        if !success || counts_ptr.is_null() {
+            return vec![None; count as usize];
+        }
+
+        let mut result = Vec::with_capacity(count as usize);
+
+        // SAFETY: the arrays were returned by a successful
+        // descriptor_get_pubkeys_range call, with `count` entries in
+        // counts_ptr and `total` entries in pubkeys_ptr and lens_ptr
+        unsafe {
+            let counts = std::slice::from_raw_parts(counts_ptr, count as usize);
+            let (pubkeys, lens): (&[*mut u8], &[usize]) = if total == 0 {
+                (&[], &[])
+            } else {
+                (
+                    std::slice::from_raw_parts(pubkeys_ptr, total),
+                    std::slice::from_raw_parts(lens_ptr, total),
+                )
+            };
+
+            let mut offset = 0;
+            for &n in counts {
+                let Ok(n) = usize::try_from(n) else {
+                    result.push(None);
+                    continue;
+                };
+                let keys = (offset..offset + n)
+                    .filter(|&i| !pubkeys[i].is_null() && lens[i] > 0)
+                    .map(|i| std::slice::from_raw_parts(pubkeys[i], lens[i]).to_vec())
+                    .collect();
+                offset += n;
+                result.push(Some(keys));
+            }
+
+            ffi::descriptor_free_pubkeys_range(pubkeys_ptr, lens_ptr, total, counts_ptr);
+        }
+
+        result
+    }
+
+    /// Get the public keys of a multisig descriptor in script order.
+    ///
+    /// For `sortedmulti()` this is the lexicographically sorted order that
+    /// the keys appear in once the script is built, which allows checking
+    /// that differently-ordered `sortedmulti()` inputs are equivalent.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The derivation index
+    ///
+    /// # Returns
+    ///
+    /// The public keys in script order, or `None` if the descriptor does not
+    /// contain a `multi()` or `sortedmulti()` script.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Testnet)
+    ///     .parse("wsh(sortedmulti(2,tpub1.../0/*,tpub2.../0/*))")?;
+    ///
+    /// let keys = desc.sorted_pubkeys(0).unwrap();
+    /// assert!(keys.windows(2).all(|w| w[0] <= w[1]));
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn sorted_pubkeys(&self, index: u32) -> Option<Vec<Vec<u8>>> {
+        let mut pubkeys_ptr: *mut *mut u8 = ptr::null_mut();
+        let mut lens_ptr: *mut usize = ptr::null_mut();
+        let mut count: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_get_multisig_pubkeys(
+                self.node,
+                index as i32,
+                &raw mut pubkeys_ptr,
+                &raw mut lens_ptr,
+                &raw mut count,
+            )
+        };
+
+        if !success {
+            return None;
+        }
+
+        // SAFETY: the arrays were returned by a successful descriptor_get_multisig_pubkeys call
+        Some(unsafe { take_pubkeys(pubkeys_ptr, lens_ptr, count) })
+    }
+
+    /// Check whether a public key is one of the descriptor's keys at an index.
+    ///
+    /// The key is compared against the keys derived at `index` (see
+    /// [`get_pubkeys()`](Self::get_pubkeys)). A 32-byte x-only key matches a
+    /// derived key with the same x coordinate.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Testnet)
+    ///     .parse("wsh(sortedmulti(2,tpub1.../0/*,tpub2.../0/*,tpub3.../0/*))")?;
+    /// let key = desc.get_pubkeys(5).unwrap().remove(0);
+    /// assert!(desc.contains_pubkey(&key, 5));
+    /// ```
+    #[must_use]
+    pub fn contains_pubkey(&self, pubkey: &[u8], index: u32) -> bool {
+        self.get_pubkeys(index).is_some_and(|keys| {
+            keys.iter().any(|key| {
+                key.as_slice() == pubkey || (pubkey.len() == 32 && key.get(1..) == Some(pubkey))
+            })
+        })
+    }
+
+    /// Check whether any key of the descriptor has the given origin fingerprint.
+    ///
+    /// Fingerprints come from the key origin info (`[d34db33f/...]`) and do
+    /// not depend on the derivation index. Keys are not derived, so this
+    /// also works for watch-only descriptors with hardened steps after an
+    /// xpub. As in Bitcoin Core, a key without origin info has its own
+    /// fingerprint: the first 4 bytes of the hash160 of the key (of the
+    /// extended key, for an xpub).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wpkh([d34db33f/84h/0h/0h]xpub.../0/*)")?;
+    /// assert!(desc.contains_fingerprint([0xd3, 0x4d, 0xb3, 0x3f]));
+    /// ```
+    #[must_use]
+    pub fn contains_fingerprint(&self, fp: [u8; 4]) -> bool {
+        let mut fingerprints_ptr: *mut u8 = ptr::null_mut();
+        let mut count: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_get_origin_fingerprints(
+                self.node,
+                &raw mut fingerprints_ptr,
+                &raw mut count,
+            )
+        };
+        if !success || fingerprints_ptr.is_null() {
+            return false;
+        }
+
+        // SAFETY: the buffer holds 4 * count bytes returned by
+        // descriptor_get_origin_fingerprints
+        let found = unsafe { std::slice::from_raw_parts(fingerprints_ptr, 4 * count) }
+            .chunks_exact(4)
+            .any(|chunk| chunk == fp);
+        unsafe { ffi::descriptor_free_bytes(fingerprints_ptr) };
+        found
+    }
+
+    /// List the private keys that must be supplied before this descriptor
+    /// can be signed for.
+    ///
+    /// Each requirement names a root key by its origin fingerprint and the
+    /// path from it to a key of the descriptor, e.g. the xprv with
+    /// fingerprint `d34db33f` for a `[d34db33f/48h/0h/0h/2h]xpub.../0/*`
+    /// key. Keys are those derived at `index`, so for a ranged key the path
+    /// ends in `/<index>`; the root private key can sign at every index.
+    /// Requirements are listed in the order their keys are written in the
+    /// descriptor. Keys whose private key the descriptor already holds
+    /// (`xprv` or WIF keys) are left out, so an empty list means no further
+    /// key is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the requirements cannot be determined: the
+    /// descriptor is not [solvable](Self::is_solvable), so no key would let
+    /// it be signed for, or its keys cannot be derived at `index` (a hardened
+    /// step after an `xpub`, see
+    /// [`requires_private_key_to_expand()`](Self::requires_private_key_to_expand)).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]xpub1.../0/*,[...]xpub2.../0/*,[...]xpub3.../0/*))")?;
+    /// for req in desc.signing_requirements(0)? {
+    ///     println!("{} {}", req.fingerprint.as_hex(), req.path);
+    /// }
+    /// ```
+    pub fn signing_requirements(&self, index: u32) -> Result<Vec<SigningRequirement>, Error> {
+        use bitcoin::hex::FromHex;
+        use std::str::FromStr;
+
+        let mut infos_ptr: *mut ffi::PubKeyInfo = ptr::null_mut();
+        let mut count: usize = 0;
+
+        if !self.is_solvable() {
+            return Err(Error::new("descriptor is not solvable"));
+        }
+        let success = unsafe {
+            ffi::descriptor_get_signing_requirements(
+                self.node,
+                index as i32,
+                &raw mut infos_ptr,
+                &raw mut count,
+            )
+        };
+        if !success {
+            return Err(Error::new(format!(
+                "cannot derive the descriptor's keys at index {index}"
+            )));
+        }
+        if infos_ptr.is_null() {
+            return Ok(Vec::new());
+        }
+
+        // SAFETY: infos_ptr holds count entries returned by
+        // descriptor_get_signing_requirements, each with a non-null key,
+        // fingerprint and path
+        let infos = unsafe { std::slice::from_raw_parts(infos_ptr, count) };
+        let requirements = infos
+            .iter()
+            .map(|info| {
+                let fingerprint = unsafe { CStr::from_ptr(info.origin_fingerprint) }
+                    .to_string_lossy()
+                    .into_owned();
+                let path = unsafe { CStr::from_ptr(info.origin_path) }
+                    .to_string_lossy()
+                    .into_owned();
+                Ok(SigningRequirement {
+                    fingerprint: <[u8; 4]>::from_hex(&fingerprint).map_err(|e| {
+                        Error::new(format!("invalid key origin fingerprint {fingerprint}: {e}"))
+                    })?,
+                    path: bitcoin::bip32::DerivationPath::from_str(&format!("m{path}"))
+                        .map_err(|e| Error::new(format!("invalid key origin path {path}: {e}")))?,
+                    pubkey: unsafe { std::slice::from_raw_parts(info.pubkey, info.pubkey_len) }
+                        .to_vec(),
+                })
+            })
+            .collect();
+        unsafe { ffi::descriptor_free_pubkey_infos(infos_ptr, count) };
+        requirements
+    }
+
+    /// Get the script size for this descriptor.
+    ///
+    /// Returns the size of the output script in bytes.
+    ///
+    /// # Returns
+    ///
+    /// The script size, or `None` if it cannot be determined.
+    #[must_use]
+    pub fn script_size(&self) -> Option<i64> {
+        let mut size: i64 = 0;
+        if unsafe { ffi::descriptor_get_script_size(self.node, &raw mut size) } {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    /// Get the maximum satisfaction weight for this descriptor.
+    ///
+    /// Returns the maximum weight units needed to satisfy this descriptor.
+    /// This is useful for fee estimation.
+    ///
+    /// # Arguments
+    ///
+    /// * `use_max_sig` - Whether to assume ECDSA signatures will have a high-r
+    ///   value (worst case for size estimation)
+    ///
+    /// # Returns
+    ///
+    /// The maximum satisfaction weight, or `None` if it cannot be determined.
+    #[must_use]
+    pub fn max_satisfaction_weight(&self, use_max_sig: bool) -> Option<i64> {
+        let mut weight: i64 = 0;
+        if unsafe {
+            ffi::descriptor_get_max_satisfaction_weight(self.node, use_max_sig, &raw mut weight)
+        } {
+            Some(weight)
+        } else {
+            None
+        }
+    }
+
+    /// Get the maximum weight of the witness needed to satisfy this
+    /// descriptor, as used for fee estimation.
+    ///
+    /// This is the witness only: its stack item count and its elements, at
+    /// one weight unit per byte. Unlike
+    /// [`max_satisfaction_weight()`](Self::max_satisfaction_weight), the
+    /// scriptSig is not included, so `sh(wpkh())` and `wpkh()` have the same
+    /// witness weight.
+    ///
+    /// Bitcoin Core's wallet grinds ECDSA signatures to a low R value, which
+    /// keeps them at most 71 bytes, and estimates fees with that bound, as
+    /// this does. Schnorr signatures have a fixed size.
+    ///
+    /// # Returns
+    ///
+    /// The weight, or `None` if it cannot be determined or the descriptor
+    /// is not spent with a witness (e.g. `pkh()` or `sh(multi())`). For
+    /// `sh(wpkh())` and `sh(wsh())`, the scripts at index 0 must be derivable.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet).parse("wpkh(03a3...c5bd)")?;
+    /// // Item count, length-prefixed 71-byte signature and 33-byte key
+    /// assert_eq!(desc.max_weight_to_satisfy(), Some(1 + 1 + 71 + 1 + 33));
+    /// ```
+    #[must_use]
+    pub fn max_weight_to_satisfy(&self) -> Option<i64> {
+        use ffi::DescriptorOutputType as OutputType;
+
+        let mut output_type = OutputType::DESCRIPTOR_OUTPUT_LEGACY;
+        if !unsafe { ffi::descriptor_get_output_type(self.node, &raw mut output_type) } {
+            return None;
+        }
+        let mut weight = self.max_satisfaction_weight(false)?;
+        let mut elems: i64 = 0;
+        if !unsafe { ffi::descriptor_get_max_satisfaction_elems(self.node, &raw mut elems) } {
+            return None;
+        }
+
+        match output_type {
+            OutputType::DESCRIPTOR_OUTPUT_LEGACY => return None,
+            OutputType::DESCRIPTOR_OUTPUT_P2SH_SEGWIT => {
+                // Core counts the scriptSig, a push of the redeem script, at
+                // four units per byte and as one more stack element
+                let redeem_script = self.spend_scripts(0)?.redeem_script?;
+                weight -= 4 * (1 + i64::try_from(redeem_script.len()).ok()?);
+                elems -= 1;
+            }
+            OutputType::DESCRIPTOR_OUTPUT_BECH32 | OutputType::DESCRIPTOR_OUTPUT_BECH32M => {}
+        }
+
+        let count_size = if elems < 0xfd { 1 } else { 3 };
+        Some(count_size + weight)
+    }
+
+    /// Get the tweaked Taproot output key at a specific index.
+    ///
+    /// This is the 32-byte x-only key committed to in the `OP_1 <key>`
+    /// scriptPubKey of a `tr()` or `rawtr()` descriptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The derivation index
+    ///
+    /// # Returns
+    ///
+    /// The output key, or `None` if this is not a Taproot descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("tr(a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)")?;
+    ///
+    /// let output_key = desc.taproot_output_key(0).unwrap();
+    /// assert_eq!(desc.expand(0).unwrap()[2..], output_key);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn taproot_output_key(&self, index: u32) -> Option<[u8; 32]> {
+        let mut key = [0u8; 32];
+        if unsafe {
+            ffi::descriptor_get_taproot_output_key(self.node, index as i32, key.as_mut_ptr())
+        } {
+            Some(key)
+        } else {
+            None
+        }
+    }
+
+    /// Get the Merkle root of the Taproot script tree at a specific index.
+    ///
+    /// Together with the internal key, this is what a control block commits
+    /// to when spending through a tapscript leaf.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The derivation index
+    ///
+    /// # Returns
+    ///
+    /// The Merkle root, or `None` if this is not a `tr()` descriptor or it
+    /// has no script tree (key-path only).
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn taproot_merkle_root(&self, index: u32) -> Option<[u8; 32]> {
+        let mut root = [0u8; 32];
+        if unsafe {
+            ffi::descriptor_get_taproot_merkle_root(self.node, index as i32, root.as_mut_ptr())
+        } {
+            Some(root)
+        } else {
+            None
+        }
+    }
+
+    /// Get the leaves of the Taproot script tree at a specific index.
+    ///
+    /// Leaves are listed depth-first from left to right, as written in the
+    /// descriptor. Each carries the script and leaf version needed to build
+    /// the control block of a script-path spend.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The derivation index
+    ///
+    /// # Returns
+    ///
+    /// The leaves (empty for a key-path only `tr()`), or `None` if this is
+    /// not a `tr()` descriptor.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet)
+    ///     .parse("tr(a34b...c5bd,{pk(c604...9ee5),pk(d0fa...1b15)})")?;
+    /// let leaves = desc.tap_leaves(0).unwrap();
+    /// assert_eq!(leaves.len(), 2);
+    /// assert_eq!(leaves[0].version, 0xc0);
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn tap_leaves(&self, index: u32) -> Option<Vec<TapLeaf>> {
+        let mut scripts_ptr: *mut *mut u8 = ptr::null_mut();
+        let mut lens_ptr: *mut usize = ptr::null_mut();
+        let mut versions_ptr: *mut u8 = ptr::null_mut();
+        let mut count: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_get_tap_leaves(
+                self.node,
+                index as i32,
+                &raw mut scripts_ptr,
+                &raw mut lens_ptr,
+                &raw mut versions_ptr,
+                &raw mut count,
+            )
+        };
+        if !success {
+            return None;
+        }
+
+        let mut leaves = Vec::with_capacity(count);
+        if count > 0 {
+            // SAFETY: the arrays hold count entries, returned by a successful
+            // descriptor_get_tap_leaves call
+            unsafe {
+                let scripts = std::slice::from_raw_parts(scripts_ptr, count);
+                let lens = std::slice::from_raw_parts(lens_ptr, count);
+                let versions = std::slice::from_raw_parts(versions_ptr, count);
+                for i in 0..count {
+                    let script = if scripts[i].is_null() {
+                        Vec::new()
+                    } else {
+                        std::slice::from_raw_parts(scripts[i], lens[i]).to_vec()
+                    };
+                    leaves.push(TapLeaf {
+                        version: versions[i],
+                        script,
+                    });
+                }
+            }
+        }
+
+        unsafe { ffi::descriptor_free_tap_leaves(scripts_ptr, lens_ptr, versions_ptr, count) };
+        Some(leaves)
+    }
+
+    /// Produce the scriptSig and witness spending the output at an index.
+    ///
+    /// The satisfaction is assembled for the descriptor's wrapping:
+    /// - `pk()` and `pkh()` put the signature (and key) in the scriptSig
+    /// - `wpkh()` puts the signature and key in the witness
+    /// - `wsh()` satisfies the witness script as a miniscript with
+    ///   [`Miniscript::satisfy()`](crate::Miniscript::satisfy) and appends
+    ///   the witness script to the witness
+    /// - `sh()` appends the redeem script to the scriptSig, on top of the
+    ///   satisfaction of the wrapped descriptor
+    ///
+    /// Other scripts, such as bare or P2SH `multi()`, are decoded as
+    /// miniscript and their satisfaction is placed in the scriptSig.
+    /// Signatures are requested from `satisfier` by the derived public key.
+    /// Taproot descriptors are not supported.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the descriptor cannot be expanded at `index`,
+    /// is a Taproot or unsupported descriptor, or if the satisfier's data
+    /// does not give a complete non-malleable satisfaction.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let desc = Descriptor::for_network(Network::Mainnet).parse("sh(wpkh(03a3...c5bd))")?;
+    /// let mut satisfier = SimpleSatisfier::new();
+    /// satisfier.signatures.insert(pubkey, signature);
+    ///
+    /// let spend = desc.satisfy(0, satisfier)?;
+    /// tx.input[0].script_sig = spend.script_sig;
+    /// tx.input[0].witness = spend.witness;
+    /// ```
+    pub fn satisfy<S: Satisfier + 'static>(
+        &self,
+        index: u32,
+        satisfier: S,
+    ) -> Result<DescriptorSatisfaction, Error> {
+        let script_pubkey = self
+            .expand(index)
+            .map(bitcoin::ScriptBuf::from_bytes)
+            .ok_or_else(|| Error::new(format!("cannot expand descriptor at index {index}")))?;
+        let SpendScripts {
+            redeem_script,
+            witness_script,
+        } = self
+            .spend_scripts(index)
+            .ok_or_else(|| Error::new("cannot find the scripts of the descriptor"))?;
+
+        // For P2SH outputs the redeem script is what must be satisfied
+        let script = match &redeem_script {
+            Some(redeem) => bitcoin::ScriptBuf::from_bytes(redeem.clone()),
+            None => script_pubkey,
+        };
+
+        let mut script_sig = Vec::new();
+        let mut witness = Vec::new();
+        if script.is_p2wpkh() {
+            let key = self.single_pubkey(index)?;
+            witness = vec![sign_with(&satisfier, &key)?, key];
+        } else if script.is_p2wsh() {
+            let witness_script =
+                witness_script.ok_or_else(|| Error::new("missing witness script"))?;
+            witness = satisfy_script(&witness_script, satisfier)?;
+            witness.push(witness_script);
+        } else if script.is_witness_program() {
+            return Err(Error::new(
+                "only P2WPKH and P2WSH witness programs can be satisfied",
+            ));
+        } else if script.is_p2pkh() {
+            let key = self.single_pubkey(index)?;
+            script_sig = vec![sign_with(&satisfier, &key)?, key];
+        } else if script.is_p2pk() {
+            let key = self.single_pubkey(index)?;
+            script_sig = vec![sign_with(&satisfier, &key)?];
+        } else {
+            script_sig = satisfy_script(script.as_bytes(), satisfier)?;
+        }
+        script_sig.extend(redeem_script);
+
+        let mut builder = bitcoin::script::Builder::new();
+        for item in script_sig {
+            let push = bitcoin::script::PushBytesBuf::try_from(item)
+                .map_err(|_| Error::new("scriptSig element too large"))?;
+            builder = builder.push_slice(push);
+        }
+        Ok(DescriptorSatisfaction {
+            script_sig: builder.into_script(),
+            witness: bitcoin::Witness::from_slice(&witness),
+        })
+    }
+
+    /// Get the P2SH redeem script and P2WSH witness script at an index, for
+    /// outputs that have them.
+    #[allow(clippy::cast_possible_wrap)]
+    fn spend_scripts(&self, index: u32) -> Option<SpendScripts> {
+        let mut redeem_ptr: *mut u8 = ptr::null_mut();
+        let mut redeem_len: usize = 0;
+        let mut witness_ptr: *mut u8 = ptr::null_mut();
+        let mut witness_len: usize = 0;
+
+        let success = unsafe {
+            ffi::descriptor_get_spend_scripts(
+                self.node,
+                index as i32,
+                &raw mut redeem_ptr,
+                &raw mut redeem_len,
+                &raw mut witness_ptr,
+                &raw mut witness_len,
+            )
+        };
+        if !success {
+            return None;
+        }
+
+        let take = |ptr: *mut u8, len: usize| {
+            if ptr.is_null() {
+                return None;
+            }
+            // SAFETY: ptr holds len bytes allocated by descriptor_get_spend_scripts
+            let script = unsafe { std::slice::from_raw_parts(ptr, len) }.to_vec();
+            unsafe { ffi::descriptor_free_bytes(ptr) };
+            Some(script)
+        };
+        Some(SpendScripts {
+            redeem_script: take(redeem_ptr, redeem_len),
+            witness_script: take(witness_ptr, witness_len),
+        })
+    }
+
+    /// Get the only public key of a single-key descriptor at an index.
+    fn single_pubkey(&self, index: u32) -> Result<Vec<u8>, Error> {
+        match self.get_pubkeys(index) {
+            Some(mut keys) if keys.len() == 1 => Ok(keys.remove(0)),
+            _ => Err(Error::new("expected a single public key")),
+        }
+    }
+}
+
+/// The scripts revealed when spending a descriptor's output.
+struct SpendScripts {
+    /// The P2SH redeem script.
+    redeem_script: Option<Vec<u8>>,
+    /// The P2WSH witness script.
+    witness_script: Option<Vec<u8>>,
+}
+
+/// Ask a satisfier for a signature with `key`.
+fn sign_with<S: Satisfier>(satisfier: &S, key: &[u8]) -> Result<Vec<u8>, Error> {
+    match satisfier.sign(key) {
+        (Availability::Yes, Some(sig)) if !sig.is_empty() => Ok(sig),
+        _ => Err(Error::Satisfaction("no signature available".to_string())),
+    }
+}
+
+/// Decode a script as miniscript and produce its witness stack.
+fn satisfy_script<S: Satisfier + 'static>(
+    script: &[u8],
+    satisfier: S,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let ms = Miniscript::from_script_bytes(script, Context::Wsh)?;
+    let result = ms.satisfy(satisfier, true)?;
+    if result.availability != Availability::Yes {
+        return Err(Error::Satisfaction(
+            "satisfier data does not give a complete satisfaction".to_string(),
+        ));
+    }
+    Ok(result.stack)
+}
+
+/// A complete spend of a descriptor's output.
+///
+/// Returned by [`Descriptor::satisfy()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorSatisfaction {
+    /// The scriptSig, empty for native segwit outputs.
+    pub script_sig: bitcoin::ScriptBuf,
+    /// The witness, empty for legacy outputs.
+    pub witness: bitcoin::Witness,
+}
+
+/// A leaf of a Taproot script tree.
+///
+/// Returned by [`Descriptor::tap_leaves()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TapLeaf {
+    /// The leaf version (`0xc0` for tapscript).
+    pub version: u8,
+    /// The leaf script.
+    pub script: Vec<u8>,
+}
+
+/// The outputs of a descriptor at one derivation index.
+///
+/// Returned by [`Descriptor::derive()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedOutput {
+    /// The output script (`scriptPubKey`).
+    pub script: bitcoin::ScriptBuf,
+    /// The address of the script, or `None` if it has no address
+    /// (e.g. bare `multi()` or `raw()` scripts).
+    pub address: Option<String>,
+    /// The public keys derived at this index.
+    pub pubkeys: Vec<Vec<u8>>,
+}
+
+/// A private key that must be supplied to sign for a descriptor.
+///
+/// Returned by [`Descriptor::signing_requirements()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SigningRequirement {
+    /// Fingerprint of the key at the root of [`path`](Self::path), from the
+    /// key origin info. A key without origin info is its own root.
+    pub fingerprint: [u8; 4],
+    /// Derivation path from the root key to the derived key.
+    pub path: bitcoin::bip32::DerivationPath,
+    /// The public key at the requested index.
+    pub pubkey: Vec<u8>,
+}
+
+/// Copy a pubkey array returned by the C wrapper and free it.
+///
+/// # Safety
+///
+/// `pubkeys_ptr` and `lens_ptr` must be arrays of `count` elements allocated
+/// by the descriptor wrapper (or null when `count` is zero).
+unsafe fn take_pubkeys(
+    pubkeys_ptr: *mut *mut u8,
+    lens_ptr: *mut usize,
+    count: usize,
+) -> Vec<Vec<u8>> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let mut result = Vec::with_capacity(count);
+
+    unsafe {
+        let pubkeys = std::slice::from_raw_parts(pubkeys_ptr, count);
+        let lens = std::slice::from_raw_parts(lens_ptr, count);
+
+        for i in 0..count {
+            if !pubkeys[i].is_null() && lens[i] > 0 {
+                let key = std::slice::from_raw_parts(pubkeys[i], lens[i]).to_vec();
+                result.push(key);
+            }
+        }
+
+        ffi::descriptor_free_pubkeys(pubkeys_ptr, lens_ptr, count);
+    }
+
+    result
+}
+
+/// Replace the keys of a descriptor string with placeholders.
+///
+/// Any argument that is not a nested expression, a number, or the argument
+/// of a hash lock, `raw()` or `addr()` is a key. The checksum is dropped.
+fn structure_template(descriptor: &str) -> String {
+    let descriptor = descriptor.split('#').next().unwrap_or_default();
+    let mut names: HashMap<&str, String> = HashMap::new();
+    let mut enclosing: Vec<&str> = Vec::new();
+    let mut out = String::with_capacity(descriptor.len());
+    let mut start = 0;
+
+    for (i, c) in descriptor.char_indices() {
+        if !matches!(c, '(' | ')' | ',' | '{' | '}') {
+            continue;
+        }
+        let token = &descriptor[start..i];
+        start = i + c.len_utf8();
+
+        if c == '(' {
+            out.push_str(token);
+            enclosing.push(token.rsplit(':').next().unwrap_or_default());
+        } else if !token.is_empty() {
+            let parent = enclosing.last().copied().unwrap_or_default();
+            let keep = token.bytes().all(|b| b.is_ascii_digit())
+                || matches!(
+                    parent,
+                    "sha256" | "hash256" | "ripemd160" | "hash160" | "raw" | "addr"
+                );
+            if keep {
+                out.push_str(token);
+            } else {
+                let next = names.len();
+                out.push_str(
+                    names
+                        .entry(token)
+                        .or_insert_with(|| crate::fragment::placeholder(next)),
+                );
+            }
+        }
+
+        match c {
+            '{' => enclosing.push("{"),
+            ')' | '}' => {
+                enclosing.pop();
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    out.push_str(&descriptor[start..]);
+    out
+}
+
+impl<'a> TryFrom<(&'a str, Network)> for Descriptor {
+    type Error = String;
+
+    /// Parse a descriptor for the given network.
+    ///
+    /// Equivalent to `Descriptor::for_network(network).parse(descriptor)`.
+    fn try_from((descriptor, network): (&'a str, Network)) -> Result<Self, Self::Error> {
+        Self::for_network(network).parse(descriptor)
+    }
+}
+
+impl Drop for Descriptor {
     fn drop(&mut self) {
         if !self.node.is_null() {
             unsafe { ffi::descriptor_node_free(self.node) };
@@ -597,6 +1907,48 @@ impl Drop for Descriptor {
     }
 }
 
+/// A cache of derived keys for [`Descriptor::expand_cached()`].
+///
+/// Wraps Bitcoin Core's `DescriptorCache`, which stores the parent extended
+/// public keys and derived keys of a descriptor so repeated expansions avoid
+/// redoing BIP32 derivation. A cache belongs to the first descriptor it is
+/// used with.
+pub struct DescriptorCache {
+    /// Raw pointer to the C++ `DescriptorCacheNode` object.
+    ptr: *mut ffi::DescriptorCacheNode,
+}
+
+// Safety: the cache is only accessed through FFI calls taking `&mut self`
+unsafe impl Send for DescriptorCache {}
+
+impl DescriptorCache {
+    /// Create an empty cache.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the C++ allocation fails.
+    #[must_use]
+    pub fn new() -> Self {
+        let ptr = unsafe { ffi::descriptor_cache_new() };
+        assert!(!ptr.is_null(), "failed to allocate descriptor cache");
+        Self { ptr }
+    }
+}
+
+impl Default for DescriptorCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DescriptorCache {
+    fn drop(&mut self) {
+        if !self.ptr.is_null() {
+            unsafe { ffi::descriptor_cache_free(self.ptr) };
+        }
+    }
+}
+
 /// Get the checksum for a descriptor string.
 ///
 /// Computes or validates the checksum for a descriptor string.
@@ -644,6 +1996,65 @@ pub fn get_descriptor_checksum(descriptor: &str) -> Option<String> {
     Some(checksum)
 }
 
+/// Result of validating a descriptor checksum.
+///
+/// Returned by [`validate_checksum()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The descriptor has a checksum and it is correct.
+    Valid,
+    /// The descriptor has a checksum but it does not match.
+    Invalid {
+        /// The checksum the descriptor should have.
+        expected: String,
+    },
+    /// The descriptor has no checksum.
+    Missing {
+        /// The checksum that should be appended.
+        computed: String,
+    },
+    /// No checksum can be computed, because the descriptor contains
+    /// characters outside the descriptor character set.
+    Malformed,
+}
+
+/// Validate the checksum of a descriptor string.
+///
+/// Unlike [`get_descriptor_checksum()`], this tells apart a correct
+/// checksum, a wrong one, and a missing one.
+///
+/// # Arguments
+///
+/// * `descriptor` - The descriptor string (with or without checksum)
+///
+/// # Example
+///
+/// ```ignore
+/// use miniscript_core_ffi::descriptor::{validate_checksum, ChecksumStatus};
+///
+/// match validate_checksum("wpkh(pubkey)") {
+///     ChecksumStatus::Missing { computed } => println!("append #{computed}"),
+///     status => println!("{status:?}"),
+/// }
+/// ```
+#[must_use]
+pub fn validate_checksum(descriptor: &str) -> ChecksumStatus {
+    let (body, checksum) = match descriptor.rsplit_once('#') {
+        Some((body, checksum)) => (body, Some(checksum)),
+        None => (descriptor, None),
+    };
+
+    let Some(computed) = get_descriptor_checksum(body) else {
+        return ChecksumStatus::Malformed;
+    };
+
+    match checksum {
+        None => ChecksumStatus::Missing { computed },
+        Some(checksum) if checksum == computed => ChecksumStatus::Valid,
+        Some(_) => ChecksumStatus::Invalid { expected: computed },
+    }
+}
+
 /// Get the descriptor wrapper version.
 ///
 /// Returns the version string of the descriptor FFI wrapper.
@@ -717,4 +2128,56 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_structure_template() {
+        let sha = "01".repeat(32);
+        assert_eq!(
+            structure_template(&format!(
+                "tr([d34db33f/86h/0h/0h]xpubA/0/*,{{and_v(v:pk(K2),sha256({sha})),pk(K2)}})#abcdefgh"
+            )),
+            format!("tr(A,{{and_v(v:pk(B),sha256({sha})),pk(B)}})")
+        );
+        assert_eq!(
+            structure_template("wsh(thresh(2,pk(K1),s:pk(K2),sln:older(12)))"),
+            "wsh(thresh(2,pk(A),s:pk(B),sln:older(12)))"
+        );
+    }
+
+    #[test]
+    fn test_validate_checksum() {
+        let body = "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)";
+
+        let ChecksumStatus::Missing { computed } = validate_checksum(body) else {
+            panic!("Expected a missing checksum");
+        };
+        assert_eq!(computed.len(), 8);
+
+        assert_eq!(
+            validate_checksum(&format!("{body}#{computed}")),
+            ChecksumStatus::Valid
+        );
+
+        let wrong = if computed == "aaaaaaaa" {
+            "qqqqqqqq"
+        } else {
+            "aaaaaaaa"
+        };
+        assert_eq!(
+            validate_checksum(&format!("{body}#{wrong}")),
+            ChecksumStatus::Invalid { expected: computed }
+        );
+    }
+
+    #[test]
+    fn test_network_from_str() {
+        assert_eq!("mainnet".parse::<Network>(), Ok(Network::Mainnet));
+        assert_eq!("Testnet".parse::<Network>(), Ok(Network::Testnet));
+        assert_eq!("TESTNET4".parse::<Network>(), Ok(Network::Testnet4));
+        assert_eq!("signet".parse::<Network>(), Ok(Network::Signet));
+        assert_eq!("regtest".parse::<Network>(), Ok(Network::Regtest));
+
+        let err = "bitcoin".parse::<Network>().unwrap_err();
+        assert!(err.contains("bitcoin"));
+    }
 }
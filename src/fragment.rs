@@ -0,0 +1,679 @@
+//! Miniscript fragment tree inspection.
+//!
+//! This module exposes the structure of a parsed miniscript as a sequence of
+//! [`Fragment`]s, mirroring the fragment types used by Bitcoin Core's
+//! miniscript implementation.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use miniscript_core_ffi::{Context, Fragment, Miniscript};
+//!
+//! let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).unwrap();
+//!
+//! let mut keys = 0;
+//! ms.walk(|fragment| {
+//!     if let Fragment::PkK(_) = fragment {
+//!         keys += 1;
+//!     }
+//! });
+//! assert_eq!(keys, 2);
+//! ```
+
+use crate::ffi;
+use bitcoin::hex::DisplayHex;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fmt::Write;
+use std::ptr;
+
+/// A single miniscript fragment.
+///
+/// Key material is reported as the raw bytes of the key as written in the
+/// miniscript expression, and hash digests as their raw bytes. Fragments with
+/// children (wrappers, conjunctions, disjunctions and thresholds) carry no
+/// child data themselves; their children follow them in pre-order, and
+/// [`child_count()`](Self::child_count) tells how many there are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Fragment {
+    /// `0`
+    Just0,
+    /// `1`
+    Just1,
+    /// `pk_k(key)`
+    PkK(Vec<u8>),
+    /// `pk_h(key)`
+    PkH(Vec<u8>),
+    /// `older(n)`
+    Older(u32),
+    /// `after(n)`
+    After(u32),
+    /// `sha256(h)`
+    Sha256(Vec<u8>),
+    /// `hash256(h)`
+    Hash256(Vec<u8>),
+    /// `ripemd160(h)`
+    Ripemd160(Vec<u8>),
+    /// `hash160(h)`
+    Hash160(Vec<u8>),
+    /// `a:X`
+    WrapA,
+    /// `s:X`
+    WrapS,
+    /// `c:X`
+    WrapC,
+    /// `d:X`
+    WrapD,
+    /// `v:X`
+    WrapV,
+    /// `j:X`
+    WrapJ,
+    /// `n:X`
+    WrapN,
+    /// `and_v(X,Y)`
+    AndV,
+    /// `and_b(X,Y)`
+    AndB,
+    /// `or_b(X,Z)`
+    OrB,
+    /// `or_c(X,Z)`
+    OrC,
+    /// `or_d(X,Z)`
+    OrD,
+    /// `or_i(X,Z)`
+    OrI,
+    /// `andor(X,Y,Z)`
+    AndOr,
+    /// `thresh(k,X1,...,Xn)`
+    Thresh {
+        /// Number of sub-expressions that must be satisfied.
+        k: u32,
+        /// Total number of sub-expressions.
+        n: usize,
+    },
+    /// `multi(k,key1,...,keyn)`
+    Multi {
+        /// Number of required signatures.
+        k: u32,
+        /// The keys.
+        keys: Vec<Vec<u8>>,
+    },
+    /// `multi_a(k,key1,...,keyn)`
+    MultiA {
+        /// Number of required signatures.
+        k: u32,
+        /// The keys.
+        keys: Vec<Vec<u8>>,
+    },
+}
+
+impl Fragment {
+    /// Get the number of direct children of this fragment.
+    #[must_use]
+    pub const fn child_count(&self) -> usize {
+        match self {
+            Self::Just0
+            | Self::Just1
+            | Self::PkK(_)
+            | Self::PkH(_)
+            | Self::Older(_)
+            | Self::After(_)
+            | Self::Sha256(_)
+            | Self::Hash256(_)
+            | Self::Ripemd160(_)
+            | Self::Hash160(_)
+            | Self::Multi { .. }
+            | Self::MultiA { .. } => 0,
+            Self::WrapA
+            | Self::WrapS
+            | Self::WrapC
+            | Self::WrapD
+            | Self::WrapV
+            | Self::WrapJ
+            | Self::WrapN => 1,
+            Self::AndV | Self::AndB | Self::OrB | Self::OrC | Self::OrD | Self::OrI => 2,
+            Self::AndOr => 3,
+            Self::Thresh { n, .. } => *n,
+        }
+    }
+
+    /// Get the type of this fragment.
+    #[must_use]
+    pub const fn kind(&self) -> FragmentKind {
+        match self {
+            Self::Just0 => FragmentKind::Just0,
+            Self::Just1 => FragmentKind::Just1,
+            Self::PkK(_) => FragmentKind::PkK,
+            Self::PkH(_) => FragmentKind::PkH,
+            Self::Older(_) => FragmentKind::Older,
+            Self::After(_) => FragmentKind::After,
+            Self::Sha256(_) => FragmentKind::Sha256,
+            Self::Hash256(_) => FragmentKind::Hash256,
+            Self::Ripemd160(_) => FragmentKind::Ripemd160,
+            Self::Hash160(_) => FragmentKind::Hash160,
+            Self::WrapA => FragmentKind::WrapA,
+            Self::WrapS => FragmentKind::WrapS,
+            Self::WrapC => FragmentKind::WrapC,
+            Self::WrapD => FragmentKind::WrapD,
+            Self::WrapV => FragmentKind::WrapV,
+            Self::WrapJ => FragmentKind::WrapJ,
+            Self::WrapN => FragmentKind::WrapN,
+            Self::AndV => FragmentKind::AndV,
+            Self::AndB => FragmentKind::AndB,
+            Self::OrB => FragmentKind::OrB,
+            Self::OrC => FragmentKind::OrC,
+            Self::OrD => FragmentKind::OrD,
+            Self::OrI => FragmentKind::OrI,
+            Self::AndOr => FragmentKind::AndOr,
+            Self::Thresh { .. } => FragmentKind::Thresh,
+            Self::Multi { .. } => FragmentKind::Multi,
+            Self::MultiA { .. } => FragmentKind::MultiA,
+        }
+    }
+
+    /// Convert a node returned by `miniscript_get_tree` into a `Fragment`.
+    ///
+    /// # Safety
+    ///
+    /// `node.keys` must point to `node.key_count` valid C strings (or be null),
+    /// and `node.data` must point to `node.data_len` bytes (or be null).
+    unsafe fn from_ffi(node: &ffi::MiniscriptTreeNode) -> Self {
+        use ffi::MiniscriptFragment as F;
+
+        // SAFETY: guaranteed by the caller
+        let keys = || -> Vec<Vec<u8>> {
+            if node.keys.is_null() {
+                return Vec::new();
+            }
+            (0..node.key_count)
+                .map(|i| {
+                    let key = unsafe { *node.keys.add(i) };
+                    if key.is_null() {
+                        Vec::new()
+                    } else {
+                        unsafe { CStr::from_ptr(key) }.to_bytes().to_vec()
+                    }
+                })
+                .collect()
+        };
+        // SAFETY: guaranteed by the caller
+        let data = || -> Vec<u8> {
+            if node.data.is_null() || node.data_len == 0 {
+                Vec::new()
+            } else {
+                unsafe { std::slice::from_raw_parts(node.data, node.data_len) }.to_vec()
+            }
+        };
+        let first_key = || keys().into_iter().next().unwrap_or_default();
+
+        match node.fragment {
+            F::MINISCRIPT_FRAGMENT_JUST_0 => Self::Just0,
+            F::MINISCRIPT_FRAGMENT_JUST_1 => Self::Just1,
+            F::MINISCRIPT_FRAGMENT_PK_K => Self::PkK(first_key()),
+            F::MINISCRIPT_FRAGMENT_PK_H => Self::PkH(first_key()),
+            F::MINISCRIPT_FRAGMENT_OLDER => Self::Older(node.k),
+            F::MINISCRIPT_FRAGMENT_AFTER => Self::After(node.k),
+            F::MINISCRIPT_FRAGMENT_SHA256 => Self::Sha256(data()),
+            F::MINISCRIPT_FRAGMENT_HASH256 => Self::Hash256(data()),
+            F::MINISCRIPT_FRAGMENT_RIPEMD160 => Self::Ripemd160(data()),
+            F::MINISCRIPT_FRAGMENT_HASH160 => Self::Hash160(data()),
+            F::MINISCRIPT_FRAGMENT_WRAP_A => Self::WrapA,
+            F::MINISCRIPT_FRAGMENT_WRAP_S => Self::WrapS,
+            F::MINISCRIPT_FRAGMENT_WRAP_C => Self::WrapC,
+            F::MINISCRIPT_FRAGMENT_WRAP_D => Self::WrapD,
+            F::MINISCRIPT_FRAGMENT_WRAP_V => Self::WrapV,
+            F::MINISCRIPT_FRAGMENT_WRAP_J => Self::WrapJ,
+            F::MINISCRIPT_FRAGMENT_WRAP_N => Self::WrapN,
+            F::MINISCRIPT_FRAGMENT_AND_V => Self::AndV,
+            F::MINISCRIPT_FRAGMENT_AND_B => Self::AndB,
+            F::MINISCRIPT_FRAGMENT_OR_B => Self::OrB,
+            F::MINISCRIPT_FRAGMENT_OR_C => Self::OrC,
+            F::MINISCRIPT_FRAGMENT_OR_D => Self::OrD,
+            F::MINISCRIPT_FRAGMENT_OR_I => Self::OrI,
+            F::MINISCRIPT_FRAGMENT_ANDOR => Self::AndOr,
+            F::MINISCRIPT_FRAGMENT_THRESH => Self::Thresh {
+                k: node.k,
+                n: node.child_count,
+            },
+            F::MINISCRIPT_FRAGMENT_MULTI => Self::Multi {
+                k: node.k,
+                keys: keys(),
+            },
+            F::MINISCRIPT_FRAGMENT_MULTI_A => Self::MultiA {
+                k: node.k,
+                keys: keys(),
+            },
+        }
+    }
+}
+
+/// The type of a [`Fragment`], without its data.
+///
+/// See [`Fragment::kind()`] and
+/// [`Miniscript::fragment_counts()`](crate::Miniscript::fragment_counts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FragmentKind {
+    /// `0`
+    Just0,
+    /// `1`
+    Just1,
+    /// `pk_k(key)`
+    PkK,
+    /// `pk_h(key)`
+    PkH,
+    /// `older(n)`
+    Older,
+    /// `after(n)`
+    After,
+    /// `sha256(h)`
+    Sha256,
+    /// `hash256(h)`
+    Hash256,
+    /// `ripemd160(h)`
+    Ripemd160,
+    /// `hash160(h)`
+    Hash160,
+    /// `a:X`
+    WrapA,
+    /// `s:X`
+    WrapS,
+    /// `c:X`
+    WrapC,
+    /// `d:X`
+    WrapD,
+    /// `v:X`
+    WrapV,
+    /// `j:X`
+    WrapJ,
+    /// `n:X`
+    WrapN,
+    /// `and_v(X,Y)`
+    AndV,
+    /// `and_b(X,Y)`
+    AndB,
+    /// `or_b(X,Z)`
+    OrB,
+    /// `or_c(X,Z)`
+    OrC,
+    /// `or_d(X,Z)`
+    OrD,
+    /// `or_i(X,Z)`
+    OrI,
+    /// `andor(X,Y,Z)`
+    AndOr,
+    /// `thresh(k,X1,...,Xn)`
+    Thresh,
+    /// `multi(k,key1,...,keyn)`
+    Multi,
+    /// `multi_a(k,key1,...,keyn)`
+    MultiA,
+}
+
+/// How a key is used in a miniscript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PkKind {
+    /// The full public key is in the script (`pk_k`, `multi`, `multi_a`).
+    Pk,
+    /// Only the key hash is in the script (`pk_h`); the witness must
+    /// provide the public key alongside the signature.
+    PkH,
+}
+
+/// A key appearing in a miniscript and how it is used.
+///
+/// See [`Miniscript::key_usages()`](crate::Miniscript::key_usages).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeyUsage {
+    /// The key bytes as written in the miniscript expression.
+    pub key: Vec<u8>,
+    /// Whether the key appears as a full key or a key hash.
+    pub kind: PkKind,
+}
+
+/// The hash function of a hash lock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HashKind {
+    /// `sha256(h)`, a 32-byte digest.
+    Sha256,
+    /// `hash256(h)` (double SHA256), a 32-byte digest.
+    Hash256,
+    /// `ripemd160(h)`, a 20-byte digest.
+    Ripemd160,
+    /// `hash160(h)` (RIPEMD160 of SHA256), a 20-byte digest.
+    Hash160,
+}
+
+impl HashKind {
+    /// Get the digest length in bytes for this hash function.
+    #[must_use]
+    pub const fn digest_len(self) -> usize {
+        match self {
+            Self::Sha256 | Self::Hash256 => 32,
+            Self::Ripemd160 | Self::Hash160 => 20,
+        }
+    }
+}
+
+/// A hash lock appearing in a miniscript.
+///
+/// See [`Miniscript::hash_locks()`](crate::Miniscript::hash_locks).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HashLock {
+    /// The hash function.
+    pub kind: HashKind,
+    /// The committed digest, [`HashKind::digest_len()`] bytes long.
+    pub digest: Vec<u8>,
+}
+
+/// Collect the fragments of a miniscript node in pre-order.
+///
+/// Returns `None` if the tree could not be retrieved.
+pub(crate) fn collect(node: *const ffi::MiniscriptNode) -> Option<Vec<Fragment>> {
+    let mut nodes: *mut ffi::MiniscriptTreeNode = ptr::null_mut();
+    let mut count: usize = 0;
+
+    // SAFETY: node is a valid miniscript node owned by the caller
+    if !unsafe { ffi::miniscript_get_tree(node, &raw mut nodes, &raw mut count) } {
+        return None;
+    }
+
+    let fragments = if nodes.is_null() {
+        Vec::new()
+    } else {
+        // SAFETY: miniscript_get_tree returned `count` initialized nodes
+        unsafe { std::slice::from_raw_parts(nodes, count) }
+            .iter()
+            .map(|n| unsafe { Fragment::from_ffi(n) })
+            .collect()
+    };
+
+    // SAFETY: nodes was allocated by miniscript_get_tree
+    unsafe { ffi::miniscript_free_tree(nodes, count) };
+
+    Some(fragments)
+}
+
+/// Write a pre-order fragment sequence as a miniscript expression with each
+/// distinct key replaced by a placeholder (`A`, `B`, ..., `Z`, `AA`, ...)
+/// in order of first appearance.
+///
+/// The expression uses the plain fragment syntax (`c:pk_k(A)` rather than
+/// `pk(A)`). Returns `None` if the sequence does not describe a complete tree.
+pub(crate) fn template(fragments: &[Fragment]) -> Option<String> {
+    let mut iter = fragments.iter();
+    let mut names = HashMap::new();
+    let mut out = String::new();
+    // Children still to write for each fragment whose children are being
+    // written, and whether it is closed with a parenthesis (wrappers are not)
+    let mut open: Vec<(usize, bool)> = Vec::new();
+    loop {
+        let fragment = iter.next()?;
+        write_template(fragment, &mut names, &mut out);
+        let children = fragment.child_count();
+        if children > 0 {
+            let wrapper = matches!(
+                fragment,
+                Fragment::WrapA
+                    | Fragment::WrapS
+                    | Fragment::WrapC
+                    | Fragment::WrapD
+                    | Fragment::WrapV
+                    | Fragment::WrapJ
+                    | Fragment::WrapN
+            );
+            open.push((children, !wrapper));
+            continue;
+        }
+        // Close every fragment whose last child was just written
+        loop {
+            let Some((remaining, paren)) = open.last_mut() else {
+                if iter.next().is_some() {
+                    return None;
+                }
+                return Some(out);
+            };
+            *remaining -= 1;
+            if *remaining > 0 {
+                out.push(',');
+                break;
+            }
+            if *paren {
+                out.push(')');
+            }
+            open.pop();
+        }
+    }
+}
+
+/// Get the placeholder name for the `index`-th distinct key.
+pub(crate) fn placeholder(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Write a fragment as it starts in a template: leaves in full, wrappers as
+/// their prefix (`v:`) and other fragments up to their first child
+/// (`and_v(`, `thresh(2,`).
+fn write_template(fragment: &Fragment, names: &mut HashMap<Vec<u8>, String>, out: &mut String) {
+    let mut name = |key: &Vec<u8>| -> String {
+        let next = names.len();
+        names
+            .entry(key.clone())
+            .or_insert_with(|| placeholder(next))
+            .clone()
+    };
+
+    match fragment {
+        Fragment::Just0 => out.push('0'),
+        Fragment::Just1 => out.push('1'),
+        Fragment::PkK(key) => {
+            let _ = write!(out, "pk_k({})", name(key));
+        }
+        Fragment::PkH(key) => {
+            let _ = write!(out, "pk_h({})", name(key));
+        }
+        Fragment::Older(n) => {
+            let _ = write!(out, "older({n})");
+        }
+        Fragment::After(n) => {
+            let _ = write!(out, "after({n})");
+        }
+        Fragment::Sha256(h) => {
+            let _ = write!(out, "sha256({})", h.as_hex());
+        }
+        Fragment::Hash256(h) => {
+            let _ = write!(out, "hash256({})", h.as_hex());
+        }
+        Fragment::Ripemd160(h) => {
+            let _ = write!(out, "ripemd160({})", h.as_hex());
+        }
+        Fragment::Hash160(h) => {
+            let _ = write!(out, "hash160({})", h.as_hex());
+        }
+        Fragment::Multi { k, keys } | Fragment::MultiA { k, keys } => {
+            let fragment_name = if matches!(fragment, Fragment::Multi { .. }) {
+                "multi"
+            } else {
+                "multi_a"
+            };
+            let _ = write!(out, "{fragment_name}({k}");
+            for key in keys {
+                let _ = write!(out, ",{}", name(key));
+            }
+            out.push(')');
+        }
+        Fragment::WrapA => out.push_str("a:"),
+        Fragment::WrapS => out.push_str("s:"),
+        Fragment::WrapC => out.push_str("c:"),
+        Fragment::WrapD => out.push_str("d:"),
+        Fragment::WrapV => out.push_str("v:"),
+        Fragment::WrapJ => out.push_str("j:"),
+        Fragment::WrapN => out.push_str("n:"),
+        Fragment::AndV => out.push_str("and_v("),
+        Fragment::AndB => out.push_str("and_b("),
+        Fragment::OrB => out.push_str("or_b("),
+        Fragment::OrC => out.push_str("or_c("),
+        Fragment::OrD => out.push_str("or_d("),
+        Fragment::OrI => out.push_str("or_i("),
+        Fragment::AndOr => out.push_str("andor("),
+        Fragment::Thresh { k, .. } => {
+            let _ = write!(out, "thresh({k},");
+        }
+    }
+}
+
+/// Check whether some satisfaction of a pre-order fragment sequence reveals a
+/// hash preimage.
+///
+/// Returns `None` if the sequence does not describe a complete tree.
+pub(crate) fn needs_preimage(fragments: &[Fragment]) -> Option<bool> {
+    // Visiting a pre-order sequence backwards reaches every fragment after
+    // its children, whose results are then on top of the stack, first
+    // child first
+    let mut results: Vec<(bool, bool)> = Vec::new();
+    for fragment in fragments.iter().rev() {
+        let n = fragment.child_count();
+        if results.len() < n {
+            return None;
+        }
+        let subs: Vec<(bool, bool)> = results.drain(results.len() - n..).rev().collect();
+        results.push(preimage_result(fragment, &subs));
+    }
+    match results[..] {
+        [(_, preimage)] => Some(preimage),
+        _ => None,
+    }
+}
+
+/// Returns whether a fragment can be satisfied at all, and whether some
+/// satisfaction of it reveals a hash preimage, given the same for its subs.
+fn preimage_result(fragment: &Fragment, subs: &[(bool, bool)]) -> (bool, bool) {
+    match fragment {
+        Fragment::Just0 => (false, false),
+        Fragment::Just1
+        | Fragment::PkK(_)
+        | Fragment::PkH(_)
+        | Fragment::Older(_)
+        | Fragment::After(_)
+        | Fragment::Multi { .. }
+        | Fragment::MultiA { .. } => (true, false),
+        Fragment::Sha256(_)
+        | Fragment::Hash256(_)
+        | Fragment::Ripemd160(_)
+        | Fragment::Hash160(_) => (true, true),
+        Fragment::WrapA
+        | Fragment::WrapS
+        | Fragment::WrapC
+        | Fragment::WrapD
+        | Fragment::WrapV
+        | Fragment::WrapJ
+        | Fragment::WrapN => subs[0],
+        Fragment::AndV | Fragment::AndB => {
+            let sat = subs[0].0 && subs[1].0;
+            (sat, sat && (subs[0].1 || subs[1].1))
+        }
+        Fragment::OrB | Fragment::OrC | Fragment::OrD | Fragment::OrI => {
+            (subs[0].0 || subs[1].0, subs[0].1 || subs[1].1)
+        }
+        Fragment::AndOr => {
+            let and_sat = subs[0].0 && subs[1].0;
+            (
+                and_sat || subs[2].0,
+                (and_sat && (subs[0].1 || subs[1].1)) || subs[2].1,
+            )
+        }
+        Fragment::Thresh { k, .. } => {
+            let k = *k as usize;
+            let satisfiable = subs.iter().filter(|(sat, _)| *sat).count();
+            // A preimage is revealed if a sub revealing one can be satisfied
+            // together with k - 1 of the others
+            let preimage = k > 0 && satisfiable >= k && subs.iter().any(|(_, preimage)| *preimage);
+            (satisfiable >= k, preimage)
+        }
+    }
+}
+
+/// Get the depth of the tree described by a pre-order fragment sequence:
+/// the number of fragments on its longest root-to-leaf path.
+pub(crate) fn depth(fragments: &[Fragment]) -> usize {
+    // Children still to visit at each level of the current path
+    let mut pending: Vec<usize> = Vec::new();
+    let mut max = 0;
+    for fragment in fragments {
+        while pending.last() == Some(&0) {
+            pending.pop();
+        }
+        if let Some(remaining) = pending.last_mut() {
+            *remaining -= 1;
+        }
+        max = max.max(pending.len() + 1);
+        pending.push(fragment.child_count());
+    }
+    max
+}
+
+/// Assign each disjunctive branch of a pre-order fragment sequence the
+/// probability of being used, assuming every alternative is equally likely.
+///
+/// Returns `(index, probability)` pairs, where `index` is the branch's
+/// position in `fragments`, in pre-order. The two sides of an `or_*` each get
+/// half of their parent's probability, the `X` and `Z` of `andor(X,Y,Z)`
+/// stand for its two branches, and each of the `n` subs of `thresh(k,...)`
+/// gets `k/n` of it. Returns `None` if the sequence does not describe a
+/// complete tree.
+pub(crate) fn branch_probabilities(fragments: &[Fragment]) -> Option<Vec<(usize, f64)>> {
+    let mut out = Vec::new();
+    // Each fragment whose children are being visited, with its probability
+    // and the position of its next child
+    let mut open: Vec<(&Fragment, f64, usize)> = Vec::new();
+    for (index, fragment) in fragments.iter().enumerate() {
+        let probability = match open.last_mut() {
+            // The tree was already complete
+            None if index > 0 => return None,
+            None => 1.0,
+            Some((parent, probability, next)) => {
+                let i = *next;
+                *next += 1;
+                #[allow(clippy::cast_precision_loss)]
+                let share = match parent {
+                    Fragment::OrB | Fragment::OrC | Fragment::OrD | Fragment::OrI => Some(0.5),
+                    // Y is only used together with X
+                    Fragment::AndOr if i != 1 => Some(0.5),
+                    Fragment::AndOr => None,
+                    Fragment::Thresh { k, n } => Some(f64::from(*k) / *n as f64),
+                    _ => None,
+                };
+                match share {
+                    Some(share) => {
+                        out.push((index, *probability * share));
+                        *probability * share
+                    }
+                    None if matches!(parent, Fragment::AndOr) => *probability * 0.5,
+                    None => *probability,
+                }
+            }
+        };
+        if fragment.child_count() > 0 {
+            open.push((fragment, probability, 0));
+        }
+        // Leave every fragment whose last child has been visited
+        while let Some((parent, _, next)) = open.last() {
+            if *next < parent.child_count() {
+                break;
+            }
+            open.pop();
+        }
+    }
+    if fragments.is_empty() || !open.is_empty() {
+        return None;
+    }
+    Some(out)
+}
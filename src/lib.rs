@@ -144,21 +144,21 @@
 //!
 //! | Flag | Meaning |
 //! |------|---------|
-//! | `B` | Base expression (consumes nothing, produces nonzero) |
-//! | `V` | Verify expression (consumes nothing, produces nothing, fails if unsatisfied) |
-//! | `K` | Key expression (consumes nothing, produces a public key) |
-//! | `W` | Wrapped expression (consumes one stack element) |
-//! | `z` | Zero-arg property (consumes no stack elements) |
-//! | `o` | One-arg property (consumes exactly one stack element) |
-//! | `n` | Nonzero property (never produces zero) |
-//! | `d` | Dissatisfiable property (has a dissatisfaction) |
-//! | `u` | Unit property (on satisfaction, puts exactly 1 on stack) |
-//! | `e` | Expression property (can be used as an expression) |
-//! | `f` | Forced property (always requires a signature) |
-//! | `s` | Safe property (cannot be malleated) |
-//! | `m` | Nonmalleable property (satisfaction is unique) |
-//! | `x` | Expensive verify property |
-//! | `k` | Timelock property (contains a timelock) |
+//! | `B` | Base expression (takes its inputs from the top of the stack, pushes nonzero when satisfied and 0 when dissatisfied) |
+//! | `V` | Verify expression (takes its inputs from the top of the stack, pushes nothing when satisfied, cannot be dissatisfied) |
+//! | `K` | Key expression (takes its inputs from the top of the stack, pushes a public key that a signature must be provided for) |
+//! | `W` | Wrapped expression (takes its inputs from one below the top of the stack, pushes like a base expression) |
+//! | `z` | Zero-arg property (always consumes exactly 0 stack elements) |
+//! | `o` | One-arg property (always consumes exactly 1 stack element) |
+//! | `n` | Nonzero property (no satisfaction needs a zero top stack element) |
+//! | `d` | Dissatisfiable property (a dissatisfaction is easy to construct) |
+//! | `u` | Unit property (pushes exactly 1 when satisfied) |
+//! | `e` | Expression property (has a nonmalleable dissatisfaction) |
+//! | `f` | Forced property (every dissatisfaction needs a signature) |
+//! | `s` | Safe property (every satisfaction needs a signature) |
+//! | `m` | Nonmalleable property (a nonmalleable satisfaction exists for every way to satisfy it) |
+//! | `x` | Expensive verify property (last opcode is not EQUAL, CHECKSIG or CHECKMULTISIG) |
+//! | `k` | No timelock mix property (does not combine height and time locks) |
 //!
 //! ## Thread Safety
 //!
@@ -185,6 +185,24 @@
 //! }
 //! ```
 //!
+//! ## Build Configuration
+//!
+//! The build script fetches and compiles Bitcoin Core, which can be
+//! controlled with environment variables:
+//!
+//! - `BITCOIN_CORE_VERSION`: the Bitcoin Core tag to fetch (default
+//!   `v30.2`), e.g. to test a release candidate. It has no effect on a
+//!   vendored checkout or `BITCOIN_CORE_SRC`; [`bitcoin_core_version()`]
+//!   reports the release of the source actually compiled.
+//! - `BITCOIN_CORE_SRC`: an existing Bitcoin Core `src` directory to build
+//!   instead of fetching one.
+//! - `BITCOIN_CORE_LIB_DIR`: with the `system-bitcoin` feature, a directory
+//!   of prebuilt libraries to link instead of building.
+//!
+//! ```bash
+//! BITCOIN_CORE_VERSION=v30.3rc1 cargo build
+//! ```
+//!
 //! ## Comparison with rust-miniscript
 //!
 //! | Feature | bitcoin-core-miniscript-ffi | rust-miniscript |
@@ -212,7 +230,9 @@ mod ffi {
 }
 
 // Re-export FFI types that are used in the public API
-pub use ffi::{MiniscriptAvailability, MiniscriptContext, MiniscriptNode, MiniscriptResult};
+pub use ffi::{
+    DescriptorNode, MiniscriptAvailability, MiniscriptContext, MiniscriptNode, MiniscriptResult,
+};
 pub use ffi::{SatisfactionResult as FfiSatisfactionResult, SatisfierCallbacks};
 
 // Import FFI functions for internal use
@@ -224,20 +244,39 @@ use ffi::{
     miniscript_has_timelock_mix, miniscript_is_non_malleable, miniscript_is_sane,
     miniscript_is_valid, miniscript_is_valid_top_level, miniscript_max_satisfaction_size,
     miniscript_needs_signature, miniscript_node_free, miniscript_satisfaction_result_free,
-    miniscript_satisfy, miniscript_to_script, miniscript_to_string, miniscript_valid_satisfactions,
-    miniscript_version,
+    miniscript_satisfaction_size_with_sig, miniscript_satisfy, miniscript_to_script,
+    miniscript_to_string, miniscript_valid_satisfactions, miniscript_version,
 };
 
+// Spans around FFI calls (feature `tracing`)
+mod trace;
+use trace::ffi_span;
+
+// Fragment tree inspection
+pub mod fragment;
+pub use fragment::{Fragment, FragmentKind, HashKind, HashLock, KeyUsage, PkKind};
+
+// Typed expression construction
+pub mod builder;
+pub use builder::MiniscriptBuilder;
+
+// Policy lifting
+pub mod policy;
+pub use policy::{Policy, Template};
+
 // Descriptor module
 pub mod descriptor;
 pub use descriptor::{
-    Descriptor, DescriptorBuilder, Network as DescriptorNetwork, descriptor_version,
-    get_descriptor_checksum,
+    ChecksumStatus, DerivedOutput, Descriptor, DescriptorBuilder, DescriptorCache,
+    DescriptorSatisfaction, Network as DescriptorNetwork, SigningRequirement, TapLeaf,
+    descriptor_version, get_descriptor_checksum, validate_checksum,
 };
 
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 
 // Re-export bitcoin types for convenience
@@ -269,7 +308,9 @@ pub use bitcoin::taproot::Signature as SchnorrSignature;
 /// // Parse for SegWit v1 (Tapscript)
 /// let tap = Miniscript::from_str("pk(A)", Context::Tapscript);
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Contexts are ordered by `SegWit` version: `Wsh < Tapscript`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Context {
     /// P2WSH context (`SegWit` v0)
     ///
@@ -293,6 +334,78 @@ impl From<Context> for MiniscriptContext {
     }
 }
 
+impl Context {
+    /// Get the script limits Bitcoin Core enforces in this context.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::Context;
+    ///
+    /// let limits = Context::Wsh.limits();
+    /// assert_eq!(limits.max_script_size, 3600);
+    /// assert_eq!(limits.max_ops, Some(201));
+    /// ```
+    #[must_use]
+    pub fn limits(self) -> ContextLimits {
+        let mut limits = ffi::MiniscriptContextLimits {
+            max_script_size: 0,
+            max_ops: 0,
+            max_witness_items: 0,
+            max_exec_stack_size: 0,
+        };
+        // SAFETY: limits is a valid output struct
+        unsafe { ffi::miniscript_context_limits(self.into(), &raw mut limits) };
+
+        let limit = |value: u32| (value != 0).then_some(value as usize);
+        ContextLimits {
+            max_script_size: limits.max_script_size as usize,
+            max_ops: limit(limits.max_ops),
+            max_witness_items: limit(limits.max_witness_items),
+            max_exec_stack_size: limit(limits.max_exec_stack_size),
+        }
+    }
+
+    /// Length of a signature in this context, without its length prefix.
+    ///
+    /// With `max`, the largest standard signature: a high-R DER signature
+    /// plus sighash byte (72 bytes) or a Schnorr signature with an explicit
+    /// sighash byte (65). Otherwise the usual size: low-R (71) or default
+    /// sighash (64).
+    const fn sig_len(self, max: bool) -> usize {
+        match (self, max) {
+            (Self::Wsh, true) => 72,
+            (Self::Wsh, false) => 71,
+            (Self::Tapscript, true) => 65,
+            (Self::Tapscript, false) => 64,
+        }
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wsh => write!(f, "wsh"),
+            Self::Tapscript => write!(f, "tapscript"),
+        }
+    }
+}
+
+impl std::str::FromStr for Context {
+    type Err = Error;
+
+    /// Parse a context from `"wsh"` or `"tapscript"` (case-insensitive).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wsh" => Ok(Self::Wsh),
+            "tapscript" => Ok(Self::Tapscript),
+            _ => Err(Error::new(format!(
+                "unknown context '{s}', expected 'wsh' or 'tapscript'"
+            ))),
+        }
+    }
+}
+
 /// Availability of a satisfaction.
 ///
 /// Indicates whether a miniscript can be satisfied with the provided data.
@@ -337,6 +450,370 @@ impl From<MiniscriptAvailability> for Availability {
     }
 }
 
+impl fmt::Display for Availability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::No => write!(f, "no"),
+            Self::Yes => write!(f, "yes"),
+            Self::Maybe => write!(f, "maybe"),
+        }
+    }
+}
+
+/// Where a miniscript mixes heightlocks and timelocks.
+///
+/// Mixing happens when a single spending path requires both a block-height
+/// and a block-time lock of the same kind, which can never be satisfied.
+/// See [`Miniscript::timelock_mix_detail()`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimelockMixReport {
+    /// Heights and times are mixed among absolute (`after`) timelocks.
+    pub height_time_in_after: bool,
+    /// Heights and times are mixed among relative (`older`) timelocks.
+    pub height_time_in_older: bool,
+}
+
+impl TimelockMixReport {
+    /// Check whether any timelock mixing was found.
+    #[must_use]
+    pub const fn has_mix(&self) -> bool {
+        self.height_time_in_after || self.height_time_in_older
+    }
+}
+
+/// A sanity issue found by [`Miniscript::parse_checked()`] or
+/// [`Miniscript::sanity()`].
+///
+/// Each warning corresponds to a condition that makes a miniscript fail
+/// [`Miniscript::is_sane()`] while still being valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Warning {
+    /// The miniscript has malleable satisfactions.
+    Malleable,
+    /// The miniscript can be satisfied without any signature.
+    NoSignatureRequired,
+    /// The miniscript mixes heightlocks and timelocks in a spending path.
+    TimelockMix,
+    /// The miniscript is not of type `B` and cannot be used as a top-level script.
+    NotTopLevel,
+    /// The same key is used more than once.
+    DuplicateKey,
+    /// The ops or stack size limits of the context are exceeded.
+    ResourceLimitExceeded,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malleable => write!(f, "miniscript is malleable"),
+            Self::NoSignatureRequired => write!(f, "miniscript can be spent without a signature"),
+            Self::TimelockMix => write!(f, "miniscript mixes heightlocks and timelocks"),
+            Self::NotTopLevel => write!(f, "miniscript is not valid at the top level"),
+            Self::DuplicateKey => write!(f, "miniscript contains duplicate keys"),
+            Self::ResourceLimitExceeded => write!(f, "miniscript exceeds resource limits"),
+        }
+    }
+}
+
+impl std::error::Error for Warning {}
+
+/// Result of [`Miniscript::sanity()`]: the first sanity check that fails,
+/// as a [`Warning`].
+pub type SanityResult = Result<(), Warning>;
+
+/// The type property flags Bitcoin Core reports, with their meaning as
+/// documented in Core's `script/miniscript.h`.
+const TYPE_FLAGS: [(char, &str); 15] = [
+    (
+        'B',
+        "Base expression (takes its inputs from the top of the stack, pushes nonzero when satisfied and 0 when dissatisfied)",
+    ),
+    (
+        'V',
+        "Verify expression (takes its inputs from the top of the stack, pushes nothing when satisfied, cannot be dissatisfied)",
+    ),
+    (
+        'K',
+        "Key expression (takes its inputs from the top of the stack, pushes a public key that a signature must be provided for)",
+    ),
+    (
+        'W',
+        "Wrapped expression (takes its inputs from one below the top of the stack, pushes like a base expression)",
+    ),
+    (
+        'z',
+        "Zero-arg property (always consumes exactly 0 stack elements)",
+    ),
+    (
+        'o',
+        "One-arg property (always consumes exactly 1 stack element)",
+    ),
+    (
+        'n',
+        "Nonzero property (no satisfaction needs a zero top stack element)",
+    ),
+    (
+        'd',
+        "Dissatisfiable property (a dissatisfaction is easy to construct)",
+    ),
+    ('u', "Unit property (pushes exactly 1 when satisfied)"),
+    (
+        'e',
+        "Expression property (has a nonmalleable dissatisfaction)",
+    ),
+    (
+        'f',
+        "Forced property (every dissatisfaction needs a signature)",
+    ),
+    ('s', "Safe property (every satisfaction needs a signature)"),
+    (
+        'm',
+        "Nonmalleable property (a nonmalleable satisfaction exists for every way to satisfy it)",
+    ),
+    (
+        'x',
+        "Expensive verify property (last opcode is not EQUAL, CHECKSIG or CHECKMULTISIG)",
+    ),
+    (
+        'k',
+        "No timelock mix property (does not combine height and time locks)",
+    ),
+];
+
+/// The type properties of a miniscript, from [`Miniscript::typ()`].
+///
+/// Each property is a single-character flag, as in the string returned by
+/// [`Miniscript::get_type()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MiniscriptType {
+    flags: String,
+}
+
+impl MiniscriptType {
+    /// Check if the flag is set, e.g. `'B'` for a base expression.
+    #[must_use]
+    pub fn has(&self, flag: char) -> bool {
+        self.flags.contains(flag)
+    }
+
+    /// Get the flags as a string like `"Bdems"`.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.flags
+    }
+
+    /// Get each set flag with a description of its meaning.
+    ///
+    /// Flags are listed in a fixed order: the basic type (`B`, `V`, `K` or
+    /// `W`) first, then the properties.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// for (flag, meaning) in ms.typ().unwrap().explain() {
+    ///     println!("{flag}: {meaning}");
+    /// }
+    /// ```
+    #[must_use]
+    pub fn explain(&self) -> Vec<(char, &'static str)> {
+        TYPE_FLAGS
+            .into_iter()
+            .filter(|(flag, _)| self.has(*flag))
+            .collect()
+    }
+}
+
+impl fmt::Display for MiniscriptType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.flags)
+    }
+}
+
+/// Context tags of the [`Miniscript::to_bytes()`] serialization.
+const CONTEXT_TAG_WSH: u8 = 0;
+const CONTEXT_TAG_TAPSCRIPT: u8 = 1;
+
+/// Default maximum input length accepted by [`Miniscript::from_str()`] (1 MiB).
+///
+/// No valid miniscript comes close: even Tapscript is bounded by the
+/// standard transaction weight, so longer input is rejected before parsing.
+pub const MAX_INPUT_LEN: usize = 1 << 20;
+
+/// Maximum number of spend paths examined by
+/// [`Miniscript::required_signers()`],
+/// [`Miniscript::branch_timelocks()`],
+/// [`Miniscript::has_nonmalleable_satisfaction()`] and
+/// [`Miniscript::worst_case_branch_weight()`].
+///
+/// Each path costs one satisfaction attempt, and thresholds make the number
+/// of paths grow combinatorially (`thresh(10,...)` over 20 keys already has
+/// 184,756), so analysis stops after this many paths. Each method documents
+/// how it reports miniscripts with more.
+pub const MAX_SPEND_PATHS: usize = 1000;
+
+/// The script limits Bitcoin Core enforces for a context.
+///
+/// Returned by [`Context::limits()`]. The values come from Bitcoin Core's
+/// consensus and policy constants, so they follow the version the crate is
+/// built against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ContextLimits {
+    /// Maximum script size in bytes (3600 in P2WSH; in Tapscript the bound
+    /// implied by the maximum standard transaction weight).
+    pub max_script_size: usize,
+    /// Maximum non-push opcodes (201 in P2WSH, none in Tapscript).
+    pub max_ops: Option<usize>,
+    /// Maximum standard witness stack items, not counting the witness
+    /// script (100 in P2WSH, none in Tapscript). Checked by
+    /// [`Miniscript::check_stack_size()`] in P2WSH.
+    pub max_witness_items: Option<usize>,
+    /// Maximum execution stack elements, including the altstack (1000 in
+    /// Tapscript). Checked by [`Miniscript::check_stack_size()`] in
+    /// Tapscript; Bitcoin Core's miniscript does not check it for P2WSH.
+    pub max_exec_stack_size: Option<usize>,
+}
+
+/// A single measured resource together with its applicable limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimit {
+    /// The measured value, or `None` if it could not be computed (for
+    /// example when the miniscript cannot be satisfied).
+    pub used: Option<usize>,
+    /// The limit for the miniscript's context, or `None` if none applies.
+    pub limit: Option<usize>,
+    /// Whether the measured value is within the limit.
+    ///
+    /// This is `true` when either the value or the limit is unknown.
+    pub within_limit: bool,
+}
+
+impl ResourceLimit {
+    fn new(used: Option<usize>, limit: Option<usize>) -> Self {
+        let within_limit = match (used, limit) {
+            (Some(used), Some(limit)) => used <= limit,
+            _ => true,
+        };
+        Self {
+            used,
+            limit,
+            within_limit,
+        }
+    }
+}
+
+/// Resource usage of a miniscript against the limits of its context.
+///
+/// See [`Miniscript::resource_usage()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// Non-push opcodes executed (limited to 201 in P2WSH, unlimited in Tapscript).
+    pub ops: ResourceLimit,
+    /// Witness stack items needed to satisfy (limited to 100 in P2WSH).
+    pub stack_size: ResourceLimit,
+    /// Maximum execution stack size (limited to 1000 in Tapscript).
+    pub exec_stack_size: ResourceLimit,
+    /// Script size in bytes (3600 in P2WSH, bounded by transaction weight in Tapscript).
+    pub script_size: ResourceLimit,
+}
+
+impl ResourceUsage {
+    /// Check whether every resource is within its limit.
+    #[must_use]
+    pub const fn within_limits(&self) -> bool {
+        self.ops.within_limit
+            && self.stack_size.within_limit
+            && self.exec_stack_size.within_limit
+            && self.script_size.within_limit
+    }
+}
+
+/// A resource checked against the standardness limits of a context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitKind {
+    /// Non-push opcodes executed.
+    Ops,
+    /// Witness stack items needed to satisfy.
+    StackSize,
+    /// Maximum execution stack size.
+    ExecStackSize,
+    /// Script size in bytes.
+    ScriptSize,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ops => write!(f, "ops"),
+            Self::StackSize => write!(f, "stack size"),
+            Self::ExecStackSize => write!(f, "execution stack size"),
+            Self::ScriptSize => write!(f, "script size"),
+        }
+    }
+}
+
+/// A resource that exceeds its limit.
+///
+/// See [`Miniscript::exceeds_standard_limits()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LimitViolation {
+    /// The exceeded resource.
+    pub kind: LimitKind,
+    /// The measured value.
+    pub measured: usize,
+    /// The limit for the miniscript's context.
+    pub max: usize,
+}
+
+impl fmt::Display for LimitViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} exceeds the limit of {}",
+            self.kind, self.measured, self.max
+        )
+    }
+}
+
+/// Size and cost metrics of a miniscript in one context.
+///
+/// See [`Miniscript::compare_contexts()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextMetrics {
+    /// Script size in bytes.
+    pub script_size: Option<usize>,
+    /// Number of non-push opcodes.
+    pub ops: Option<u32>,
+    /// Maximum witness weight of a satisfaction, excluding the script itself.
+    ///
+    /// Witness data weighs one weight unit per byte, so this equals
+    /// [`Miniscript::max_satisfaction_size()`].
+    pub max_satisfaction_weight: Option<usize>,
+}
+
+impl ContextMetrics {
+    fn of(ms: &Miniscript) -> Self {
+        Self {
+            script_size: ms.get_script_size(),
+            ops: ms.get_ops(),
+            max_satisfaction_weight: ms.max_satisfaction_size(),
+        }
+    }
+}
+
+/// The same miniscript string compiled for both script contexts.
+///
+/// See [`Miniscript::compare_contexts()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextComparison {
+    /// Metrics in the P2WSH context, or the error parsing it there.
+    pub wsh: Result<ContextMetrics, Error>,
+    /// Metrics in the Tapscript context, or the error parsing it there.
+    pub tapscript: Result<ContextMetrics, Error>,
+}
+
 impl From<Availability> for MiniscriptAvailability {
     fn from(avail: Availability) -> Self {
         match avail {
@@ -349,8 +826,9 @@ impl From<Availability> for MiniscriptAvailability {
 
 /// Error type for miniscript operations.
 ///
-/// Contains a human-readable error message describing what went wrong.
-/// This error type is returned by parsing and satisfaction operations.
+/// This error type is returned by parsing and satisfaction operations. Its
+/// [`Display`](fmt::Display) output is a human-readable description of what
+/// went wrong.
 ///
 /// # Example
 ///
@@ -362,15 +840,217 @@ impl From<Availability> for MiniscriptAvailability {
 ///     println!("Parse error: {}", e);
 /// }
 /// ```
-#[derive(Debug)]
-pub struct Error {
-    /// The error message describing what went wrong.
-    message: String,
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error reported by Bitcoin Core or the FFI layer.
+    Core {
+        /// The error message describing what went wrong.
+        message: String,
+        /// The stable error code reported by the C wrapper, if any.
+        code: Option<ErrorCode>,
+    },
+    /// Satisfaction could not be completed because of the `Satisfier`.
+    Satisfaction(String),
+    /// A key is a descriptor key (an extended or WIF key, possibly with a
+    /// key origin or derivation path) that bare miniscript cannot
+    /// represent. Parse such keys with the [`Descriptor`] API instead.
+    ///
+    /// Hex public keys and other key names are accepted as they are.
+    UnsupportedKeyFormat {
+        /// The offending key as written in the input.
+        key: String,
+    },
+    /// The input is longer than the allowed maximum and was not parsed.
+    ///
+    /// See [`Miniscript::from_str_limited()`] and [`MAX_INPUT_LEN`].
+    InputTooLong {
+        /// Length of the input in bytes.
+        len: usize,
+        /// The maximum accepted length in bytes.
+        max: usize,
+    },
+    /// A public key has the wrong length for its script context.
+    ///
+    /// See [`SimpleSatisfier::insert_signature()`].
+    InvalidKeyLength {
+        /// The script context the key was used in.
+        context: Context,
+        /// Length of the key in bytes.
+        len: usize,
+        /// The key length the context requires.
+        expected: usize,
+    },
+    /// The miniscript is nested deeper than allowed.
+    ///
+    /// See [`Miniscript::from_str_bounded()`].
+    TooDeep {
+        /// The depth found. Inputs rejected before parsing report a lower
+        /// bound read from the text.
+        depth: usize,
+        /// The maximum accepted depth.
+        max: usize,
+    },
+    /// The input contains a byte that cannot appear in a miniscript: a
+    /// non-ASCII byte or a null byte.
+    NonAsciiInput {
+        /// Byte offset of the first offending byte.
+        position: usize,
+    },
+    /// Two satisfiers hold different data for the same key or hash.
+    ///
+    /// See [`SimpleSatisfier::merge()`].
+    SatisfierConflict {
+        /// The public key or hash digest with conflicting entries.
+        key: Vec<u8>,
+    },
+    /// A script contains an opcode that only exists in the other context,
+    /// e.g. `OP_CHECKSIGADD` decoded as [`Context::Wsh`].
+    ///
+    /// See [`Miniscript::from_script_bytes()`].
+    ContextMismatch {
+        /// The context the script was decoded under.
+        expected: Context,
+        /// The first opcode that does not belong to `expected`.
+        found_opcode: bitcoin::opcodes::Opcode,
+    },
+}
+
+impl Error {
+    /// Create an error that did not originate in the C wrapper.
+    fn new(message: impl Into<String>) -> Self {
+        Self::Core {
+            message: message.into(),
+            code: None,
+        }
+    }
+
+    /// Create an error from a failed C wrapper result.
+    fn from_ffi(message: String, code: i32) -> Self {
+        match ErrorCode::from_ffi(code) {
+            // The wrapper reports the offending key as the message
+            ErrorCode::UnknownKeyFormat => Self::UnsupportedKeyFormat { key: message },
+            code => Self::Core {
+                message,
+                code: Some(code),
+            },
+        }
+    }
+
+    /// Get the stable error code reported by the C wrapper.
+    ///
+    /// Unlike the message, which comes from Bitcoin Core and may change
+    /// between versions, codes are stable. See [`ErrorCode`] for their
+    /// meaning. Errors raised on the Rust side have no code.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, ErrorCode, Miniscript};
+    ///
+    /// let err = Miniscript::from_str("pk(A", Context::Wsh).unwrap_err();
+    /// assert_eq!(err.code(), Some(ErrorCode::Syntax));
+    /// ```
+    #[must_use]
+    pub const fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Core { code, .. } => *code,
+            Self::Satisfaction(_)
+            | Self::InputTooLong { .. }
+            | Self::InvalidKeyLength { .. }
+            | Self::TooDeep { .. }
+            | Self::NonAsciiInput { .. }
+            | Self::SatisfierConflict { .. }
+            | Self::ContextMismatch { .. } => None,
+            Self::UnsupportedKeyFormat { .. } => Some(ErrorCode::UnknownKeyFormat),
+        }
+    }
+}
+
+/// Stable error codes reported by the C wrapper.
+///
+/// These mirror `MiniscriptErrorCode` in `miniscript_wrapper.h` and are
+/// returned by [`Error::code()`]. Existing values never change meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum ErrorCode {
+    /// Unclassified failure.
+    Unknown = 1,
+    /// A null pointer or invalid context was passed.
+    InvalidArgument = 2,
+    /// The input could not be parsed.
+    Syntax = 3,
+    /// The input parsed but fails type checking.
+    Type = 4,
+    /// The script exceeds the size limits of its context.
+    ResourceLimit = 5,
+    /// A key uses descriptor key syntax that bare miniscript cannot
+    /// represent, reported as [`Error::UnsupportedKeyFormat`].
+    UnknownKeyFormat = 6,
+    /// A descriptor checksum does not match.
+    Checksum = 7,
+    /// The script is not a valid miniscript encoding.
+    ScriptDecode = 8,
+}
+
+impl ErrorCode {
+    /// Map a code from the C wrapper, taking values this version does not
+    /// know as [`ErrorCode::Unknown`].
+    const fn from_ffi(code: i32) -> Self {
+        match code {
+            2 => Self::InvalidArgument,
+            3 => Self::Syntax,
+            4 => Self::Type,
+            5 => Self::ResourceLimit,
+            6 => Self::UnknownKeyFormat,
+            7 => Self::Checksum,
+            8 => Self::ScriptDecode,
+            _ => Self::Unknown,
+        }
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message)
+        match self {
+            Self::Core { message, .. } => write!(f, "{message}"),
+            Self::Satisfaction(message) => write!(f, "satisfaction failed: {message}"),
+            Self::UnsupportedKeyFormat { key } => write!(
+                f,
+                "unsupported key format '{key}', use the Descriptor API for descriptor keys"
+            ),
+            Self::InputTooLong { len, max } => {
+                write!(f, "input is {len} bytes long, the maximum is {max}")
+            }
+            Self::InvalidKeyLength {
+                context,
+                len,
+                expected,
+            } => write!(
+                f,
+                "{context} keys must be {expected} bytes long, got {len} bytes"
+            ),
+            Self::TooDeep { depth, max } => {
+                write!(
+                    f,
+                    "miniscript is nested {depth} levels deep, the maximum is {max}"
+                )
+            }
+            Self::NonAsciiInput { position } => {
+                write!(
+                    f,
+                    "input contains a non-ASCII or null byte at position {position}"
+                )
+            }
+            Self::SatisfierConflict { key } => {
+                use bitcoin::hex::DisplayHex;
+                write!(f, "satisfiers hold different data for {}", key.as_hex())
+            }
+            Self::ContextMismatch {
+                expected,
+                found_opcode,
+            } => write!(f, "{found_opcode} cannot appear in a {expected} script"),
+        }
     }
 }
 
@@ -435,7 +1115,8 @@ pub trait Satisfier: Send {
     ///
     /// A tuple of (availability, optional signature bytes). Return `Availability::Yes`
     /// with the signature if signing succeeds, or `Availability::No` with `None` if
-    /// the key is not available.
+    /// the key is not available. A signature reported as available without any
+    /// bytes is treated as unavailable.
     fn sign(&self, key: &[u8]) -> (Availability, Option<Vec<u8>>);
 
     /// Check if the absolute timelock is satisfied.
@@ -468,7 +1149,8 @@ pub trait Satisfier: Send {
     ///
     /// # Returns
     ///
-    /// A tuple of (availability, optional preimage bytes).
+    /// A tuple of (availability, optional preimage bytes). Preimages must be
+    /// 32 bytes; any other length is treated as unavailable.
     fn sat_sha256(&self, hash: &[u8]) -> (Availability, Option<Vec<u8>>);
 
     /// Get the preimage for a RIPEMD160 hash.
@@ -479,7 +1161,8 @@ pub trait Satisfier: Send {
     ///
     /// # Returns
     ///
-    /// A tuple of (availability, optional preimage bytes).
+    /// A tuple of (availability, optional preimage bytes). Preimages must be
+    /// 32 bytes; any other length is treated as unavailable.
     fn sat_ripemd160(&self, hash: &[u8]) -> (Availability, Option<Vec<u8>>);
 
     /// Get the preimage for a HASH256 (double SHA256) hash.
@@ -490,7 +1173,8 @@ pub trait Satisfier: Send {
     ///
     /// # Returns
     ///
-    /// A tuple of (availability, optional preimage bytes).
+    /// A tuple of (availability, optional preimage bytes). Preimages must be
+    /// 32 bytes; any other length is treated as unavailable.
     fn sat_hash256(&self, hash: &[u8]) -> (Availability, Option<Vec<u8>>);
 
     /// Get the preimage for a HASH160 hash.
@@ -501,10 +1185,75 @@ pub trait Satisfier: Send {
     ///
     /// # Returns
     ///
-    /// A tuple of (availability, optional preimage bytes).
+    /// A tuple of (availability, optional preimage bytes). Preimages must be
+    /// 32 bytes; any other length is treated as unavailable.
     fn sat_hash160(&self, hash: &[u8]) -> (Availability, Option<Vec<u8>>);
 }
 
+/// Maps key names to the public keys they stand for in scripts.
+///
+/// Bare miniscript keys are plain strings, so `pk(A)` does not name a real
+/// key. When encoding a script, and when asking a [`Satisfier`] to sign, the
+/// C wrapper turns each name into public key bytes: hex keys of the
+/// context's length (33 bytes for [`Context::Wsh`], 32 for
+/// [`Context::Tapscript`]) decode as themselves, and any other name maps to
+/// a deterministic dummy key. The dummy key is the first
+/// `SHA256(name || counter)`, with a 4-byte little-endian counter starting
+/// at 0, that is a valid x-only key, prefixed with `0x02` outside Tapscript.
+/// Distinct names thus get distinct, valid keys.
+///
+/// [`pubkey_for()`](Self::pubkey_for) asks the C wrapper for these bytes, so
+/// signatures keyed by them are found when satisfying.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use miniscript_core_ffi::{Availability, Context, DummyKeyMap, Miniscript, SimpleSatisfier};
+///
+/// let keys = DummyKeyMap::new();
+/// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+///
+/// let mut satisfier = SimpleSatisfier::new();
+/// satisfier.signatures.insert(keys.pubkey_for("A", Context::Wsh), vec![0x30; 72]);
+/// let result = ms.satisfy(satisfier, true).unwrap();
+/// assert_eq!(result.availability, Availability::Yes);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DummyKeyMap;
+
+impl DummyKeyMap {
+    /// Create a key map.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Get the public key bytes `name` stands for in `ctx`.
+    ///
+    /// Returns 33 bytes for [`Context::Wsh`] and 32 bytes for
+    /// [`Context::Tapscript`].
+    #[must_use]
+    pub fn pubkey_for(&self, name: &str, ctx: Context) -> Vec<u8> {
+        let mut key = [0u8; 33];
+        let mut len: usize = 0;
+        // SAFETY: name is valid for name.len() bytes and key holds 33 bytes
+        let success = unsafe {
+            ffi::miniscript_key_bytes(
+                name.as_ptr().cast(),
+                name.len(),
+                ctx.into(),
+                key.as_mut_ptr(),
+                &raw mut len,
+            )
+        };
+        if success {
+            key[..len.min(key.len())].to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 /// A simple satisfier that uses pre-populated data.
 ///
 /// This is a convenience implementation of [`Satisfier`] that stores signatures,
@@ -514,12 +1263,13 @@ pub trait Satisfier: Send {
 /// # Example
 ///
 /// ```rust,no_run
-/// use miniscript_core_ffi::SimpleSatisfier;
+/// use miniscript_core_ffi::{Context, DummyKeyMap, SimpleSatisfier};
 ///
 /// let mut satisfier = SimpleSatisfier::new();
 ///
-/// // Add a signature for key "A"
-/// satisfier.signatures.insert(b"A".to_vec(), vec![0x30, 0x44, /* ... */]);
+/// // Add a signature for key "A", keyed by the public key it stands for
+/// let key = DummyKeyMap::new().pubkey_for("A", Context::Wsh);
+/// satisfier.signatures.insert(key, vec![0x30, 0x44, /* ... */]);
 ///
 /// // Mark absolute timelock 500000 as satisfied
 /// satisfier.after_satisfied.insert(500000);
@@ -560,6 +1310,168 @@ impl SimpleSatisfier {
             hash160_preimages: HashMap::new(),
         }
     }
+
+    /// Create a satisfier from the signatures and preimages of a PSBT input.
+    ///
+    /// `partial_sigs` and `tap_script_sigs` are keyed by their serialized
+    /// public key (33 or 65 bytes, or 32 bytes x-only) in `signatures`, and
+    /// the PSBT preimage maps are keyed by the hash bytes as they appear in
+    /// the script. PSBT inputs carry no timelock information, so no
+    /// timelocks are marked as satisfied.
+    ///
+    /// A key may sign several leaves of a taproot tree, with a different
+    /// signature for each, so only the `tap_script_sigs` for `leaf_hash`
+    /// (the leaf being satisfied) are kept. Pass `None` for non-taproot
+    /// scripts, which ignores `tap_script_sigs`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::SimpleSatisfier;
+    ///
+    /// let input = bitcoin::psbt::Input::default();
+    /// let satisfier = SimpleSatisfier::from_psbt_input(&input, None);
+    /// ```
+    #[must_use]
+    pub fn from_psbt_input(
+        input: &bitcoin::psbt::Input,
+        leaf_hash: Option<bitcoin::TapLeafHash>,
+    ) -> Self {
+        use bitcoin::hashes::Hash;
+
+        let mut satisfier = Self::new();
+        for (key, sig) in &input.partial_sigs {
+            satisfier.signatures.insert(key.to_bytes(), sig.to_vec());
+        }
+        for ((key, leaf), sig) in &input.tap_script_sigs {
+            if Some(*leaf) != leaf_hash {
+                continue;
+            }
+            satisfier
+                .signatures
+                .insert(key.serialize().to_vec(), sig.to_vec());
+        }
+        for (hash, preimage) in &input.sha256_preimages {
+            satisfier
+                .sha256_preimages
+                .insert(hash.to_byte_array().to_vec(), preimage.clone());
+        }
+        for (hash, preimage) in &input.ripemd160_preimages {
+            satisfier
+                .ripemd160_preimages
+                .insert(hash.to_byte_array().to_vec(), preimage.clone());
+        }
+        for (hash, preimage) in &input.hash256_preimages {
+            satisfier
+                .hash256_preimages
+                .insert(hash.to_byte_array().to_vec(), preimage.clone());
+        }
+        for (hash, preimage) in &input.hash160_preimages {
+            satisfier
+                .hash160_preimages
+                .insert(hash.to_byte_array().to_vec(), preimage.clone());
+        }
+        satisfier
+    }
+
+    /// Add a signature for a key, checking the key length for the context.
+    ///
+    /// Keys are 33-byte compressed public keys in [`Context::Wsh`] and
+    /// 32-byte x-only public keys in [`Context::Tapscript`], matching the
+    /// keys passed to [`Satisfier::sign()`] when satisfying.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidKeyLength`] if the key has the wrong length
+    /// for `ctx`. The satisfier is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, SimpleSatisfier};
+    ///
+    /// let mut satisfier = SimpleSatisfier::new();
+    /// let xonly = [0x02; 32];
+    /// assert!(satisfier.insert_signature(&xonly, vec![0; 64], Context::Tapscript).is_ok());
+    /// assert!(satisfier.insert_signature(&xonly, vec![0; 72], Context::Wsh).is_err());
+    /// ```
+    pub fn insert_signature(
+        &mut self,
+        key: &[u8],
+        sig: Vec<u8>,
+        ctx: Context,
+    ) -> Result<(), Error> {
+        let expected = match ctx {
+            Context::Wsh => 33,
+            Context::Tapscript => 32,
+        };
+        if key.len() != expected {
+            return Err(Error::InvalidKeyLength {
+                context: ctx,
+                len: key.len(),
+                expected,
+            });
+        }
+        self.signatures.insert(key.to_vec(), sig);
+        Ok(())
+    }
+
+    /// Add all signatures, preimages and timelocks of another satisfier.
+    ///
+    /// Entries present in both with the same value are kept once. If the
+    /// two hold different signatures for the same key, or different
+    /// preimages for the same hash, nothing is merged and an error is
+    /// returned, as only one of them can be right.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::SatisfierConflict`] naming the first conflicting key
+    /// or hash. The satisfier is left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, DummyKeyMap, SimpleSatisfier};
+    ///
+    /// let keys = DummyKeyMap::new();
+    /// let mut alice = SimpleSatisfier::new();
+    /// alice.signatures.insert(keys.pubkey_for("A", Context::Wsh), vec![0x30; 72]);
+    /// let mut bob = SimpleSatisfier::new();
+    /// bob.signatures.insert(keys.pubkey_for("B", Context::Wsh), vec![0x30; 72]);
+    ///
+    /// alice.merge(bob).unwrap();
+    /// assert_eq!(alice.signatures.len(), 2);
+    /// ```
+    pub fn merge(&mut self, mut other: Self) -> Result<(), Error> {
+        let pairs = [
+            (&mut self.signatures, &mut other.signatures),
+            (&mut self.sha256_preimages, &mut other.sha256_preimages),
+            (
+                &mut self.ripemd160_preimages,
+                &mut other.ripemd160_preimages,
+            ),
+            (&mut self.hash256_preimages, &mut other.hash256_preimages),
+            (&mut self.hash160_preimages, &mut other.hash160_preimages),
+        ];
+
+        for (ours, theirs) in &pairs {
+            if let Some(key) = theirs
+                .iter()
+                .find(|(key, value)| ours.get(*key).is_some_and(|v| v != *value))
+                .map(|(key, _)| key)
+            {
+                return Err(Error::SatisfierConflict { key: key.clone() });
+            }
+        }
+
+        for (ours, theirs) in pairs {
+            // Taken rather than moved, as the satisfier may wipe itself on drop
+            ours.extend(std::mem::take(theirs));
+        }
+        self.after_satisfied.extend(other.after_satisfied.drain());
+        self.older_satisfied.extend(other.older_satisfied.drain());
+        Ok(())
+    }
 }
 
 impl Default for SimpleSatisfier {
@@ -568,6 +1480,37 @@ impl Default for SimpleSatisfier {
     }
 }
 
+/// Wipes the stored preimages and signatures, leaving the satisfier empty.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SimpleSatisfier {
+    fn zeroize(&mut self) {
+        for map in [
+            &mut self.signatures,
+            &mut self.sha256_preimages,
+            &mut self.ripemd160_preimages,
+            &mut self.hash256_preimages,
+            &mut self.hash160_preimages,
+        ] {
+            map.values_mut().for_each(zeroize::Zeroize::zeroize);
+            map.clear();
+        }
+        self.after_satisfied.clear();
+        self.older_satisfied.clear();
+    }
+}
+
+// Implemented with or without the `zeroize` feature, so that enabling it
+// does not change which moves out of a satisfier compile
+impl Drop for SimpleSatisfier {
+    fn drop(&mut self) {
+        #[cfg(feature = "zeroize")]
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for SimpleSatisfier {}
+
 impl Satisfier for SimpleSatisfier {
     fn sign(&self, key: &[u8]) -> (Availability, Option<Vec<u8>>) {
         self.signatures
@@ -618,20 +1561,71 @@ impl Satisfier for SimpleSatisfier {
     }
 }
 
-/// Result of a satisfaction attempt.
-///
-/// Contains the availability status and the witness stack that can be used
-/// to satisfy the miniscript in a transaction.
-///
-/// # Example
-///
-/// ```rust,no_run
-/// use miniscript_core_ffi::{Miniscript, Context, SimpleSatisfier, Availability};
+/// Satisfier that may have every signature, preimage and timelock.
 ///
-/// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
-/// let satisfier = SimpleSatisfier::new();
-///
-/// let result = ms.satisfy(satisfier, true).unwrap();
+/// Returns placeholder data marked [`Availability::Maybe`], so a satisfaction
+/// only tells which spends are possible in principle. Signatures have the
+/// largest size of the context, so witness sizes are upper bounds.
+struct MaybeSatisfier {
+    sig_len: usize,
+}
+
+impl MaybeSatisfier {
+    const fn new(context: Context) -> Self {
+        Self {
+            sig_len: context.sig_len(true),
+        }
+    }
+
+    fn placeholder(len: usize) -> (Availability, Option<Vec<u8>>) {
+        (Availability::Maybe, Some(vec![0; len]))
+    }
+}
+
+impl Satisfier for MaybeSatisfier {
+    fn sign(&self, _key: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        Self::placeholder(self.sig_len)
+    }
+
+    fn check_after(&self, _value: u32) -> bool {
+        true
+    }
+
+    fn check_older(&self, _value: u32) -> bool {
+        true
+    }
+
+    fn sat_sha256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        Self::placeholder(PREIMAGE_LEN)
+    }
+
+    fn sat_ripemd160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        Self::placeholder(PREIMAGE_LEN)
+    }
+
+    fn sat_hash256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        Self::placeholder(PREIMAGE_LEN)
+    }
+
+    fn sat_hash160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        Self::placeholder(PREIMAGE_LEN)
+    }
+}
+
+/// Result of a satisfaction attempt.
+///
+/// Contains the availability status and the witness stack that can be used
+/// to satisfy the miniscript in a transaction.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use miniscript_core_ffi::{Miniscript, Context, SimpleSatisfier, Availability};
+///
+/// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+/// let satisfier = SimpleSatisfier::new();
+///
+/// let result = ms.satisfy(satisfier, true).unwrap();
 /// match result.availability {
 ///     Availability::Yes => {
 ///         let witness = result.to_witness();
@@ -652,9 +1646,131 @@ pub struct SatisfyResult {
     /// Each element is a byte vector representing one witness stack item.
     /// Use [`to_witness()`](Self::to_witness) to convert to a [`bitcoin::Witness`].
     pub stack: Vec<Vec<u8>>,
+    /// Problems with the data returned by the [`Satisfier`].
+    pub warnings: Vec<SatisfyWarning>,
+}
+
+/// A problem with the data a [`Satisfier`] returned during satisfaction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SatisfyWarning {
+    /// A hash preimage was not 32 bytes long, so it was treated as unavailable.
+    InvalidPreimageLength {
+        /// The hash the preimage was requested for.
+        hash: Vec<u8>,
+        /// The length of the returned preimage.
+        len: usize,
+    },
+    /// A signature was reported as available without any signature bytes,
+    /// so it was treated as unavailable.
+    MissingSignature {
+        /// The key the signature was requested for.
+        key: Vec<u8>,
+    },
+}
+
+impl fmt::Display for SatisfyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use bitcoin::hex::DisplayHex;
+
+        match self {
+            Self::InvalidPreimageLength { hash, len } => write!(
+                f,
+                "preimage for hash {} is {len} bytes, expected {PREIMAGE_LEN}",
+                hash.as_hex()
+            ),
+            Self::MissingSignature { key } => {
+                write!(f, "no signature bytes returned for key {}", key.as_hex())
+            }
+        }
+    }
+}
+
+/// How a miniscript can be satisfied with the data of a satisfier.
+///
+/// Returned by [`Miniscript::malleability_for()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Malleability {
+    /// A non-malleable witness can be produced.
+    NonMalleable,
+    /// A witness can be produced, but only a malleable one: a third party
+    /// could change it without invalidating the spend.
+    Malleable,
+    /// No witness can be produced.
+    Unsatisfiable,
+}
+
+impl fmt::Display for Malleability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonMalleable => write!(f, "non-malleable"),
+            Self::Malleable => write!(f, "malleable"),
+            Self::Unsatisfiable => write!(f, "unsatisfiable"),
+        }
+    }
+}
+
+/// The keys that must all sign to use one spend path.
+///
+/// Returned by [`Miniscript::required_signers()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignerSet {
+    /// The keys as written in the miniscript, sorted.
+    pub keys: Vec<Vec<u8>>,
+}
+
+/// The signer sets of a miniscript's spend paths.
+///
+/// Returned by [`Miniscript::required_signers()`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequiredSigners {
+    /// One set per distinct group of signers, in spend path order.
+    pub sets: Vec<SignerSet>,
+    /// Whether the miniscript has more than [`MAX_SPEND_PATHS`] spend
+    /// paths, so only the first ones were examined.
+    pub truncated: bool,
+}
+
+/// The timelocks that must be met to use one spend path.
+///
+/// Returned by [`Miniscript::branch_timelocks()`]. Each lock is the strictest
+/// of its kind on the path, i.e. the earliest value that makes it spendable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchTimelock {
+    /// The absolute lock (`nLockTime`) required, if any.
+    pub after: Option<LockTime>,
+    /// The relative lock (`nSequence`) required, if any.
+    pub older: Option<RelativeLockTime>,
+    /// Whether the path mixes heights and times in one kind of lock, so it
+    /// can never be spent. The mixed lock is then reported as `None`.
+    pub conflicting: bool,
+}
+
+/// Wipes the witness stack, which may contain hash preimages.
+///
+/// `SatisfyResult` is not wiped on drop so that its fields can still be moved
+/// out; call this once the witness has been used.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for SatisfyResult {
+    fn zeroize(&mut self) {
+        self.stack.iter_mut().for_each(zeroize::Zeroize::zeroize);
+        self.stack.clear();
+    }
 }
 
 impl SatisfyResult {
+    /// Get the witness stack with each element hex-encoded.
+    ///
+    /// Empty elements are returned as empty strings.
+    #[must_use]
+    pub fn to_hex_stack(&self) -> Vec<String> {
+        use bitcoin::hex::DisplayHex;
+
+        self.stack
+            .iter()
+            .map(|elem| elem.to_lower_hex_string())
+            .collect()
+    }
+
     /// Convert the witness stack to a [`bitcoin::Witness`].
     ///
     /// This is useful for constructing transactions with the satisfaction.
@@ -662,6 +1778,72 @@ impl SatisfyResult {
     pub fn to_witness(&self) -> Witness {
         Witness::from_slice(&self.stack)
     }
+
+    /// Get the weight of the witness stack in weight units.
+    ///
+    /// This is the serialized size of the stack, including the element count
+    /// and length prefixes, as witness data weighs one unit per byte. The
+    /// witness script of a P2WSH spend is not included.
+    #[must_use]
+    pub fn witness_weight(&self) -> usize {
+        self.to_witness().size()
+    }
+
+    /// Convert the witness stack to a spendable P2WSH [`bitcoin::Witness`].
+    ///
+    /// The witness script of `ms` is appended as the final witness element,
+    /// as required when spending a P2WSH output.
+    ///
+    /// # Arguments
+    ///
+    /// * `ms` - The miniscript this result satisfies
+    ///
+    /// # Returns
+    ///
+    /// The witness, or `None` if `ms` is not in the WSH context (Tapscript
+    /// spends additionally need a control block) or its script cannot be built.
+    #[must_use]
+    pub fn to_wsh_witness(&self, ms: &Miniscript) -> Option<Witness> {
+        if ms.context() != Context::Wsh {
+            return None;
+        }
+        let script = ms.to_script_bytes()?;
+        let mut witness = self.to_witness();
+        witness.push(script);
+        Some(witness)
+    }
+
+    /// Set the witness of a transaction input from this satisfaction.
+    ///
+    /// Any existing witness is replaced. When spending a P2WSH output, pass
+    /// the witness script so it is appended as the final element.
+    ///
+    /// # Arguments
+    ///
+    /// * `txin` - The input to update
+    /// * `witness_script` - The P2WSH witness script, if any
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use miniscript_core_ffi::{Context, Miniscript, SimpleSatisfier};
+    /// # let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// # let result = ms.satisfy(SimpleSatisfier::new(), true).unwrap();
+    /// let mut txin = bitcoin::TxIn::default();
+    /// let script = ms.to_script().unwrap();
+    /// result.apply_to_txin(&mut txin, Some(&script));
+    /// ```
+    pub fn apply_to_txin(
+        &self,
+        txin: &mut bitcoin::TxIn,
+        witness_script: Option<&bitcoin::Script>,
+    ) {
+        let mut witness = self.to_witness();
+        if let Some(script) = witness_script {
+            witness.push(script.as_bytes());
+        }
+        txin.witness = witness;
+    }
 }
 
 impl std::fmt::Debug for SatisfyResult {
@@ -669,12 +1851,117 @@ impl std::fmt::Debug for SatisfyResult {
         f.debug_struct("SatisfyResult")
             .field("availability", &self.availability)
             .field("stack_len", &self.stack.len())
+            .field(
+                "element_lens",
+                &self.stack.iter().map(Vec::len).collect::<Vec<_>>(),
+            )
+            .field("warnings", &self.warnings)
             .finish()
     }
 }
 
 // FFI callback trampolines
 
+/// State shared with the FFI callbacks while satisfying.
+struct SatisfierContext<'a> {
+    satisfier: &'a dyn Satisfier,
+    warnings: RefCell<Vec<SatisfyWarning>>,
+    panicked: Cell<bool>,
+}
+
+impl<'a> SatisfierContext<'a> {
+    fn new(satisfier: &'a dyn Satisfier) -> Self {
+        Self {
+            satisfier,
+            warnings: RefCell::new(Vec::new()),
+            panicked: Cell::new(false),
+        }
+    }
+
+    /// Build the callback table handed to `miniscript_satisfy`.
+    ///
+    /// The table points at `self`, which must outlive every use of it.
+    fn callbacks(&self) -> SatisfierCallbacks {
+        SatisfierCallbacks {
+            rust_context: ptr::from_ref(self).cast_mut().cast::<std::ffi::c_void>(),
+            sign_callback: Some(sign_callback),
+            check_after_callback: Some(check_after_callback),
+            check_older_callback: Some(check_older_callback),
+            sat_sha256_callback: Some(sat_sha256_callback),
+            sat_ripemd160_callback: Some(sat_ripemd160_callback),
+            sat_hash256_callback: Some(sat_hash256_callback),
+            sat_hash160_callback: Some(sat_hash160_callback),
+        }
+    }
+
+    /// Run a `Satisfier` method, catching any panic so it cannot unwind
+    /// across the FFI boundary.
+    ///
+    /// On panic, `fallback` is returned and the panic is recorded so that
+    /// `satisfy()` can report it.
+    fn guard<T>(&self, fallback: T, f: impl FnOnce(&dyn Satisfier) -> T) -> T {
+        if self.panicked.get() {
+            return fallback;
+        }
+        panic::catch_unwind(AssertUnwindSafe(|| f(self.satisfier))).unwrap_or_else(|_| {
+            self.panicked.set(true);
+            fallback
+        })
+    }
+}
+
+/// Required length of a hash preimage (`SIZE 32 EQUALVERIFY`).
+const PREIMAGE_LEN: usize = 32;
+
+/// Hand a preimage returned by the `Satisfier` back to the C++ caller.
+///
+/// A preimage that is not exactly 32 bytes can never satisfy the script, so
+/// it is reported as unavailable and recorded as a warning.
+///
+/// # Safety
+///
+/// `preimage_out` and `preimage_len_out` must be valid, non-null pointers.
+unsafe fn export_preimage(
+    ctx: &SatisfierContext,
+    hash: &[u8],
+    avail: Availability,
+    preimage: Option<Vec<u8>>,
+    preimage_out: *mut *mut u8,
+    preimage_len_out: *mut usize,
+) -> MiniscriptAvailability {
+    let Some(preimage_data) = preimage else {
+        return avail.into();
+    };
+    // Wipe our copy of the preimage once it has been handed to C++
+    #[cfg(feature = "zeroize")]
+    let preimage_data = zeroize::Zeroizing::new(preimage_data);
+
+    if preimage_data.len() != PREIMAGE_LEN {
+        if avail != Availability::No {
+            ctx.warnings
+                .borrow_mut()
+                .push(SatisfyWarning::InvalidPreimageLength {
+                    hash: hash.to_vec(),
+                    len: preimage_data.len(),
+                });
+        }
+        return MiniscriptAvailability::MINISCRIPT_AVAILABILITY_NO;
+    }
+
+    let len = preimage_data.len();
+    // SAFETY: the output pointers are valid as guaranteed by the caller
+    unsafe {
+        let ptr = libc::malloc(len).cast::<u8>();
+        if !ptr.is_null() {
+            std::ptr::copy_nonoverlapping(preimage_data.as_ptr(), ptr, len);
+            *preimage_out = ptr;
+            *preimage_len_out = len;
+        }
+    }
+
+    avail.into()
+}
+
 /// FFI callback function for signing operations.
 ///
 /// This function is called by the C++ miniscript implementation when it needs
@@ -686,7 +1973,7 @@ impl std::fmt::Debug for SatisfyResult {
 /// This function is marked as safe but contains an unsafe block because:
 /// - It is only called from C++ code via the FFI boundary
 /// - The caller (C++ code) guarantees that:
-///   - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+///   - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 ///   - `key_bytes` is a valid pointer to `key_len` bytes
 ///   - `sig_out` and `sig_len_out` are valid, non-null pointers
 /// - Memory allocated with `libc::malloc` is freed by the C++ caller
@@ -694,11 +1981,11 @@ impl std::fmt::Debug for SatisfyResult {
 /// # Invariants
 ///
 /// - The `context` pointer must remain valid for the duration of the callback
-/// - The callback must not panic (panics across FFI boundaries are UB)
+/// - Panics in the `Satisfier` are caught and never unwind into C++
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `key_bytes` - Pointer to the key bytes to sign with
 /// * `key_len` - Length of the key bytes
 /// * `sig_out` - Output pointer for the signature bytes (allocated with malloc)
@@ -716,24 +2003,33 @@ extern "C" fn sign_callback(
 ) -> MiniscriptAvailability {
     // SAFETY: This callback is only invoked by the C++ miniscript library during
     // the `satisfy` call. The invariants are:
-    // 1. `context` was created by `Box::into_raw(Box::new(boxed_satisfier))` in `satisfy()`
+    // 1. `context` points to the `SatisfierContext` borrowed by `satisfy_with()`
     // 2. `key_bytes` points to valid memory of `key_len` bytes (from C++ std::vector)
     // 3. `sig_out` and `sig_len_out` are valid output pointers (stack-allocated in C++)
     // 4. The satisfier outlives this callback (it's freed after `miniscript_satisfy` returns)
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
+        let ctx = &*(context as *const SatisfierContext);
         let key = std::slice::from_raw_parts(key_bytes, key_len);
 
-        let (avail, sig) = satisfier.sign(key);
+        let (avail, sig) = ctx.guard((Availability::No, None), |s| s.sign(key));
 
-        if let Some(sig_data) = sig {
-            let len = sig_data.len();
-            let ptr = libc::malloc(len).cast::<u8>();
-            if !ptr.is_null() {
-                std::ptr::copy_nonoverlapping(sig_data.as_ptr(), ptr, len);
-                *sig_out = ptr;
-                *sig_len_out = len;
+        // A signature claimed as available must come with its bytes, or the
+        // witness would contain an empty (dissatisfying) element.
+        let Some(sig_data) = sig.filter(|sig| !sig.is_empty()) else {
+            if avail != Availability::No {
+                ctx.warnings
+                    .borrow_mut()
+                    .push(SatisfyWarning::MissingSignature { key: key.to_vec() });
             }
+            return MiniscriptAvailability::MINISCRIPT_AVAILABILITY_NO;
+        };
+
+        let len = sig_data.len();
+        let ptr = libc::malloc(len).cast::<u8>();
+        if !ptr.is_null() {
+            std::ptr::copy_nonoverlapping(sig_data.as_ptr(), ptr, len);
+            *sig_out = ptr;
+            *sig_len_out = len;
         }
 
         avail.into()
@@ -749,23 +2045,23 @@ extern "C" fn sign_callback(
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - The satisfier remains valid for the duration of the callback
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `value` - The timelock value to check (block height or Unix timestamp)
 ///
 /// # Returns
 ///
 /// Returns `true` if the timelock is satisfied, `false` otherwise.
 extern "C" fn check_after_callback(context: *mut std::ffi::c_void, value: u32) -> bool {
-    // SAFETY: `context` was created by `Box::into_raw` in `satisfy()` and remains
-    // valid until after `miniscript_satisfy` returns.
+    // SAFETY: `context` points to the `SatisfierContext` borrowed by `satisfy_with()` and
+    // remains valid until after `miniscript_satisfy` returns.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
-        satisfier.check_after(value)
+        let ctx = &*(context as *const SatisfierContext);
+        ctx.guard(false, |s| s.check_after(value))
     }
 }
 
@@ -778,23 +2074,23 @@ extern "C" fn check_after_callback(context: *mut std::ffi::c_void, value: u32) -
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - The satisfier remains valid for the duration of the callback
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `value` - The relative timelock value to check (block count or time units)
 ///
 /// # Returns
 ///
 /// Returns `true` if the relative timelock is satisfied, `false` otherwise.
 extern "C" fn check_older_callback(context: *mut std::ffi::c_void, value: u32) -> bool {
-    // SAFETY: `context` was created by `Box::into_raw` in `satisfy()` and remains
-    // valid until after `miniscript_satisfy` returns.
+    // SAFETY: `context` points to the `SatisfierContext` borrowed by `satisfy_with()` and
+    // remains valid until after `miniscript_satisfy` returns.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
-        satisfier.check_older(value)
+        let ctx = &*(context as *const SatisfierContext);
+        ctx.guard(false, |s| s.check_older(value))
     }
 }
 
@@ -807,14 +2103,14 @@ extern "C" fn check_older_callback(context: *mut std::ffi::c_void, value: u32) -
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - `hash` is a valid pointer to `hash_len` bytes
 /// - `preimage_out` and `preimage_len_out` are valid, non-null pointers
 /// - Memory allocated with `libc::malloc` is freed by the C++ caller
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `hash` - Pointer to the SHA256 hash bytes (32 bytes)
 /// * `hash_len` - Length of the hash bytes (should be 32)
 /// * `preimage_out` - Output pointer for the preimage bytes (allocated with malloc)
@@ -833,22 +2129,19 @@ extern "C" fn sat_sha256_callback(
     // SAFETY: See function-level safety documentation. All pointers are valid
     // for the duration of the callback as guaranteed by the C++ caller.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
+        let ctx = &*(context as *const SatisfierContext);
         let hash_slice = std::slice::from_raw_parts(hash, hash_len);
 
-        let (avail, preimage) = satisfier.sat_sha256(hash_slice);
+        let (avail, preimage) = ctx.guard((Availability::No, None), |s| s.sat_sha256(hash_slice));
 
-        if let Some(preimage_data) = preimage {
-            let len = preimage_data.len();
-            let ptr = libc::malloc(len).cast::<u8>();
-            if !ptr.is_null() {
-                std::ptr::copy_nonoverlapping(preimage_data.as_ptr(), ptr, len);
-                *preimage_out = ptr;
-                *preimage_len_out = len;
-            }
-        }
-
-        avail.into()
+        export_preimage(
+            ctx,
+            hash_slice,
+            avail,
+            preimage,
+            preimage_out,
+            preimage_len_out,
+        )
     }
 }
 
@@ -861,14 +2154,14 @@ extern "C" fn sat_sha256_callback(
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - `hash` is a valid pointer to `hash_len` bytes
 /// - `preimage_out` and `preimage_len_out` are valid, non-null pointers
 /// - Memory allocated with `libc::malloc` is freed by the C++ caller
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `hash` - Pointer to the RIPEMD160 hash bytes (20 bytes)
 /// * `hash_len` - Length of the hash bytes (should be 20)
 /// * `preimage_out` - Output pointer for the preimage bytes (allocated with malloc)
@@ -887,22 +2180,20 @@ extern "C" fn sat_ripemd160_callback(
     // SAFETY: See function-level safety documentation. All pointers are valid
     // for the duration of the callback as guaranteed by the C++ caller.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
+        let ctx = &*(context as *const SatisfierContext);
         let hash_slice = std::slice::from_raw_parts(hash, hash_len);
 
-        let (avail, preimage) = satisfier.sat_ripemd160(hash_slice);
+        let (avail, preimage) =
+            ctx.guard((Availability::No, None), |s| s.sat_ripemd160(hash_slice));
 
-        if let Some(preimage_data) = preimage {
-            let len = preimage_data.len();
-            let ptr = libc::malloc(len).cast::<u8>();
-            if !ptr.is_null() {
-                std::ptr::copy_nonoverlapping(preimage_data.as_ptr(), ptr, len);
-                *preimage_out = ptr;
-                *preimage_len_out = len;
-            }
-        }
-
-        avail.into()
+        export_preimage(
+            ctx,
+            hash_slice,
+            avail,
+            preimage,
+            preimage_out,
+            preimage_len_out,
+        )
     }
 }
 
@@ -916,14 +2207,14 @@ extern "C" fn sat_ripemd160_callback(
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - `hash` is a valid pointer to `hash_len` bytes
 /// - `preimage_out` and `preimage_len_out` are valid, non-null pointers
 /// - Memory allocated with `libc::malloc` is freed by the C++ caller
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `hash` - Pointer to the HASH256 hash bytes (32 bytes)
 /// * `hash_len` - Length of the hash bytes (should be 32)
 /// * `preimage_out` - Output pointer for the preimage bytes (allocated with malloc)
@@ -942,22 +2233,19 @@ extern "C" fn sat_hash256_callback(
     // SAFETY: See function-level safety documentation. All pointers are valid
     // for the duration of the callback as guaranteed by the C++ caller.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
+        let ctx = &*(context as *const SatisfierContext);
         let hash_slice = std::slice::from_raw_parts(hash, hash_len);
 
-        let (avail, preimage) = satisfier.sat_hash256(hash_slice);
+        let (avail, preimage) = ctx.guard((Availability::No, None), |s| s.sat_hash256(hash_slice));
 
-        if let Some(preimage_data) = preimage {
-            let len = preimage_data.len();
-            let ptr = libc::malloc(len).cast::<u8>();
-            if !ptr.is_null() {
-                std::ptr::copy_nonoverlapping(preimage_data.as_ptr(), ptr, len);
-                *preimage_out = ptr;
-                *preimage_len_out = len;
-            }
-        }
-
-        avail.into()
+        export_preimage(
+            ctx,
+            hash_slice,
+            avail,
+            preimage,
+            preimage_out,
+            preimage_len_out,
+        )
     }
 }
 
@@ -971,14 +2259,14 @@ extern "C" fn sat_hash256_callback(
 /// # Safety
 ///
 /// This function contains an unsafe block. The caller (C++ code) guarantees:
-/// - `context` is a valid pointer created by `Box::into_raw(Box::new(Box<dyn Satisfier>))`
+/// - `context` is a valid pointer to the `SatisfierContext` passed to `satisfy_with()`
 /// - `hash` is a valid pointer to `hash_len` bytes
 /// - `preimage_out` and `preimage_len_out` are valid, non-null pointers
 /// - Memory allocated with `libc::malloc` is freed by the C++ caller
 ///
 /// # Parameters
 ///
-/// * `context` - Raw pointer to a boxed `Satisfier` trait object
+/// * `context` - Raw pointer to the `SatisfierContext` wrapping the `Satisfier`
 /// * `hash` - Pointer to the HASH160 hash bytes (20 bytes)
 /// * `hash_len` - Length of the hash bytes (should be 20)
 /// * `preimage_out` - Output pointer for the preimage bytes (allocated with malloc)
@@ -997,23 +2285,40 @@ extern "C" fn sat_hash160_callback(
     // SAFETY: See function-level safety documentation. All pointers are valid
     // for the duration of the callback as guaranteed by the C++ caller.
     unsafe {
-        let satisfier = &*(context as *const Box<dyn Satisfier>);
+        let ctx = &*(context as *const SatisfierContext);
         let hash_slice = std::slice::from_raw_parts(hash, hash_len);
 
-        let (avail, preimage) = satisfier.sat_hash160(hash_slice);
+        let (avail, preimage) = ctx.guard((Availability::No, None), |s| s.sat_hash160(hash_slice));
 
-        if let Some(preimage_data) = preimage {
-            let len = preimage_data.len();
-            let ptr = libc::malloc(len).cast::<u8>();
-            if !ptr.is_null() {
-                std::ptr::copy_nonoverlapping(preimage_data.as_ptr(), ptr, len);
-                *preimage_out = ptr;
-                *preimage_len_out = len;
-            }
-        }
+        export_preimage(
+            ctx,
+            hash_slice,
+            avail,
+            preimage,
+            preimage_out,
+            preimage_len_out,
+        )
+    }
+}
 
-        avail.into()
+/// Copy a string returned by the C wrapper and free it.
+///
+/// # Safety
+///
+/// `c_str` must be null or a string allocated by the C wrapper that is not
+/// used afterwards.
+unsafe fn take_c_string(c_str: *mut std::os::raw::c_char) -> Option<String> {
+    if c_str.is_null() {
+        return None;
     }
+
+    // SAFETY: c_str is a valid C string
+    let result = unsafe { CStr::from_ptr(c_str) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { miniscript_free_string(c_str) };
+
+    Some(result)
 }
 
 /// A parsed miniscript node.
@@ -1064,6 +2369,10 @@ unsafe impl Sync for Miniscript {}
 impl Miniscript {
     /// Parse a miniscript from a string.
     ///
+    /// Inputs longer than [`MAX_INPUT_LEN`] (1 MiB) are rejected without
+    /// being parsed; use [`from_str_limited()`](Self::from_str_limited) to
+    /// choose a different cap.
+    ///
     /// # Arguments
     ///
     /// * `input` - The miniscript string (e.g., "`and_v(v:pk(A),pk(B))`")
@@ -1071,18 +2380,149 @@ impl Miniscript {
     ///
     /// # Errors
     ///
-    /// Returns an error if parsing fails.
+    /// Returns [`Error::InputTooLong`] if the input exceeds [`MAX_INPUT_LEN`],
+    /// [`Error::NonAsciiInput`] if it contains a non-ASCII or null byte, or
+    /// an error if parsing fails.
     pub fn from_str(input: &str, context: Context) -> Result<Self, Error> {
-        let c_input = CString::new(input).map_err(|_| Error {
-            message: "input contains null byte".to_string(),
-        })?;
+        Self::from_str_limited(input, context, MAX_INPUT_LEN)
+    }
+
+    /// Parse a miniscript from a string of at most `max_len` bytes.
+    ///
+    /// The length is checked before anything is copied or handed to
+    /// Bitcoin Core, so oversized input is rejected cheaply.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The miniscript string
+    /// * `context` - The script context (WSH or Tapscript)
+    /// * `max_len` - The maximum accepted input length in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InputTooLong`] if the input exceeds `max_len`,
+    /// [`Error::NonAsciiInput`] if it contains a non-ASCII or null byte, or
+    /// an error if parsing fails.
+    pub fn from_str_limited(input: &str, context: Context, max_len: usize) -> Result<Self, Error> {
+        Self::parse_string(input, context, max_len, false)
+    }
+
+    /// Parse a miniscript from a string, rejecting it if it is nested deeper
+    /// than `max_depth`.
+    ///
+    /// Depth is measured as by [`depth()`](Self::depth). Inputs whose text
+    /// alone shows they are too deep (counting parentheses and wrappers)
+    /// are rejected before being handed to Bitcoin Core; the exact depth is
+    /// checked after parsing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TooDeep`] if the miniscript is too deep, or the
+    /// errors of [`from_str()`](Self::from_str).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Error, Miniscript};
+    ///
+    /// let err = Miniscript::from_str_bounded("or_i(pk(A),or_i(pk(B),pk(C)))", Context::Wsh, 3)
+    ///     .unwrap_err();
+    /// assert!(matches!(err, Error::TooDeep { max: 3, .. }));
+    /// ```
+    pub fn from_str_bounded(
+        input: &str,
+        context: Context,
+        max_depth: usize,
+    ) -> Result<Self, Error> {
+        let lower_bound = text_depth(input);
+        if lower_bound > max_depth {
+            return Err(Error::TooDeep {
+                depth: lower_bound,
+                max: max_depth,
+            });
+        }
+
+        let ms = Self::from_str(input, context)?;
+        let depth = ms.depth();
+        if depth > max_depth {
+            return Err(Error::TooDeep {
+                depth,
+                max: max_depth,
+            });
+        }
+        Ok(ms)
+    }
+
+    /// Parse a miniscript from a string, keeping it even if it fails type
+    /// checking.
+    ///
+    /// This is meant for tooling such as editors that want to show the
+    /// structure of a malformed miniscript. The returned node may have
+    /// [`is_valid()`](Self::is_valid) false; its string form,
+    /// [`subexpressions()`](Self::subexpressions) and type properties can be
+    /// inspected, but it **must not** be used to build scripts, addresses or
+    /// witnesses, as nothing about them is guaranteed to be spendable.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_str()`](Self::from_str), except
+    /// for type checking failures.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str_permissive("t:or_c(0,1)", Context::Wsh).unwrap();
+    /// assert!(!ms.is_valid());
+    /// ```
+    pub fn from_str_permissive(input: &str, context: Context) -> Result<Self, Error> {
+        Self::parse_string(input, context, MAX_INPUT_LEN, true)
+    }
+
+    /// Shared implementation of [`from_str_limited()`](Self::from_str_limited)
+    /// and [`from_str_permissive()`](Self::from_str_permissive).
+    fn parse_string(
+        input: &str,
+        context: Context,
+        max_len: usize,
+        permissive: bool,
+    ) -> Result<Self, Error> {
+        if input.len() > max_len {
+            return Err(Error::InputTooLong {
+                len: input.len(),
+                max: max_len,
+            });
+        }
+
+        // Miniscript is pure ASCII; anything else would only produce a
+        // confusing parse error from Bitcoin Core
+        if let Some(position) = invalid_input_position(input) {
+            return Err(Error::NonAsciiInput { position });
+        }
+        let c_input = CString::new(input).map_err(|_| Error::new("input contains null byte"))?;
 
         let mut node_ptr: *mut MiniscriptNode = ptr::null_mut();
 
+        let span = ffi_span!(
+            "Miniscript::from_str",
+            input_len = input.len(),
+            %context,
+            permissive
+        );
         // SAFETY: We're passing valid pointers and the C code handles null checks.
         let result = unsafe {
-            ffi::miniscript_from_string(c_input.as_ptr(), context.into(), &raw mut node_ptr)
+            if permissive {
+                ffi::miniscript_from_string_permissive(
+                    c_input.as_ptr(),
+                    context.into(),
+                    &raw mut node_ptr,
+                )
+            } else {
+                ffi::miniscript_from_string(c_input.as_ptr(), context.into(), &raw mut node_ptr)
+            }
         };
+        span.record(result.success);
 
         if result.success {
             Ok(Self {
@@ -1100,7 +2540,7 @@ impl Miniscript {
                 unsafe { miniscript_free_string(result.error_message) };
                 msg
             };
-            Err(Error { message })
+            Err(Error::from_ffi(message, result.error_code))
         }
     }
 
@@ -1109,50 +2549,338 @@ impl Miniscript {
     pub fn to_string(&self) -> Option<String> {
         // SAFETY: self.ptr is valid while self exists
         let c_str = unsafe { miniscript_to_string(self.ptr) };
-        if c_str.is_null() {
-            return None;
-        }
-
-        // SAFETY: c_str is a valid C string
-        let result = unsafe { CStr::from_ptr(c_str) }
-            .to_string_lossy()
-            .into_owned();
-        unsafe { miniscript_free_string(c_str) };
-
-        Some(result)
+        // SAFETY: c_str was returned by miniscript_to_string
+        unsafe { take_c_string(c_str) }
     }
 
-    /// Check if the miniscript is valid (type-checks correctly).
+    /// Get Bitcoin Core's canonical string, with syntactic sugar applied.
+    ///
+    /// This is the same as [`to_string()`](Self::to_string): `c:pk_k(A)`
+    /// is written `pk(A)`, `and_v(X,1)` is written `t:X`, and so on. See
+    /// [`expanded_string()`](Self::expanded_string) for the unsugared form.
     #[must_use]
-    pub fn is_valid(&self) -> bool {
-        // SAFETY: self.ptr is valid while self exists
-        unsafe { miniscript_is_valid(self.ptr) }
+    pub fn normalized_string(&self) -> Option<String> {
+        self.to_string()
     }
 
-    /// Check if the miniscript is sane.
+    /// Get the string with syntactic sugar expanded into the underlying
+    /// fragments.
     ///
-    /// This includes checks for:
-    /// - No duplicate keys
-    /// - No timelock mixing
-    /// - Within resource limits
+    /// `pk(A)` and `pkh(A)` are written `c:pk_k(A)` and `c:pk_h(A)`, `t:X`,
+    /// `l:X` and `u:X` are written `and_v(X,1)`, `or_i(0,X)` and
+    /// `or_i(X,0)`, and `and_n(X,Y)` is written `andor(X,Y,0)`. Consecutive
+    /// wrappers are still combined, as in `vc:pk_k(A)`. The result parses
+    /// back to the same miniscript.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.expanded_string().unwrap(), "and_v(vc:pk_k(A),c:pk_k(B))");
+    /// ```
     #[must_use]
-    pub fn is_sane(&self) -> bool {
+    pub fn expanded_string(&self) -> Option<String> {
         // SAFETY: self.ptr is valid while self exists
-        unsafe { miniscript_is_sane(self.ptr) }
+        let c_str = unsafe { ffi::miniscript_to_string_ex(self.ptr, true) };
+        // SAFETY: c_str was returned by miniscript_to_string_ex
+        unsafe { take_c_string(c_str) }
     }
 
-    /// Get the type properties of the miniscript.
+    /// Parse a miniscript and review it for sanity issues.
     ///
-    /// Returns a string like "Bdems" where each letter indicates a property.
-    #[must_use]
-    pub fn get_type(&self) -> Option<String> {
-        // SAFETY: self.ptr is valid while self exists
-        let c_str = unsafe { miniscript_get_type(self.ptr) };
-        if c_str.is_null() {
-            return None;
-        }
+    /// Unlike [`from_str()`](Self::from_str) followed by [`is_sane()`](Self::is_sane),
+    /// this reports *which* checks failed so they can be shown to users.
+    /// Valid but insane miniscripts still parse successfully.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The miniscript string
+    /// * `context` - The script context (WSH or Tapscript)
+    ///
+    /// # Returns
+    ///
+    /// The parsed miniscript and the list of warnings (empty if it is sane).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, Warning};
+    ///
+    /// let (_ms, warnings) = Miniscript::parse_checked("after(100)", Context::Wsh).unwrap();
+    /// assert!(warnings.contains(&Warning::NoSignatureRequired));
+    /// ```
+    pub fn parse_checked(input: &str, context: Context) -> Result<(Self, Vec<Warning>), Error> {
+        let ms = Self::from_str(input, context)?;
 
-        // SAFETY: c_str is a valid C string
+        let mut warnings = Vec::new();
+        if !ms.is_non_malleable() {
+            warnings.push(Warning::Malleable);
+        }
+        if !ms.needs_signature() {
+            warnings.push(Warning::NoSignatureRequired);
+        }
+        if ms.has_timelock_mix() {
+            warnings.push(Warning::TimelockMix);
+        }
+        if !ms.is_valid_top_level() {
+            warnings.push(Warning::NotTopLevel);
+        }
+        if !ms.check_duplicate_key() {
+            warnings.push(Warning::DuplicateKey);
+        }
+        if !ms.check_ops_limit() || !ms.check_stack_size() {
+            warnings.push(Warning::ResourceLimitExceeded);
+        }
+
+        Ok((ms, warnings))
+    }
+
+    /// Parse a miniscript wrapped in a `wsh(...)` or `tr(...)` descriptor.
+    ///
+    /// The context is picked from the wrapper: `wsh(X)` parses `X` as P2WSH
+    /// and `tr(X)` or `tr(KEY,X)` parses `X` as Tapscript. A trailing
+    /// `#checksum` is ignored. Input without a wrapper is parsed as P2WSH.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The descriptor or miniscript string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wrapper is malformed, if a `tr()` descriptor
+    /// has a script tree rather than a single leaf, or if parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_descriptor_fragment("tr(pk(A))").unwrap();
+    /// assert_eq!(ms.context(), Context::Tapscript);
+    /// ```
+    pub fn from_descriptor_fragment(input: &str) -> Result<Self, Error> {
+        let input = input.split_once('#').map_or(input, |(body, _)| body).trim();
+
+        let unwrap = |prefix: &str| {
+            input
+                .strip_prefix(prefix)
+                .map(|rest| {
+                    rest.strip_suffix(')')
+                        .ok_or_else(|| Error::new(format!("unbalanced {prefix}...) wrapper")))
+                })
+                .transpose()
+        };
+
+        if let Some(inner) = unwrap("wsh(")? {
+            return Self::from_str(inner, Context::Wsh);
+        }
+
+        if let Some(inner) = unwrap("tr(")? {
+            // tr(KEY,SCRIPT): skip the internal key, a top-level comma
+            let mut depth = 0usize;
+            let split = inner.char_indices().find_map(|(i, c)| match c {
+                '(' | '{' | '[' => {
+                    depth += 1;
+                    None
+                }
+                ')' | '}' | ']' => {
+                    depth = depth.saturating_sub(1);
+                    None
+                }
+                ',' if depth == 0 => Some(i),
+                _ => None,
+            });
+            let leaf = split.map_or(inner, |i| &inner[i + 1..]);
+            if leaf.starts_with('{') {
+                return Err(Error::new(
+                    "tr() script trees are not supported, expected a single leaf",
+                ));
+            }
+            return Self::from_str(leaf, Context::Tapscript);
+        }
+
+        Self::from_str(input, Context::Wsh)
+    }
+
+    /// Parse a miniscript prefixed with its context label.
+    ///
+    /// Accepts the output of [`labeled_string()`](Self::labeled_string):
+    /// `wsh:X` parses `X` as P2WSH and `tap:X` parses it as Tapscript.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label is missing or unknown, or if parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_labeled_string("tap:pk(A)").unwrap();
+    /// assert_eq!(ms.context(), Context::Tapscript);
+    /// ```
+    pub fn from_labeled_string(input: &str) -> Result<Self, Error> {
+        let (label, script) = input
+            .split_once(':')
+            .ok_or_else(|| Error::new("missing context label, expected 'wsh:' or 'tap:'"))?;
+        let context = match label {
+            "wsh" => Context::Wsh,
+            "tap" => Context::Tapscript,
+            _ => {
+                return Err(Error::new(format!(
+                    "unknown context label '{label}', expected 'wsh' or 'tap'"
+                )));
+            }
+        };
+        Self::from_str(script, context)
+    }
+
+    /// Get the miniscript string prefixed with its context.
+    ///
+    /// Returns `wsh:X` for P2WSH and `tap:X` for Tapscript, so that the same
+    /// expression in different contexts can be told apart in logs. Parse it
+    /// back with [`from_labeled_string()`](Self::from_labeled_string).
+    #[must_use]
+    pub fn labeled_string(&self) -> String {
+        let label = match self.context {
+            Context::Wsh => "wsh",
+            Context::Tapscript => "tap",
+        };
+        format!("{label}:{}", self.to_string().unwrap_or_default())
+    }
+
+    /// Check whether a miniscript string is already in canonical form.
+    ///
+    /// The input is parsed and compared as is against its
+    /// [`to_string()`](Self::to_string) serialization. For example `pk(A)` is
+    /// canonical, while `c:pk_k(A)` is not because it normalizes to `pk(A)`,
+    /// and neither is input with whitespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The miniscript string to check
+    /// * `context` - The script context (WSH or Tapscript)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if parsing fails or the miniscript cannot be serialized.
+    pub fn is_canonical_input(input: &str, context: Context) -> Result<bool, Error> {
+        let ms = Self::from_str(input, context)?;
+        let canonical = ms
+            .to_string()
+            .ok_or_else(|| Error::new("failed to serialize miniscript"))?;
+        Ok(canonical == input)
+    }
+
+    /// Parse a miniscript string in both contexts and compare their costs.
+    ///
+    /// This is useful when migrating a policy from P2WSH to Tapscript. The
+    /// input only has to be valid in one of the contexts; the other side
+    /// then holds its parse error.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The miniscript string to compare
+    ///
+    /// # Errors
+    ///
+    /// Returns the P2WSH parse error if the input is valid in neither context.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::Miniscript;
+    ///
+    /// let cmp = Miniscript::compare_contexts("and_v(v:pk(A),pk(B))").unwrap();
+    /// let wsh = cmp.wsh.unwrap();
+    /// let tap = cmp.tapscript.unwrap();
+    /// println!("script size: {:?} vs {:?}", wsh.script_size, tap.script_size);
+    /// ```
+    pub fn compare_contexts(input: &str) -> Result<ContextComparison, Error> {
+        let wsh = Self::from_str(input, Context::Wsh).map(|ms| ContextMetrics::of(&ms));
+        let tapscript = Self::from_str(input, Context::Tapscript).map(|ms| ContextMetrics::of(&ms));
+
+        if let (Err(err), Err(_)) = (&wsh, &tapscript) {
+            return Err(err.clone());
+        }
+
+        Ok(ContextComparison { wsh, tapscript })
+    }
+
+    /// Check if the miniscript is valid (type-checks correctly).
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        // SAFETY: self.ptr is valid while self exists
+        unsafe { miniscript_is_valid(self.ptr) }
+    }
+
+    /// Check if the miniscript is sane.
+    ///
+    /// This includes checks for:
+    /// - No duplicate keys
+    /// - No timelock mixing
+    /// - Within resource limits
+    #[must_use]
+    pub fn is_sane(&self) -> bool {
+        // SAFETY: self.ptr is valid while self exists
+        unsafe { miniscript_is_sane(self.ptr) }
+    }
+
+    /// Check if the miniscript is sane, reporting the failing check if not.
+    ///
+    /// Runs the checks that make up [`is_sane()`](Self::is_sane) in the
+    /// order Bitcoin Core evaluates them and returns the first that fails,
+    /// so the result is `Ok` exactly when `is_sane()` is true. Use
+    /// [`parse_checked()`](Self::parse_checked) to get every failing check.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`Warning`] of the first check that fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, Warning};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),pk(A))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.sanity(), Err(Warning::DuplicateKey));
+    /// ```
+    pub fn sanity(&self) -> SanityResult {
+        let checks = [
+            (self.is_valid_top_level(), Warning::NotTopLevel),
+            (
+                self.check_ops_limit() && self.check_stack_size(),
+                Warning::ResourceLimitExceeded,
+            ),
+            (self.is_non_malleable(), Warning::Malleable),
+            (!self.has_timelock_mix(), Warning::TimelockMix),
+            (self.check_duplicate_key(), Warning::DuplicateKey),
+            (self.needs_signature(), Warning::NoSignatureRequired),
+        ];
+        match checks.into_iter().find(|(passed, _)| !passed) {
+            Some((_, failure)) => Err(failure),
+            None => Ok(()),
+        }
+    }
+
+    /// Get the type properties of the miniscript.
+    ///
+    /// Returns a string like "Bdems" where each letter indicates a property.
+    #[must_use]
+    pub fn get_type(&self) -> Option<String> {
+        // SAFETY: self.ptr is valid while self exists
+        let c_str = unsafe { miniscript_get_type(self.ptr) };
+        if c_str.is_null() {
+            return None;
+        }
+
+        // SAFETY: c_str is a valid C string
         let result = unsafe { CStr::from_ptr(c_str) }
             .to_string_lossy()
             .into_owned();
@@ -1161,6 +2889,21 @@ impl Miniscript {
         Some(result)
     }
 
+    /// Get the type properties of the miniscript as a [`MiniscriptType`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// assert!(ms.typ().unwrap().has('B'));
+    /// ```
+    #[must_use]
+    pub fn typ(&self) -> Option<MiniscriptType> {
+        self.get_type().map(|flags| MiniscriptType { flags })
+    }
+
     /// Get the maximum witness size for satisfying this miniscript.
     #[must_use]
     pub fn max_satisfaction_size(&self) -> Option<usize> {
@@ -1173,12 +2916,96 @@ impl Miniscript {
         }
     }
 
+    /// Get the maximum witness size for satisfying this miniscript, assuming
+    /// every signature is `sig_len` bytes long.
+    ///
+    /// [`max_satisfaction_size()`](Self::max_satisfaction_size) assumes
+    /// worst-case signatures (72 bytes for ECDSA, 65 for Schnorr). Use this to
+    /// estimate with e.g. low-R ECDSA signatures (71 bytes) or Schnorr
+    /// signatures using the default sighash (64 bytes). `sig_len` excludes the
+    /// one-byte length prefix of the witness element.
+    ///
+    /// Returns `None` if the miniscript cannot be satisfied.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Miniscript, Context};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// assert_eq!(ms.satisfaction_size_with(72), ms.max_satisfaction_size());
+    /// ```
+    #[must_use]
+    pub fn satisfaction_size_with(&self, sig_len: usize) -> Option<usize> {
+        let mut size: usize = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { miniscript_satisfaction_size_with_sig(self.ptr, sig_len, &raw mut size) } {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    /// Estimate the fee, in satoshis, of satisfying this miniscript.
+    ///
+    /// The maximum satisfaction weight is the witness size reported by
+    /// [`satisfaction_size_with()`](Self::satisfaction_size_with), since
+    /// witness bytes count as one weight unit each. It is converted to virtual bytes by
+    /// dividing by 4 and rounding **up** (`ceil(weight / 4)`), matching how
+    /// Bitcoin Core rounds transaction vsize, and then multiplied by the fee rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_rate_sat_per_vb` - The fee rate in satoshis per virtual byte
+    /// * `use_max_sig` - Whether to assume maximum-size signatures, as in
+    ///   [`Descriptor::max_satisfaction_weight()`](crate::Descriptor::max_satisfaction_weight):
+    ///   73-byte ECDSA signature elements (high-R) rather than 72, and 66-byte
+    ///   Schnorr ones (explicit sighash) rather than 65. Both include the
+    ///   length prefix.
+    ///
+    /// # Returns
+    ///
+    /// The estimated cost in satoshis, or `None` if the satisfaction size is unknown.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn spend_cost(&self, fee_rate_sat_per_vb: f64, use_max_sig: bool) -> Option<f64> {
+        let weight = self.satisfaction_size_with(self.context.sig_len(use_max_sig))?;
+        let vbytes = weight.div_ceil(4);
+        Some(vbytes as f64 * fee_rate_sat_per_vb)
+    }
+
     /// Get the context this miniscript was parsed with.
     #[must_use]
     pub const fn context(&self) -> Context {
         self.context
     }
 
+    /// Get the underlying C `MiniscriptNode` for use with other C functions.
+    ///
+    /// The node stays owned by this `Miniscript`.
+    ///
+    /// # Safety
+    ///
+    /// The pointer is only valid while `self` is alive and must not be freed
+    /// by the caller. The node must not be modified through it, as
+    /// `Miniscript` is shared between threads on the assumption that the
+    /// node is immutable.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, MiniscriptNode};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// // SAFETY: the pointer is not used after `ms` is dropped
+    /// let node: *const MiniscriptNode = unsafe { ms.as_raw() };
+    /// assert!(!node.is_null());
+    /// ```
+    #[must_use]
+    pub const unsafe fn as_raw(&self) -> *const MiniscriptNode {
+        self.ptr
+    }
+
     /// Check if the miniscript is non-malleable.
     #[must_use]
     pub fn is_non_malleable(&self) -> bool {
@@ -1193,6 +3020,29 @@ impl Miniscript {
         unsafe { miniscript_needs_signature(self.ptr) }
     }
 
+    /// Check if some way of satisfying the miniscript requires a hash preimage.
+    ///
+    /// Unlike [`hash_locks()`](Self::hash_locks), which lists every hash
+    /// lock, this only counts hash locks on a satisfiable spend path: the
+    /// `sha256` in `and_v(v:sha256(H),0)` is never revealed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let h = "0000000000000000000000000000000000000000000000000000000000000001";
+    /// let htlc = format!("or_d(pk(A),and_v(v:pk(B),sha256({h})))");
+    /// let ms = Miniscript::from_str(&htlc, Context::Wsh).unwrap();
+    /// assert!(ms.needs_preimage());
+    /// ```
+    #[must_use]
+    pub fn needs_preimage(&self) -> bool {
+        fragment::collect(self.ptr)
+            .and_then(|fragments| fragment::needs_preimage(&fragments))
+            .unwrap_or(false)
+    }
+
     /// Check if the miniscript has a timelock mix (mixing height and time locks).
     #[must_use]
     pub fn has_timelock_mix(&self) -> bool {
@@ -1200,6 +3050,306 @@ impl Miniscript {
         unsafe { miniscript_has_timelock_mix(self.ptr) }
     }
 
+    /// Report where the miniscript mixes heightlocks and timelocks.
+    ///
+    /// This complements [`has_timelock_mix()`](Self::has_timelock_mix) by
+    /// telling whether the mixing happens among `after` or `older` timelocks.
+    /// Absolute and relative timelocks may be freely combined, so that is
+    /// never reported as mixing.
+    #[must_use]
+    pub fn timelock_mix_detail(&self) -> TimelockMixReport {
+        let mut after = false;
+        let mut older = false;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { ffi::miniscript_timelock_mix_detail(self.ptr, &raw mut after, &raw mut older) }
+        {
+            TimelockMixReport {
+                height_time_in_after: after,
+                height_time_in_older: older,
+            }
+        } else {
+            TimelockMixReport::default()
+        }
+    }
+
+    /// Check whether the miniscript could be spent holding only some keys.
+    ///
+    /// Runs a size-estimation satisfaction in which the given keys can sign
+    /// ([`Availability::Maybe`]) and everything else is unavailable, so no
+    /// signatures are needed. Keys are matched by the name written in the
+    /// miniscript (e.g. `b"A"`) or by the public key bytes a [`Satisfier`]
+    /// would be asked to sign for. Timelocks and hash preimages count as
+    /// unavailable.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),pk(B))", Context::Wsh).unwrap();
+    /// assert!(ms.can_satisfy_with_keys(&[b"A"]));
+    /// ```
+    #[must_use]
+    pub fn can_satisfy_with_keys(&self, available_keys: &[&[u8]]) -> bool {
+        let keys: Vec<*const u8> = available_keys.iter().map(|k| k.as_ptr()).collect();
+        let lens: Vec<usize> = available_keys.iter().map(|k| k.len()).collect();
+        // SAFETY: self.ptr is valid while self exists, and keys/lens describe
+        // `available_keys`, which outlives the call
+        unsafe {
+            ffi::miniscript_can_satisfy_with_keys(
+                self.ptr,
+                keys.as_ptr(),
+                lens.as_ptr(),
+                keys.len(),
+            )
+        }
+    }
+
+    /// Estimate how likely each disjunctive branch is to be used.
+    ///
+    /// Bitcoin Core's miniscript has no likelihood annotations and its
+    /// satisfier simply picks the smallest witness, so nothing is inferred
+    /// from the script. Instead every alternative is assumed equally likely:
+    /// the two branches of an `or_*` each get half of their parent's
+    /// probability, and each of the `n` subs of `thresh(k,...)` gets `k/n` of
+    /// it. `andor(X,Y,Z)` is reported as its two branches `X` (with `Y`) and
+    /// `Z`. These are heuristics for fee estimation, not properties of the
+    /// script.
+    ///
+    /// Branches are returned in pre-order, each as its miniscript string
+    /// together with its probability.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),or_i(pk(B),pk(C)))", Context::Wsh).unwrap();
+    /// let branches = ms.branch_probabilities();
+    /// assert_eq!(branches[0], ("pk(A)".to_string(), 0.5));
+    /// assert_eq!(branches[3], ("pk(C)".to_string(), 0.25));
+    /// ```
+    #[must_use]
+    pub fn branch_probabilities(&self) -> Vec<(String, f64)> {
+        let Some(branches) =
+            fragment::collect(self.ptr).and_then(|f| fragment::branch_probabilities(&f))
+        else {
+            return Vec::new();
+        };
+        let positions: Vec<usize> = branches.iter().map(|&(index, _)| index).collect();
+        let mut strings_ptr: *mut *mut std::os::raw::c_char = ptr::null_mut();
+
+        // SAFETY: self.ptr is valid while self exists and positions holds
+        // positions.len() elements
+        if !unsafe {
+            ffi::miniscript_subtree_strings(
+                self.ptr,
+                positions.as_ptr(),
+                positions.len(),
+                &raw mut strings_ptr,
+            )
+        } || strings_ptr.is_null()
+        {
+            return Vec::new();
+        }
+
+        // SAFETY: strings_ptr holds one string per position
+        let strings = unsafe { std::slice::from_raw_parts(strings_ptr, positions.len()) };
+        let result = strings
+            .iter()
+            .zip(branches)
+            .filter(|(string, _)| !string.is_null())
+            .map(|(&string, (_, probability))| {
+                // SAFETY: each non-null string is a valid C string
+                let string = unsafe { CStr::from_ptr(string) }
+                    .to_string_lossy()
+                    .into_owned();
+                (string, probability)
+            })
+            .collect();
+        unsafe { ffi::miniscript_free_strings(strings_ptr, positions.len()) };
+        result
+    }
+
+    /// Count the distinct ways this miniscript can be spent.
+    ///
+    /// Each disjunction branch and each choice of `k` sub-expressions in a
+    /// threshold counts as a separate path; conjunctions multiply the paths
+    /// of their operands. For example `or_i(pk(A),pk(B))` has two spend
+    /// paths and `and_v(v:pk(A),pk(B))` has one. Individual paths can be
+    /// satisfied with [`satisfy_branch()`](Self::satisfy_branch).
+    ///
+    /// Returns `None` if the count does not fit in a `usize`.
+    #[must_use]
+    pub fn spend_path_count(&self) -> Option<usize> {
+        let mut count: usize = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { ffi::miniscript_spend_path_count(self.ptr, &raw mut count) } {
+            Some(count)
+        } else {
+            None
+        }
+    }
+
+    /// List who can spend this miniscript: the keys required by each path.
+    ///
+    /// Spend paths are enumerated as by
+    /// [`spend_path_count()`](Self::spend_path_count). A path is included if
+    /// it can be satisfied non-malleably with only its own signatures,
+    /// preimages and timelocks, which excludes paths without any signature.
+    /// Paths requiring the same keys (e.g. differing only in a timelock) are
+    /// reported once. For `thresh(2,pk(A),s:pk(B),s:pk(C))` this gives
+    /// `{A,B}`, `{A,C}` and `{B,C}`.
+    ///
+    /// At most [`MAX_SPEND_PATHS`] paths are examined; `truncated` is set
+    /// if there are more.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),pk(C)))", Context::Wsh).unwrap();
+    /// let signers = ms.required_signers();
+    /// assert_eq!(signers.sets[0].keys, vec![b"A".to_vec()]);
+    /// assert_eq!(signers.sets[1].keys, vec![b"B".to_vec(), b"C".to_vec()]);
+    /// ```
+    #[must_use]
+    pub fn required_signers(&self) -> RequiredSigners {
+        let mut keys_ptr: *mut *mut std::os::raw::c_char = ptr::null_mut();
+        let mut sizes_ptr: *mut usize = ptr::null_mut();
+        let mut set_count: usize = 0;
+        let mut truncated = false;
+
+        // SAFETY: self.ptr is valid while self exists
+        let success = unsafe {
+            ffi::miniscript_required_signers(
+                self.ptr,
+                MAX_SPEND_PATHS,
+                &raw mut keys_ptr,
+                &raw mut sizes_ptr,
+                &raw mut set_count,
+                &raw mut truncated,
+            )
+        };
+        if !success {
+            return RequiredSigners::default();
+        }
+
+        let mut sets = Vec::with_capacity(set_count);
+        if set_count > 0 && !sizes_ptr.is_null() {
+            // SAFETY: sizes_ptr holds set_count sizes, and keys_ptr holds
+            // their sum of C strings
+            let sizes = unsafe { std::slice::from_raw_parts(sizes_ptr, set_count) };
+            let total: usize = sizes.iter().sum();
+            let keys = if keys_ptr.is_null() {
+                &[][..]
+            } else {
+                unsafe { std::slice::from_raw_parts(keys_ptr, total) }
+            };
+            let mut keys = keys.iter().map(|&key| {
+                if key.is_null() {
+                    Vec::new()
+                } else {
+                    unsafe { CStr::from_ptr(key) }.to_bytes().to_vec()
+                }
+            });
+            for &size in sizes {
+                sets.push(SignerSet {
+                    keys: keys.by_ref().take(size).collect(),
+                });
+            }
+        }
+
+        // SAFETY: the arrays were returned by miniscript_required_signers
+        unsafe { ffi::miniscript_free_signer_sets(keys_ptr, sizes_ptr, set_count) };
+
+        RequiredSigners { sets, truncated }
+    }
+
+    /// Get the timelocks required by each spend path.
+    ///
+    /// Spend paths are enumerated as by
+    /// [`spend_path_count()`](Self::spend_path_count), so the entry at index
+    /// `i` describes the path satisfied by
+    /// [`satisfy_branch()`](Self::satisfy_branch) with branch `i`. Paths
+    /// without timelocks have neither `after` nor `older` set.
+    ///
+    /// Returns `None` if there are more than [`MAX_SPEND_PATHS`] paths, as
+    /// only some of them could be listed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, LockTime, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(1000)))", Context::Wsh).unwrap();
+    /// let locks = ms.branch_timelocks().unwrap();
+    /// assert_eq!(locks[0].after, None);
+    /// assert_eq!(locks[1].after, Some(LockTime::from_height(1000).unwrap()));
+    /// ```
+    #[must_use]
+    pub fn branch_timelocks(&self) -> Option<Vec<BranchTimelock>> {
+        let mut locks_ptr: *mut ffi::MiniscriptBranchTimelock = ptr::null_mut();
+        let mut count: usize = 0;
+
+        // SAFETY: self.ptr is valid while self exists
+        let success = unsafe {
+            ffi::miniscript_all_branch_timelocks(
+                self.ptr,
+                MAX_SPEND_PATHS,
+                &raw mut locks_ptr,
+                &raw mut count,
+            )
+        };
+        if !success {
+            return None;
+        }
+        if locks_ptr.is_null() {
+            return Some(Vec::new());
+        }
+
+        // SAFETY: locks_ptr holds count entries returned by
+        // miniscript_all_branch_timelocks
+        let raw = unsafe { std::slice::from_raw_parts(locks_ptr, count) };
+        let locks = raw
+            .iter()
+            .map(|lock| {
+                let mut conflicting = false;
+                let after = match (lock.after_height, lock.after_time) {
+                    (0, 0) => None,
+                    (height, 0) => Some(LockTime::from_consensus(height)),
+                    (0, time) => Some(LockTime::from_consensus(time)),
+                    _ => {
+                        conflicting = true;
+                        None
+                    }
+                };
+                // Both values are masked to 16 bits by the C++ side
+                let older = match (lock.older_blocks, lock.older_time) {
+                    (0, 0) => None,
+                    (blocks, 0) => u16::try_from(blocks)
+                        .ok()
+                        .map(RelativeLockTime::from_height),
+                    (0, time) => u16::try_from(time)
+                        .ok()
+                        .map(RelativeLockTime::from_512_second_intervals),
+                    _ => {
+                        conflicting = true;
+                        None
+                    }
+                };
+                BranchTimelock {
+                    after,
+                    older,
+                    conflicting,
+                }
+            })
+            .collect();
+        unsafe { ffi::miniscript_free_branch_timelocks(locks_ptr) };
+        Some(locks)
+    }
+
     /// Check if the miniscript is valid at the top level.
     #[must_use]
     pub fn is_valid_top_level(&self) -> bool {
@@ -1221,19 +3371,135 @@ impl Miniscript {
         unsafe { miniscript_check_stack_size(self.ptr) }
     }
 
-    /// Check if the miniscript has no duplicate keys.
+    /// Get the resource usage of the miniscript against its context's limits.
+    ///
+    /// The limits are those checked by Bitcoin Core: the ops limit and the
+    /// witness stack item limit apply to P2WSH only, while the execution
+    /// stack limit is only checked for Tapscript. The booleans therefore
+    /// agree with [`check_ops_limit()`](Self::check_ops_limit) and
+    /// [`check_stack_size()`](Self::check_stack_size).
     #[must_use]
-    pub fn check_duplicate_key(&self) -> bool {
-        // SAFETY: self.ptr is valid while self exists
-        unsafe { miniscript_check_duplicate_key(self.ptr) }
+    pub fn resource_usage(&self) -> ResourceUsage {
+        let limits = self.context.limits();
+
+        ResourceUsage {
+            ops: ResourceLimit::new(self.get_ops().map(|n| n as usize), limits.max_ops),
+            stack_size: ResourceLimit::new(
+                self.get_stack_size().map(|n| n as usize),
+                limits.max_witness_items,
+            ),
+            exec_stack_size: ResourceLimit::new(
+                self.get_exec_stack_size().map(|n| n as usize),
+                limits.max_exec_stack_size,
+            ),
+            script_size: ResourceLimit::new(self.get_script_size(), Some(limits.max_script_size)),
+        }
     }
 
-    /// Get the number of ops in the miniscript.
+    /// Check whether the miniscript meets the standardness limits of its context.
+    ///
+    /// For P2WSH this means a script of at most 3600 bytes, at most 201
+    /// non-push opcodes and at most 100 witness stack items. Tapscript has
+    /// no opcode limit (see [`get_static_ops()`](Self::get_static_ops) for
+    /// the count); its script size is bounded by the maximum standard
+    /// transaction weight and its execution stack by 1000 elements.
+    ///
+    /// This complements [`is_sane()`](Self::is_sane), which also covers
+    /// malleability, timelock mixing and duplicate keys. Use
+    /// [`resource_usage()`](Self::resource_usage) to find out which limit
+    /// is exceeded.
     #[must_use]
-    pub fn get_ops(&self) -> Option<u32> {
-        let mut ops: u32 = 0;
-        // SAFETY: self.ptr is valid while self exists
-        if unsafe { miniscript_get_ops(self.ptr, &raw mut ops) } {
+    pub fn is_standard(&self) -> bool {
+        self.is_valid() && self.resource_usage().within_limits()
+    }
+
+    /// List the standardness limits the miniscript exceeds.
+    ///
+    /// This checks the same limits as [`is_standard()`](Self::is_standard),
+    /// naming each exceeded one with its measured value and maximum.
+    /// Resources that cannot be measured are not reported.
+    ///
+    /// # Returns
+    ///
+    /// `None` if every resource is within its limit, otherwise the
+    /// violations in the order ops, stack size, execution stack size and
+    /// script size.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// assert_eq!(ms.exceeds_standard_limits(), None);
+    /// ```
+    #[must_use]
+    pub fn exceeds_standard_limits(&self) -> Option<Vec<LimitViolation>> {
+        let usage = self.resource_usage();
+        let violations: Vec<LimitViolation> = [
+            (LimitKind::Ops, usage.ops),
+            (LimitKind::StackSize, usage.stack_size),
+            (LimitKind::ExecStackSize, usage.exec_stack_size),
+            (LimitKind::ScriptSize, usage.script_size),
+        ]
+        .into_iter()
+        .filter_map(|(kind, resource)| match (resource.used, resource.limit) {
+            (Some(measured), Some(max)) if measured > max => Some(LimitViolation {
+                kind,
+                measured,
+                max,
+            }),
+            _ => None,
+        })
+        .collect();
+        (!violations.is_empty()).then_some(violations)
+    }
+
+    /// Check if the miniscript has no duplicate keys.
+    #[must_use]
+    pub fn check_duplicate_key(&self) -> bool {
+        // SAFETY: self.ptr is valid while self exists
+        unsafe { miniscript_check_duplicate_key(self.ptr) }
+    }
+
+    /// Get every key that appears more than once.
+    ///
+    /// Each duplicated key is listed once, in order of first appearance,
+    /// with its bytes as written in the miniscript. A key used both as
+    /// `pk` and `pkh` counts as duplicated, as in
+    /// [`check_duplicate_key()`](Self::check_duplicate_key).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),pk(A))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.duplicate_keys(), vec![b"A".to_vec()]);
+    /// ```
+    #[must_use]
+    pub fn duplicate_keys(&self) -> Vec<Vec<u8>> {
+        let usages = self.key_usages();
+        let mut counts: HashMap<&[u8], usize> = HashMap::new();
+        for usage in &usages {
+            *counts.entry(&usage.key).or_default() += 1;
+        }
+
+        let mut duplicates: Vec<Vec<u8>> = Vec::new();
+        for usage in &usages {
+            if counts[usage.key.as_slice()] > 1 && !duplicates.contains(&usage.key) {
+                duplicates.push(usage.key.clone());
+            }
+        }
+        duplicates
+    }
+
+    /// Get the number of ops in the miniscript.
+    #[must_use]
+    pub fn get_ops(&self) -> Option<u32> {
+        let mut ops: u32 = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { miniscript_get_ops(self.ptr, &raw mut ops) } {
             Some(ops)
         } else {
             None
@@ -1252,158 +3518,1026 @@ impl Miniscript {
         }
     }
 
-    /// Get the maximum execution stack size.
-    #[must_use]
-    pub fn get_exec_stack_size(&self) -> Option<u32> {
-        let mut size: u32 = 0;
-        // SAFETY: self.ptr is valid while self exists
-        if unsafe { miniscript_get_exec_stack_size(self.ptr, &raw mut size) } {
-            Some(size)
-        } else {
-            None
-        }
+    /// Get the maximum execution stack size.
+    #[must_use]
+    pub fn get_exec_stack_size(&self) -> Option<u32> {
+        let mut size: u32 = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { miniscript_get_exec_stack_size(self.ptr, &raw mut size) } {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    /// Get the script size.
+    #[must_use]
+    pub fn get_script_size(&self) -> Option<usize> {
+        let mut size: usize = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { miniscript_get_script_size(self.ptr, &raw mut size) } {
+            Some(size)
+        } else {
+            None
+        }
+    }
+
+    /// Check if the miniscript has valid satisfactions.
+    #[must_use]
+    pub fn valid_satisfactions(&self) -> bool {
+        // SAFETY: self.ptr is valid while self exists
+        unsafe { miniscript_valid_satisfactions(self.ptr) }
+    }
+
+    /// Get the static ops count (for Tapscript).
+    #[must_use]
+    pub fn get_static_ops(&self) -> Option<u32> {
+        let mut ops: u32 = 0;
+        // SAFETY: self.ptr is valid while self exists
+        if unsafe { miniscript_get_static_ops(self.ptr, &raw mut ops) } {
+            Some(ops)
+        } else {
+            None
+        }
+    }
+
+    /// Convert the miniscript to raw script bytes.
+    #[must_use]
+    pub fn to_script_bytes(&self) -> Option<Vec<u8>> {
+        self.with_script_bytes(<[u8]>::to_vec)
+    }
+
+    /// Pass the raw script bytes to a closure without copying them.
+    ///
+    /// The script is borrowed from the buffer produced by the C wrapper,
+    /// which is freed once `f` returns. Prefer this over
+    /// [`to_script_bytes()`](Self::to_script_bytes) when the script is only
+    /// hashed or measured.
+    ///
+    /// # Returns
+    ///
+    /// The result of `f`, or `None` if the script cannot be produced.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Miniscript, Context};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// let len = ms.with_script_bytes(<[u8]>::len).unwrap();
+    /// assert_eq!(len, 35);
+    /// ```
+    pub fn with_script_bytes<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        /// Frees the script buffer, even if the closure panics.
+        struct ScriptBuffer(*mut u8);
+
+        impl Drop for ScriptBuffer {
+            fn drop(&mut self) {
+                unsafe { miniscript_free_bytes(self.0) };
+            }
+        }
+
+        let mut script_ptr: *mut u8 = ptr::null_mut();
+        let mut script_len: usize = 0;
+
+        // SAFETY: self.ptr is valid while self exists
+        if !unsafe { miniscript_to_script(self.ptr, &raw mut script_ptr, &raw mut script_len) }
+            || script_ptr.is_null()
+        {
+            return None;
+        }
+
+        let buffer = ScriptBuffer(script_ptr);
+        // SAFETY: script_ptr is valid and contains script_len bytes until
+        // buffer is dropped
+        let script = unsafe { std::slice::from_raw_parts(buffer.0, script_len) };
+        Some(f(script))
+    }
+
+    /// Check whether this miniscript compiles to exactly the given script.
+    ///
+    /// The script is compared in place with the buffer produced by the C
+    /// wrapper, without copying it.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Miniscript, Context};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// let script = ms.to_script_bytes().unwrap();
+    /// assert!(ms.matches_script(&script));
+    /// ```
+    #[must_use]
+    pub fn matches_script(&self, script: &[u8]) -> bool {
+        self.with_script_bytes(|bytes| bytes == script)
+            .unwrap_or(false)
+    }
+
+    /// Check whether a raw script decodes to the same miniscript as
+    /// `expected`.
+    ///
+    /// The script is parsed in `context` and compared with `expected` by
+    /// canonical form. Both sides go through Core's script decoder, so keys
+    /// are compared by their encoding whether `expected` was written with
+    /// hex keys or placeholder names. Unlike
+    /// [`matches_script()`](Self::matches_script), this returns `false` if
+    /// the script is not valid miniscript in `context` or `expected` has a
+    /// different context.
+    #[must_use]
+    pub fn from_script_matches(script: &[u8], context: Context, expected: &Self) -> bool {
+        if expected.context != context {
+            return false;
+        }
+        let canonical = |bytes: &[u8]| Self::from_script_bytes(bytes, context).ok()?.to_string();
+        let Some(actual) = canonical(script) else {
+            return false;
+        };
+        expected
+            .with_script_bytes(canonical)
+            .flatten()
+            .is_some_and(|wanted| wanted == actual)
+    }
+
+    /// Convert the miniscript to a [`bitcoin::ScriptBuf`].
+    ///
+    /// This returns the script as a proper Bitcoin script type from the `bitcoin` crate.
+    #[must_use]
+    pub fn to_script(&self) -> Option<ScriptBuf> {
+        self.to_script_bytes().map(ScriptBuf::from_bytes)
+    }
+
+    /// Disassemble the script into opcode mnemonics.
+    ///
+    /// Uses the ASM formatting of [`bitcoin::Script`], e.g.
+    /// `OP_PUSHBYTES_33 02...ab OP_CHECKSIG`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Miniscript, Context};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// assert!(ms.script_asm().unwrap().ends_with("OP_CHECKSIG"));
+    /// ```
+    #[must_use]
+    pub fn script_asm(&self) -> Option<String> {
+        self.to_script().map(|script| script.to_asm_string())
+    }
+
+    /// Get the script as a lowercase hex string.
+    ///
+    /// Read it back with [`from_script_hex()`](Self::from_script_hex).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("older(16)", Context::Wsh).unwrap();
+    /// assert_eq!(ms.script_hex().unwrap(), "60b2");
+    /// ```
+    #[must_use]
+    pub fn script_hex(&self) -> Option<String> {
+        use bitcoin::hex::DisplayHex;
+
+        self.with_script_bytes(|script| script.to_lower_hex_string())
+    }
+
+    /// Compute the SHA256 of the witness script.
+    ///
+    /// This is the 32-byte witness program of the P2WSH output committing to
+    /// this miniscript.
+    ///
+    /// # Returns
+    ///
+    /// `None` for [`Context::Tapscript`] or if the script cannot be produced.
+    #[must_use]
+    pub fn wsh_script_hash(&self) -> Option<[u8; 32]> {
+        use bitcoin::hashes::Hash;
+
+        if self.context != Context::Wsh {
+            return None;
+        }
+        let script = self.to_script_bytes()?;
+        Some(Sha256::hash(&script).to_byte_array())
+    }
+
+    /// Get the P2WSH witness program committing to this miniscript.
+    ///
+    /// This is a version 0 program over
+    /// [`wsh_script_hash()`](Self::wsh_script_hash), ready for
+    /// [`bitcoin::Address::from_witness_program()`].
+    ///
+    /// # Returns
+    ///
+    /// `None` for [`Context::Tapscript`], where the output also commits to
+    /// an internal key, or if the script cannot be produced.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// let program = ms.witness_program().unwrap();
+    /// let address = bitcoin::Address::from_witness_program(program, bitcoin::KnownHrp::Mainnet);
+    /// ```
+    #[must_use]
+    pub fn witness_program(&self) -> Option<bitcoin::WitnessProgram> {
+        let hash = self.wsh_script_hash()?;
+        bitcoin::WitnessProgram::new(bitcoin::WitnessVersion::V0, &hash).ok()
+    }
+
+    /// Get the P2SH-wrapped P2WSH `scriptPubKey` committing to this
+    /// miniscript, as produced by an `sh(wsh(...))` descriptor.
+    ///
+    /// The redeem script is `OP_0 <sha256(script)>` and the output is
+    /// `OP_HASH160 <hash160(redeem script)> OP_EQUAL`.
+    ///
+    /// # Returns
+    ///
+    /// `None` for [`Context::Tapscript`] or if the script cannot be produced.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// assert!(ms.sh_wsh_script_pubkey().unwrap().is_p2sh());
+    /// ```
+    #[must_use]
+    pub fn sh_wsh_script_pubkey(&self) -> Option<ScriptBuf> {
+        let redeem_script = ScriptBuf::new_witness_program(&self.witness_program()?);
+        Some(ScriptBuf::new_p2sh(&redeem_script.script_hash()))
+    }
+
+    /// Parse a miniscript from raw script bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::ContextMismatch`] if the script uses an opcode of the
+    /// other context (`OP_CHECKSIGADD` under [`Context::Wsh`],
+    /// `OP_CHECKMULTISIG(VERIFY)` under [`Context::Tapscript`]), or another
+    /// error if parsing fails.
+    pub fn from_script_bytes(script: &[u8], context: Context) -> Result<Self, Error> {
+        if let Some(found_opcode) = foreign_opcode(script, context) {
+            return Err(Error::ContextMismatch {
+                expected: context,
+                found_opcode,
+            });
+        }
+
+        let mut node_ptr: *mut MiniscriptNode = ptr::null_mut();
+
+        let span = ffi_span!(
+            "Miniscript::from_script_bytes",
+            script_len = script.len(),
+            %context
+        );
+        // SAFETY: We're passing valid pointers and the C code handles null checks.
+        let result = unsafe {
+            miniscript_from_script(
+                script.as_ptr(),
+                script.len(),
+                context.into(),
+                &raw mut node_ptr,
+            )
+        };
+        span.record(result.success);
+
+        if result.success {
+            Ok(Self {
+                ptr: node_ptr,
+                context,
+            })
+        } else {
+            let message = if result.error_message.is_null() {
+                "unknown error".to_string()
+            } else {
+                // SAFETY: error_message is a valid C string if not null
+                let msg = unsafe { CStr::from_ptr(result.error_message) }
+                    .to_string_lossy()
+                    .into_owned();
+                unsafe { miniscript_free_string(result.error_message) };
+                msg
+            };
+            Err(Error::from_ffi(message, result.error_code))
+        }
+    }
+
+    /// Parse a miniscript from a hex-encoded script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `hex` is not valid hex, or if parsing the decoded
+    /// script fails as in [`from_script_bytes()`](Self::from_script_bytes).
+    pub fn from_script_hex(hex: &str, context: Context) -> Result<Self, Error> {
+        use bitcoin::hex::FromHex;
+
+        let script =
+            Vec::<u8>::from_hex(hex).map_err(|e| Error::new(format!("invalid script hex: {e}")))?;
+        Self::from_script_bytes(&script, context)
+    }
+
+    /// Serialize the miniscript to a compact binary form.
+    ///
+    /// The form is one context tag byte (`0` for WSH, `1` for Tapscript)
+    /// followed by the script. Read it back with
+    /// [`from_bytes()`](Self::from_bytes). Key names are not stored: named
+    /// keys come back as the hex of their placeholder keys, as with
+    /// [`from_script_bytes()`](Self::from_script_bytes).
+    ///
+    /// # Returns
+    ///
+    /// The serialized bytes, or `None` if the script cannot be produced.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),older(100))", Context::Wsh).unwrap();
+    /// let bytes = ms.to_bytes().unwrap();
+    /// let decoded = Miniscript::from_bytes(&bytes).unwrap();
+    /// assert!(decoded.matches_script(&ms.to_script_bytes().unwrap()));
+    /// ```
+    #[must_use]
+    pub fn to_bytes(&self) -> Option<Vec<u8>> {
+        let tag = match self.context {
+            Context::Wsh => CONTEXT_TAG_WSH,
+            Context::Tapscript => CONTEXT_TAG_TAPSCRIPT,
+        };
+        self.with_script_bytes(|script| {
+            let mut bytes = Vec::with_capacity(1 + script.len());
+            bytes.push(tag);
+            bytes.extend_from_slice(script);
+            bytes
+        })
+    }
+
+    /// Parse a miniscript from the binary form produced by
+    /// [`to_bytes()`](Self::to_bytes).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is empty, the context tag is unknown,
+    /// or the script does not decode to a miniscript.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (&tag, script) = bytes
+            .split_first()
+            .ok_or_else(|| Error::new("empty input, expected a context tag"))?;
+        let context = match tag {
+            CONTEXT_TAG_WSH => Context::Wsh,
+            CONTEXT_TAG_TAPSCRIPT => Context::Tapscript,
+            _ => return Err(Error::new(format!("unknown context tag {tag}"))),
+        };
+        Self::from_script_bytes(script, context)
+    }
+
+    /// Visit every fragment of the miniscript tree in pre-order.
+    ///
+    /// Each fragment is passed to `f` before its children, which are visited
+    /// left to right. See [`Fragment::child_count()`] for reconstructing the
+    /// tree shape from the visiting order.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Fragment, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_b(pk(A),s:pk(B))", Context::Wsh).unwrap();
+    /// let mut fragments = Vec::new();
+    /// ms.walk(|f| fragments.push(f.clone()));
+    /// assert_eq!(fragments[0], Fragment::OrB);
+    /// ```
+    pub fn walk<F: FnMut(&Fragment)>(&self, mut f: F) {
+        for fragment in fragment::collect(self.ptr).unwrap_or_default() {
+            f(&fragment);
+        }
+    }
+
+    /// Get the nesting depth of the miniscript.
+    ///
+    /// This is the number of fragments on the longest path from the root
+    /// to a leaf. Wrappers and the fragments hidden by syntactic sugar
+    /// count, so `pk(A)` (which is `c:pk_k(A)`) has depth 2.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),or_i(pk(B),pk(C)))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.depth(), 4);
+    /// ```
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        fragment::collect(self.ptr)
+            .map(|fragments| fragment::depth(&fragments))
+            .unwrap_or(0)
+    }
+
+    /// Get every subtree of the miniscript as its own [`Miniscript`].
+    ///
+    /// Subtrees are returned in pre-order, starting with a copy of the whole
+    /// miniscript, and share its context. Wrappers count as nodes, so
+    /// `v:pk(A)` yields `v:pk(A)`, `pk(A)` and `pk_k(A)`. Subtrees are
+    /// generally not valid at the top level, but each subtree of a valid
+    /// miniscript is [`is_valid()`](Self::is_valid).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).unwrap();
+    /// let subs = ms.subexpressions();
+    /// assert_eq!(subs[1].to_string().unwrap(), "v:pk(A)");
+    /// ```
+    #[must_use]
+    pub fn subexpressions(&self) -> Vec<Self> {
+        let mut nodes_ptr: *mut *mut MiniscriptNode = ptr::null_mut();
+        let mut count: usize = 0;
+
+        // SAFETY: self.ptr is valid while self exists
+        if !unsafe { ffi::miniscript_subexpressions(self.ptr, &raw mut nodes_ptr, &raw mut count) }
+            || nodes_ptr.is_null()
+        {
+            return Vec::new();
+        }
+
+        // SAFETY: nodes_ptr holds count owned nodes; each is freed by the
+        // Miniscript wrapping it
+        let nodes = unsafe { std::slice::from_raw_parts(nodes_ptr, count) };
+        let subs = nodes
+            .iter()
+            .filter(|node| !node.is_null())
+            .map(|&ptr| Self {
+                ptr,
+                context: self.context,
+            })
+            .collect();
+        unsafe { ffi::miniscript_free_node_array(nodes_ptr) };
+        subs
+    }
+
+    /// Get every key in the miniscript along with how it is used.
+    ///
+    /// Keys are returned in the order they appear (pre-order). Keys of
+    /// `multi`/`multi_a` are reported as [`PkKind::Pk`], since the full keys
+    /// are in the script.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, PkKind};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(A),pkh(B))", Context::Wsh).unwrap();
+    /// let usages = ms.key_usages();
+    /// assert_eq!(usages[0].kind, PkKind::Pk);
+    /// assert_eq!(usages[1].kind, PkKind::PkH);
+    /// ```
+    #[must_use]
+    pub fn key_usages(&self) -> Vec<KeyUsage> {
+        let mut usages = Vec::new();
+        self.walk(|fragment| match fragment {
+            Fragment::PkK(key) => usages.push(KeyUsage {
+                key: key.clone(),
+                kind: PkKind::Pk,
+            }),
+            Fragment::PkH(key) => usages.push(KeyUsage {
+                key: key.clone(),
+                kind: PkKind::PkH,
+            }),
+            Fragment::Multi { keys, .. } | Fragment::MultiA { keys, .. } => {
+                usages.extend(keys.iter().map(|key| KeyUsage {
+                    key: key.clone(),
+                    kind: PkKind::Pk,
+                }));
+            }
+            _ => {}
+        });
+        usages
+    }
+
+    /// Get every hash lock in the miniscript with its hash function.
+    ///
+    /// Hash locks are returned in the order they appear (pre-order), with
+    /// the digest as committed in the script.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, HashKind, Miniscript};
+    ///
+    /// let h = "0000000000000000000000000000000000000000000000000000000000000001";
+    /// let ms = Miniscript::from_str(&format!("sha256({h})"), Context::Wsh).unwrap();
+    /// assert_eq!(ms.hash_locks()[0].kind, HashKind::Sha256);
+    /// ```
+    #[must_use]
+    pub fn hash_locks(&self) -> Vec<HashLock> {
+        let mut locks = Vec::new();
+        self.walk(|fragment| {
+            let (kind, digest) = match fragment {
+                Fragment::Sha256(digest) => (HashKind::Sha256, digest),
+                Fragment::Hash256(digest) => (HashKind::Hash256, digest),
+                Fragment::Ripemd160(digest) => (HashKind::Ripemd160, digest),
+                Fragment::Hash160(digest) => (HashKind::Hash160, digest),
+                _ => return,
+            };
+            locks.push(HashLock {
+                kind,
+                digest: digest.clone(),
+            });
+        });
+        locks
+    }
+
+    /// Count the fragments of each type in the miniscript.
+    ///
+    /// Fragments are those of Bitcoin Core's tree, as visited by
+    /// [`walk()`](Self::walk): `pkh(A)` counts as one
+    /// [`FragmentKind::WrapC`] and one [`FragmentKind::PkH`], and each
+    /// wrapper letter counts on its own. Types that do not appear are
+    /// absent from the map.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, FragmentKind, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_d(pk(A),pkh(B))", Context::Wsh).unwrap();
+    /// let counts = ms.fragment_counts();
+    /// assert_eq!(counts[&FragmentKind::PkK], 1);
+    /// assert_eq!(counts[&FragmentKind::WrapC], 2);
+    /// ```
+    #[must_use]
+    pub fn fragment_counts(&self) -> HashMap<FragmentKind, usize> {
+        let mut counts = HashMap::new();
+        self.walk(|fragment| *counts.entry(fragment.kind()).or_insert(0) += 1);
+        counts
+    }
+
+    /// Get the structure of the miniscript with keys replaced by placeholders.
+    ///
+    /// Each distinct key is renamed to `A`, `B`, `C`, ... (continuing with
+    /// `AA`, `AB`, ... after `Z`) in order of first appearance; timelocks and
+    /// hashes are kept. Two miniscripts that differ only in their keys have
+    /// the same template, which is useful for grouping scripts derived from
+    /// the same policy.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("and_v(v:pk(X),pk(Y))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.template().unwrap(), "and_v(v:pk(A),pk(B))");
+    /// ```
+    #[must_use]
+    pub fn template(&self) -> Option<String> {
+        let raw = fragment::template(&fragment::collect(self.ptr)?)?;
+        // Round-trip through Core to get its canonical formatting
+        Self::from_str(&raw, self.context).ok()?.to_string()
+    }
+
+    /// Lift the miniscript into the spending [`Policy`] it enforces.
+    ///
+    /// Wrappers are stripped, conjunctions become [`Policy::And`],
+    /// disjunctions become [`Policy::Or`], and `thresh`/`multi`/`multi_a`
+    /// become [`Policy::Thresh`]. `andor(X,Y,Z)` lifts to `Or(And(X,Y),Z)`.
+    /// Unsatisfiable alternatives and trivial conditions are dropped, so the
+    /// `l:`, `u:` and `t:` wrappers add nothing: `l:older(144)` lifts to
+    /// `Older(144)`.
+    ///
+    /// # Returns
+    ///
+    /// The policy, or `None` if the miniscript is invalid, malleable, or
+    /// mixes heightlocks and timelocks (its policy would be misleading).
+    #[must_use]
+    pub fn lift(&self) -> Option<Policy> {
+        if !self.is_valid() || !self.is_non_malleable() || self.has_timelock_mix() {
+            return None;
+        }
+        policy::lift(&fragment::collect(self.ptr)?)
+    }
+
+    /// Lift the miniscript and write the policy in rust-miniscript's
+    /// concrete policy syntax, e.g. `or(pk(A),and(pk(B),after(100)))`.
+    ///
+    /// This is the [`Display`](std::fmt::Display) form of the [`Policy`]
+    /// returned by [`lift()`](Self::lift), suitable for rust-miniscript's
+    /// policy compiler.
+    ///
+    /// # Returns
+    ///
+    /// The policy string, or `None` if the miniscript cannot be lifted.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(100)))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.to_policy_string().unwrap(), "or(pk(A),and(pk(B),after(100)))");
+    /// ```
+    #[must_use]
+    pub fn to_policy_string(&self) -> Option<String> {
+        self.lift().map(|policy| policy.to_string())
+    }
+
+    /// Recognize a common wallet [`Template`] in the lifted policy.
+    ///
+    /// Single keys and `k`-of-`n` key thresholds (including `and`/`or` of
+    /// bare keys) are [`Template::SingleSig`] and [`Template::Multisig`].
+    /// A disjunction of one key path and timelocked key paths is
+    /// [`Template::Inheritance`] if the immediate path is a single key and
+    /// [`Template::TimelockRecovery`] if it is a multisig. Anything else is
+    /// [`Template::Other`].
+    ///
+    /// # Returns
+    ///
+    /// The template, or `None` if the miniscript cannot be
+    /// [`lift()`](Self::lift)ed.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, Template};
+    ///
+    /// let ms = Miniscript::from_str("or_i(multi(2,A,B,C),and_v(v:pk(R),after(52560)))", Context::Wsh).unwrap();
+    /// assert_eq!(ms.classify(), Some(Template::TimelockRecovery));
+    /// ```
+    #[must_use]
+    pub fn classify(&self) -> Option<Template> {
+        self.lift().map(|policy| policy::classify(&policy))
+    }
+
+    /// Produce a witness that satisfies this miniscript.
+    ///
+    /// # Arguments
+    ///
+    /// * `satisfier` - An implementation of the Satisfier trait that provides
+    ///   signatures, hash preimages, and timelock information.
+    /// * `nonmalleable` - If true, only produce non-malleable satisfactions.
+    ///
+    /// # Returns
+    ///
+    /// A `SatisfyResult` containing the availability and witness stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if satisfaction fails or if a `Satisfier` method panics.
+    pub fn satisfy<S: Satisfier + 'static>(
+        &self,
+        satisfier: S,
+        nonmalleable: bool,
+    ) -> Result<SatisfyResult, Error> {
+        let context = SatisfierContext::new(&satisfier);
+        self.satisfy_with(&context, &context.callbacks(), None, nonmalleable)
+    }
+
+    /// Produce a witness using one specific spend path.
+    ///
+    /// Paths are numbered `0..spend_path_count()` (see
+    /// [`spend_path_count()`](Self::spend_path_count)): the left branch of a
+    /// disjunction comes before the right one, and in conjunctions and
+    /// thresholds the earlier sub-expression varies slowest. Threshold
+    /// combinations are taken in lexicographic order. For example in
+    /// `or_i(pk(A),and_v(v:pk(B),after(100)))` path 0 is `pk(A)` and path 1
+    /// is `pk(B)` with `after(100)`.
+    ///
+    /// The satisfier is only asked for the keys, hashes and timelocks of the
+    /// chosen path. If that data also satisfies another path, Bitcoin Core
+    /// may still pick the cheaper of the two.
+    ///
+    /// # Arguments
+    ///
+    /// * `branch` - Index of the spend path to use
+    /// * `satisfier` - Provides signatures, hash preimages, and timelock information
+    /// * `nonmalleable` - If true, only produce non-malleable satisfactions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `branch` is out of range, if satisfaction fails,
+    /// or if a `Satisfier` method panics.
+    pub fn satisfy_branch<S: Satisfier + 'static>(
+        &self,
+        branch: usize,
+        satisfier: S,
+        nonmalleable: bool,
+    ) -> Result<SatisfyResult, Error> {
+        let context = SatisfierContext::new(&satisfier);
+        self.satisfy_with(&context, &context.callbacks(), Some(branch), nonmalleable)
     }
 
-    /// Get the script size.
-    #[must_use]
-    pub fn get_script_size(&self) -> Option<usize> {
-        let mut size: usize = 0;
-        // SAFETY: self.ptr is valid while self exists
-        if unsafe { miniscript_get_script_size(self.ptr, &raw mut size) } {
-            Some(size)
-        } else {
-            None
-        }
+    /// Produce witnesses for several miniscripts with one satisfier.
+    ///
+    /// This is equivalent to calling [`satisfy()`](Self::satisfy) for each
+    /// item, but shares a single callback context across all of them, which
+    /// suits spending several inputs of one transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The miniscripts to satisfy
+    /// * `satisfier` - The satisfier shared by all items
+    /// * `nonmalleable` - If true, only produce non-malleable satisfactions.
+    ///
+    /// # Returns
+    ///
+    /// One result per item, in input order. Once a `Satisfier` method has
+    /// panicked, the satisfier is no longer used and every remaining item
+    /// reports [`Error::Satisfaction`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, SimpleSatisfier};
+    ///
+    /// let items = [
+    ///     Miniscript::from_str("pk(A)", Context::Wsh).unwrap(),
+    ///     Miniscript::from_str("pk(B)", Context::Wsh).unwrap(),
+    /// ];
+    /// let satisfier = SimpleSatisfier::new();
+    ///
+    /// for result in Miniscript::satisfy_many(&items, &satisfier, true) {
+    ///     println!("{:?}", result.map(|r| r.availability));
+    /// }
+    /// ```
+    pub fn satisfy_many<S: Satisfier>(
+        items: &[Self],
+        satisfier: &S,
+        nonmalleable: bool,
+    ) -> Vec<Result<SatisfyResult, Error>> {
+        let context = SatisfierContext::new(satisfier);
+        let callbacks = context.callbacks();
+        items
+            .iter()
+            .map(|ms| ms.satisfy_with(&context, &callbacks, None, nonmalleable))
+            .collect()
     }
 
-    /// Check if the miniscript has valid satisfactions.
+    /// Check whether the satisfier's data allows a non-malleable witness.
+    ///
+    /// Runs a non-malleable satisfaction and, if that fails, a malleable one.
+    /// Bitcoin Core only considers a witness non-malleable if it contains a
+    /// signature, so e.g. a bare timelock or hash lock always reports
+    /// [`Malleability::Malleable`] when it can be satisfied.
+    ///
+    /// A satisfaction that fails with an error (such as a panicking
+    /// `Satisfier`) counts as [`Malleability::Unsatisfiable`].
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Malleability, Miniscript, SimpleSatisfier};
+    ///
+    /// let ms = Miniscript::from_str("older(144)", Context::Wsh).unwrap();
+    /// let mut satisfier = SimpleSatisfier::new();
+    /// satisfier.older_satisfied.insert(144);
+    ///
+    /// assert_eq!(ms.malleability_for(satisfier), Malleability::Malleable);
+    /// ```
     #[must_use]
-    pub fn valid_satisfactions(&self) -> bool {
-        // SAFETY: self.ptr is valid while self exists
-        unsafe { miniscript_valid_satisfactions(self.ptr) }
-    }
+    pub fn malleability_for<S: Satisfier + 'static>(&self, satisfier: S) -> Malleability {
+        let context = SatisfierContext::new(&satisfier);
+        let callbacks = context.callbacks();
+        let available = |nonmalleable| {
+            self.satisfy_with(&context, &callbacks, None, nonmalleable)
+                .is_ok_and(|result| result.availability != Availability::No)
+        };
 
-    /// Get the static ops count (for Tapscript).
-    #[must_use]
-    pub fn get_static_ops(&self) -> Option<u32> {
-        let mut ops: u32 = 0;
-        // SAFETY: self.ptr is valid while self exists
-        if unsafe { miniscript_get_static_ops(self.ptr, &raw mut ops) } {
-            Some(ops)
+        if available(true) {
+            Malleability::NonMalleable
+        } else if available(false) {
+            Malleability::Malleable
         } else {
-            None
+            Malleability::Unsatisfiable
         }
     }
 
-    /// Convert the miniscript to raw script bytes.
+    /// Check whether at least one spend path has a non-malleable satisfaction.
+    ///
+    /// Unlike [`is_non_malleable()`](Self::is_non_malleable), which is the
+    /// type system's guarantee for every satisfaction, this asks whether any
+    /// satisfaction is safe to relay. Each spend path is satisfied in
+    /// non-malleable mode with a satisfier that may have all of that path's
+    /// signatures, preimages and timelocks ([`Availability::Maybe`]). Data
+    /// for other paths counts as unavailable, as a third party could
+    /// otherwise use it to swap branches.
+    ///
+    /// Paths are only tried one at a time if satisfying with everything
+    /// available fails, so this is usually a single run. Otherwise at most
+    /// the first [`MAX_SPEND_PATHS`] paths are tried, so for miniscripts
+    /// with more paths `false` means none of those was found.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// // Valid, but a bare timelock can never be satisfied non-malleably
+    /// let ms = Miniscript::from_str("older(144)", Context::Wsh).unwrap();
+    /// assert!(!ms.has_nonmalleable_satisfaction());
+    /// ```
     #[must_use]
-    pub fn to_script_bytes(&self) -> Option<Vec<u8>> {
-        let mut script_ptr: *mut u8 = ptr::null_mut();
-        let mut script_len: usize = 0;
+    pub fn has_nonmalleable_satisfaction(&self) -> bool {
+        let satisfier = MaybeSatisfier::new(self.context);
+        let context = SatisfierContext::new(&satisfier);
+        let callbacks = context.callbacks();
+        let available = |branch| {
+            self.satisfy_with(&context, &callbacks, branch, true)
+                .is_ok_and(|result| result.availability != Availability::No)
+        };
 
-        // SAFETY: self.ptr is valid while self exists
-        if unsafe { miniscript_to_script(self.ptr, &raw mut script_ptr, &raw mut script_len) } {
-            if script_ptr.is_null() {
-                return None;
-            }
-            // SAFETY: script_ptr is valid and contains script_len bytes
-            let script = unsafe { std::slice::from_raw_parts(script_ptr, script_len) }.to_vec();
-            unsafe { miniscript_free_bytes(script_ptr) };
-            Some(script)
-        } else {
-            None
-        }
+        let paths = self
+            .spend_path_count()
+            .map_or(MAX_SPEND_PATHS, |count| count.min(MAX_SPEND_PATHS));
+        available(None) || (0..paths).any(|b| available(Some(b)))
     }
 
-    /// Convert the miniscript to a [`bitcoin::ScriptBuf`].
+    /// Get the largest witness weight over all spend paths.
     ///
-    /// This returns the script as a proper Bitcoin script type from the `bitcoin` crate.
+    /// Each path is satisfied with placeholder data for its signatures,
+    /// preimages and timelocks ([`Availability::Maybe`]), with signatures
+    /// of the largest size for the context (72 bytes for ECDSA, 65 for
+    /// Schnorr, as in [`spend_cost()`](Self::spend_cost)). The result is
+    /// the largest [`SatisfyResult::witness_weight()`] among them, which
+    /// bounds the fee of any spend.
+    ///
+    /// Unlike [`max_satisfaction_size()`](Self::max_satisfaction_size),
+    /// which Bitcoin Core computes from the type system, this builds the
+    /// witness of each path.
+    ///
+    /// Returns `None` if no path can be satisfied, or if there are more than
+    /// [`MAX_SPEND_PATHS`] paths, as a maximum over only some of them would
+    /// not bound the fee.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript};
+    ///
+    /// let ms = Miniscript::from_str("or_i(pk(A),multi(3,B,C,D,E,F))", Context::Wsh).unwrap();
+    /// let weight = ms.worst_case_branch_weight().unwrap();
+    /// ```
     #[must_use]
-    pub fn to_script(&self) -> Option<ScriptBuf> {
-        self.to_script_bytes().map(ScriptBuf::from_bytes)
+    pub fn worst_case_branch_weight(&self) -> Option<usize> {
+        let satisfier = MaybeSatisfier::new(self.context);
+        let context = SatisfierContext::new(&satisfier);
+        let callbacks = context.callbacks();
+        let paths = self
+            .spend_path_count()
+            .filter(|&count| count <= MAX_SPEND_PATHS)?;
+
+        (0..paths)
+            .filter_map(|branch| {
+                self.satisfy_with(&context, &callbacks, Some(branch), false)
+                    .ok()
+                    .filter(|result| result.availability != Availability::No)
+                    .map(|result| result.witness_weight())
+            })
+            .max()
     }
 
-    /// Parse a miniscript from raw script bytes.
+    /// Get the witness weight of a satisfaction in both satisfaction modes.
     ///
-    /// # Errors
+    /// A malleable satisfaction may pick a smaller witness than the
+    /// non-malleable one, at the cost of letting third parties change it.
     ///
-    /// Returns an error if parsing fails.
-    pub fn from_script_bytes(script: &[u8], context: Context) -> Result<Self, Error> {
-        let mut node_ptr: *mut MiniscriptNode = ptr::null_mut();
-
-        // SAFETY: We're passing valid pointers and the C code handles null checks.
-        let result = unsafe {
-            miniscript_from_script(
-                script.as_ptr(),
-                script.len(),
-                context.into(),
-                &raw mut node_ptr,
-            )
+    /// # Returns
+    ///
+    /// `(nonmalleable_weight, malleable_weight)` as computed by
+    /// [`SatisfyResult::witness_weight()`], with `None` for a mode in which
+    /// the satisfier's data does not give a complete satisfaction.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, Miniscript, SimpleSatisfier};
+    ///
+    /// let ms = Miniscript::from_str("older(144)", Context::Wsh).unwrap();
+    /// let mut satisfier = SimpleSatisfier::new();
+    /// satisfier.older_satisfied.insert(144);
+    ///
+    /// // A bare timelock has no non-malleable satisfaction
+    /// let (nonmalleable, malleable) = ms.satisfaction_weights(&satisfier);
+    /// assert_eq!(nonmalleable, None);
+    /// assert!(malleable.is_some());
+    /// ```
+    #[must_use]
+    pub fn satisfaction_weights<S: Satisfier>(
+        &self,
+        satisfier: &S,
+    ) -> (Option<usize>, Option<usize>) {
+        let context = SatisfierContext::new(satisfier);
+        let callbacks = context.callbacks();
+        let weight = |nonmalleable| {
+            self.satisfy_with(&context, &callbacks, None, nonmalleable)
+                .ok()
+                .filter(|result| result.availability == Availability::Yes)
+                .map(|result| result.witness_weight())
         };
-
-        if result.success {
-            Ok(Self {
-                ptr: node_ptr,
-                context,
-            })
-        } else {
-            let message = if result.error_message.is_null() {
-                "unknown error".to_string()
-            } else {
-                // SAFETY: error_message is a valid C string if not null
-                let msg = unsafe { CStr::from_ptr(result.error_message) }
-                    .to_string_lossy()
-                    .into_owned();
-                unsafe { miniscript_free_string(result.error_message) };
-                msg
-            };
-            Err(Error { message })
-        }
+        (weight(true), weight(false))
     }
 
-    /// Produce a witness that satisfies this miniscript.
+    /// Check whether a witness stack executes this miniscript successfully.
     ///
-    /// # Arguments
+    /// Runs Bitcoin Core's script interpreter on the stack and the script,
+    /// with the rules of the miniscript's context, and checks that it
+    /// leaves a single true element. This is the inverse of
+    /// [`satisfy()`](Self::satisfy).
     ///
-    /// * `satisfier` - An implementation of the Satisfier trait that provides
-    ///   signatures, hash preimages, and timelock information.
-    /// * `nonmalleable` - If true, only produce non-malleable satisfactions.
+    /// **This does not verify signatures or timelocks.** There is no
+    /// spending transaction to check against, so any signature of a length
+    /// a real one can have (9 to 73 bytes for ECDSA, 64 or 65 for Schnorr)
+    /// is accepted, and every timelock counts as satisfied. The check
+    /// therefore covers the witness structure only: the right elements in
+    /// the right places, with empty dissatisfactions where expected. A
+    /// witness that passes may still be rejected by the network.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A `SatisfyResult` containing the availability and witness stack.
+    /// * `stack` - The witness stack, without the witness script (and, for
+    ///   Tapscript, the control block)
     ///
     /// # Errors
     ///
-    /// Returns an error if satisfaction fails.
-    pub fn satisfy<S: Satisfier + 'static>(
-        &self,
-        satisfier: S,
-        nonmalleable: bool,
-    ) -> Result<SatisfyResult, Error> {
-        // Box the satisfier so we can pass it through FFI
-        let boxed: Box<dyn Satisfier> = Box::new(satisfier);
-        let boxed_ptr = Box::into_raw(Box::new(boxed));
+    /// Returns an error if the script cannot be built or the interpreter
+    /// cannot be run.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use miniscript_core_ffi::{Context, DummyKeyMap, Miniscript, SimpleSatisfier};
+    ///
+    /// let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    /// let mut satisfier = SimpleSatisfier::new();
+    /// let key = DummyKeyMap::new().pubkey_for("A", Context::Wsh);
+    /// satisfier.signatures.insert(key, vec![0x30; 72]);
+    ///
+    /// let result = ms.satisfy(satisfier, true).unwrap();
+    /// assert!(ms.witness_executes(&result.stack).unwrap());
+    /// ```
+    pub fn witness_executes(&self, stack: &[Vec<u8>]) -> Result<bool, Error> {
+        let elems: Vec<*const u8> = stack.iter().map(|elem| elem.as_ptr()).collect();
+        let lens: Vec<usize> = stack.iter().map(Vec::len).collect();
+        let mut valid = false;
 
-        let callbacks = SatisfierCallbacks {
-            rust_context: boxed_ptr.cast::<std::ffi::c_void>(),
-            sign_callback: Some(sign_callback),
-            check_after_callback: Some(check_after_callback),
-            check_older_callback: Some(check_older_callback),
-            sat_sha256_callback: Some(sat_sha256_callback),
-            sat_ripemd160_callback: Some(sat_ripemd160_callback),
-            sat_hash256_callback: Some(sat_hash256_callback),
-            sat_hash160_callback: Some(sat_hash160_callback),
+        // SAFETY: self.ptr is valid, elems and lens hold stack.len() entries
+        // pointing into stack, which outlives the call
+        let ran = unsafe {
+            ffi::miniscript_witness_executes(
+                self.ptr,
+                elems.as_ptr(),
+                lens.as_ptr(),
+                stack.len(),
+                &raw mut valid,
+            )
         };
+        if ran {
+            Ok(valid)
+        } else {
+            Err(Error::new("failed to run the script interpreter"))
+        }
+    }
 
+    /// Run `miniscript_satisfy` with an existing callback context, or
+    /// `miniscript_satisfy_branch` if a spend path is given.
+    fn satisfy_with(
+        &self,
+        context: &SatisfierContext<'_>,
+        callbacks: &SatisfierCallbacks,
+        branch: Option<usize>,
+        nonmalleable: bool,
+    ) -> Result<SatisfyResult, Error> {
+        let span = ffi_span!(
+            "Miniscript::satisfy",
+            context = %self.context,
+            ?branch,
+            nonmalleable
+        );
         // SAFETY: self.ptr is valid, callbacks is properly initialized
-        let mut result =
-            unsafe { miniscript_satisfy(self.ptr, &raw const callbacks, nonmalleable) };
+        let mut result = unsafe {
+            match branch {
+                Some(branch) => ffi::miniscript_satisfy_branch(
+                    self.ptr,
+                    branch,
+                    ptr::from_ref(callbacks),
+                    nonmalleable,
+                ),
+                None => miniscript_satisfy(self.ptr, ptr::from_ref(callbacks), nonmalleable),
+            }
+        };
 
-        // Clean up the boxed satisfier
-        unsafe {
-            let _ = Box::from_raw(boxed_ptr);
+        span.record(!context.panicked.get() && result.error_message.is_null());
+        if context.panicked.get() {
+            unsafe { miniscript_satisfaction_result_free(&raw mut result) };
+            return Err(Error::Satisfaction("satisfier panicked".to_string()));
         }
+        let warnings = context.warnings.take();
 
         // Check for errors
         if !result.error_message.is_null() {
@@ -1411,7 +4545,7 @@ impl Miniscript {
                 .to_string_lossy()
                 .into_owned();
             unsafe { miniscript_satisfaction_result_free(&raw mut result) };
-            return Err(Error { message: msg });
+            return Err(Error::new(msg));
         }
 
         // Convert the stack
@@ -1438,6 +4572,7 @@ impl Miniscript {
         Ok(SatisfyResult {
             availability,
             stack,
+            warnings,
         })
     }
 }
@@ -1451,6 +4586,15 @@ impl Drop for Miniscript {
     }
 }
 
+impl TryFrom<&str> for Miniscript {
+    type Error = Error;
+
+    /// Parse a miniscript in the [`Context::Wsh`] context.
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        Self::from_str(input, Context::Wsh)
+    }
+}
+
 impl fmt::Debug for Miniscript {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Miniscript")
@@ -1482,6 +4626,259 @@ pub fn version() -> &'static str {
     }
 }
 
+/// Get the version of Bitcoin Core the library was built against.
+///
+/// This is the Bitcoin Core release (e.g. `"v30.2"`) whose miniscript and
+/// descriptor code is wrapped, as opposed to the wrapper's own [`version()`].
+/// It is read from the `CLIENT_VERSION_*` settings of the compiled source, so
+/// it is accurate for vendored, `BITCOIN_CORE_SRC` and prebuilt builds too,
+/// and `"unknown"` only if they could not be read. Include it in
+/// cross-verification reports.
+#[must_use]
+pub fn bitcoin_core_version() -> &'static str {
+    // SAFETY: miniscript_bitcoin_core_version returns a static string
+    unsafe {
+        CStr::from_ptr(ffi::miniscript_bitcoin_core_version())
+            .to_str()
+            .unwrap_or("unknown")
+    }
+}
+
+/// Get the git commit of the Bitcoin Core source the library was built against.
+///
+/// Returns `None` unless the source was its own git checkout (a `.git` next
+/// to its `src` directory); an enclosing repository is never reported.
+#[must_use]
+pub fn bitcoin_core_commit() -> Option<&'static str> {
+    // SAFETY: miniscript_bitcoin_core_commit returns a static string or null
+    let ptr = unsafe { ffi::miniscript_bitcoin_core_commit() };
+    if ptr.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().ok()
+}
+
+/// Get the change in script size from applying a wrapper to a miniscript.
+///
+/// Parses `base` and the wrapped expression and returns the size of the
+/// wrapped script minus the size of the base script. `wrapper` is one or
+/// more wrapper letters, with or without the trailing colon (`"v:"`, `"sv"`);
+/// if `base` already has wrappers the letters are merged, so `"s:"` over
+/// `"v:pk(A)"` gives `sv:pk(A)`.
+///
+/// Some wrappers are free: `v:` over an expression ending in `OP_CHECKSIG`
+/// turns it into `OP_CHECKSIGVERIFY` rather than adding an `OP_VERIFY`.
+///
+/// # Errors
+///
+/// Returns an error if either expression fails to parse.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use miniscript_core_ffi::{Context, wrapper_size_delta};
+///
+/// // OP_SWAP
+/// assert_eq!(wrapper_size_delta("pk(A)", "s:", Context::Wsh).unwrap(), 1);
+/// ```
+pub fn wrapper_size_delta(base: &str, wrapper: &str, context: Context) -> Result<i64, Error> {
+    let letters = wrapper.strip_suffix(':').unwrap_or(wrapper);
+    let base_has_wrappers = base
+        .find(':')
+        .is_some_and(|colon| base.find('(').is_none_or(|paren| colon < paren));
+    let wrapped = if base_has_wrappers {
+        format!("{letters}{base}")
+    } else {
+        format!("{letters}:{base}")
+    };
+
+    let size = |input: &str| {
+        let ms = Miniscript::from_str(input, context)?;
+        let size = ms
+            .get_script_size()
+            .ok_or_else(|| Error::new("failed to compute script size"))?;
+        i64::try_from(size).map_err(|_| Error::new("script size out of range"))
+    };
+    Ok(size(&wrapped)? - size(base)?)
+}
+
+/// The first opcode in `script` that only exists in the other context:
+/// `OP_CHECKSIGADD` is Tapscript only, while `OP_CHECKMULTISIG(VERIFY)` is
+/// disabled in Tapscript. Scanning stops at a malformed push, which is left
+/// for the parser to report.
+fn foreign_opcode(script: &[u8], context: Context) -> Option<bitcoin::opcodes::Opcode> {
+    use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKMULTISIGVERIFY, OP_CHECKSIGADD};
+    use bitcoin::script::{Instruction, Script};
+
+    Script::from_bytes(script)
+        .instructions()
+        .map_while(Result::ok)
+        .find_map(|instruction| match (instruction, context) {
+            (Instruction::Op(op), Context::Wsh) if op == OP_CHECKSIGADD => Some(op),
+            (Instruction::Op(op), Context::Tapscript)
+                if op == OP_CHECKMULTISIG || op == OP_CHECKMULTISIGVERIFY =>
+            {
+                Some(op)
+            }
+            _ => None,
+        })
+}
+
+/// A lower bound on the depth of the miniscript written in `input`, read
+/// from the text: each parenthesized expression and each wrapper letter is
+/// one level. Fragments without arguments are not counted.
+fn text_depth(input: &str) -> usize {
+    // Depth of the expression each open parenthesis belongs to
+    let mut enclosing = Vec::new();
+    let mut wrappers = 0;
+    let mut letters = 0;
+    let mut max = 0;
+    for b in input.bytes() {
+        match b {
+            b':' => {
+                wrappers += letters;
+                letters = 0;
+            }
+            b'(' => {
+                let depth = enclosing.last().copied().unwrap_or(0) + wrappers + 1;
+                max = max.max(depth);
+                enclosing.push(depth);
+                wrappers = 0;
+                letters = 0;
+            }
+            b')' | b',' => {
+                if b == b')' {
+                    enclosing.pop();
+                }
+                wrappers = 0;
+                letters = 0;
+            }
+            _ => letters += 1,
+        }
+    }
+    max
+}
+
+/// Find the first byte that cannot appear in a miniscript or descriptor.
+pub(crate) fn invalid_input_position(input: &str) -> Option<usize> {
+    input.bytes().position(|b| b == 0 || !b.is_ascii())
+}
+
+/// Outcome of [`fuzz_roundtrip()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundtripOutcome {
+    /// The input parsed as a miniscript.
+    Accepted {
+        /// Whether every round trip reproduced the original script.
+        ///
+        /// `false` indicates a bug in the wrapper or in Bitcoin Core.
+        stable: bool,
+    },
+    /// The input did not parse.
+    Rejected,
+}
+
+/// Check the parse/serialize round-trip invariant on arbitrary input.
+///
+/// This is the oracle for a fuzz target: any input is accepted, and the
+/// function never panics on malformed input. If `input` parses, it must
+/// serialize to a string that parses back to the same script and the same
+/// string. If it is also valid at the top level, decoding its script must
+/// reproduce the script too.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use miniscript_core_ffi::{Context, RoundtripOutcome, fuzz_roundtrip};
+///
+/// // fuzz_target!(|data: &str| {
+/// if let RoundtripOutcome::Accepted { stable } = fuzz_roundtrip("pk(A)", Context::Wsh) {
+///     assert!(stable);
+/// }
+/// // });
+/// ```
+#[must_use]
+pub fn fuzz_roundtrip(input: &str, context: Context) -> RoundtripOutcome {
+    let Ok(ms) = Miniscript::from_str(input, context) else {
+        return RoundtripOutcome::Rejected;
+    };
+
+    let stable = (|| {
+        let script = ms.to_script_bytes()?;
+        let string = ms.to_string()?;
+
+        let reparsed = Miniscript::from_str(&string, context).ok()?;
+        if !reparsed.matches_script(&script) || reparsed.to_string()? != string {
+            return Some(false);
+        }
+
+        if ms.is_valid_top_level() {
+            let decoded = Miniscript::from_script_bytes(&script, context).ok()?;
+            if !decoded.matches_script(&script) {
+                return Some(false);
+            }
+        }
+        Some(true)
+    })()
+    .unwrap_or(false);
+
+    RoundtripOutcome::Accepted { stable }
+}
+
+/// Result of checking one line of test vectors with [`verify_lines()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LineResult {
+    /// The line as given.
+    pub input: String,
+    /// Whether the line parsed as a miniscript.
+    pub parsed: bool,
+    /// Whether the miniscript is valid at the top level.
+    pub valid: bool,
+    /// Whether the miniscript is sane.
+    pub sane: bool,
+    /// The canonical string form, if the line parsed.
+    pub canonical: Option<String>,
+}
+
+/// Parse each line of a test vector file and report the result.
+///
+/// Surrounding whitespace is ignored when parsing; every line yields exactly
+/// one result, so results can be matched against expectations by position.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use miniscript_core_ffi::{Context, verify_lines};
+///
+/// let vectors = "pk(A)\nand_v(pk(A),pk(B))";
+/// for result in verify_lines(vectors.lines(), Context::Wsh) {
+///     println!("{}: parsed={} valid={}", result.input, result.parsed, result.valid);
+/// }
+/// ```
+pub fn verify_lines<'a>(
+    lines: impl Iterator<Item = &'a str>,
+    context: Context,
+) -> impl Iterator<Item = LineResult> {
+    lines.map(
+        move |line| match Miniscript::from_str(line.trim(), context) {
+            Ok(ms) => LineResult {
+                input: line.to_string(),
+                parsed: true,
+                valid: ms.is_valid_top_level(),
+                sane: ms.is_sane(),
+                canonical: ms.to_string(),
+            },
+            Err(_) => LineResult {
+                input: line.to_string(),
+                parsed: false,
+                valid: false,
+                sane: false,
+                canonical: None,
+            },
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1492,6 +4889,19 @@ mod tests {
         assert!(!v.is_empty());
     }
 
+    #[test]
+    fn test_bitcoin_core_version() {
+        // Read from the compiled source, e.g. "v30.2" or "v30.3rc1"
+        let version = bitcoin_core_version();
+        let release = version.strip_prefix('v').expect("release version");
+        let (major, rest) = release.split_once('.').expect("major.minor");
+        assert!(major.parse::<u32>().unwrap() >= 30);
+        assert!(rest.starts_with(|c: char| c.is_ascii_digit()));
+        if let Some(commit) = bitcoin_core_commit() {
+            assert!(commit.chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
+
     #[test]
     fn test_parse_simple() {
         let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
@@ -1518,6 +4928,25 @@ mod tests {
         assert!(type_str.contains('B'));
     }
 
+    #[test]
+    fn test_display() {
+        assert_eq!(Availability::Yes.to_string(), "yes");
+        assert_eq!(Availability::No.to_string(), "no");
+        assert_eq!(Availability::Maybe.to_string(), "maybe");
+        assert_eq!(Context::Wsh.to_string(), "wsh");
+        assert_eq!(Context::Tapscript.to_string(), "tapscript");
+    }
+
+    #[test]
+    fn test_context_from_str() {
+        assert_eq!("wsh".parse::<Context>().unwrap(), Context::Wsh);
+        assert_eq!("WSH".parse::<Context>().unwrap(), Context::Wsh);
+        assert_eq!("Tapscript".parse::<Context>().unwrap(), Context::Tapscript);
+
+        let err = "p2sh".parse::<Context>().unwrap_err();
+        assert!(err.to_string().contains("p2sh"));
+    }
+
     #[test]
     fn test_simple_satisfier() {
         let satisfier = SimpleSatisfier::new();
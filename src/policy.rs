@@ -0,0 +1,306 @@
+//! Spending policies lifted from miniscript.
+//!
+//! A [`Policy`] describes *what* unlocks an output (keys, timelocks, hash
+//! preimages and how they combine) without the script-level details of
+//! *how* the miniscript encodes it. Use [`Miniscript::lift()`](crate::Miniscript::lift)
+//! to obtain one.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use miniscript_core_ffi::{Context, Miniscript, Policy};
+//!
+//! let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(100)))", Context::Wsh).unwrap();
+//! let policy = ms.lift().unwrap();
+//!
+//! assert_eq!(
+//!     policy,
+//!     Policy::Or(vec![
+//!         Policy::Key(b"A".to_vec()),
+//!         Policy::And(vec![Policy::Key(b"B".to_vec()), Policy::After(100)]),
+//!     ])
+//! );
+//! ```
+
+use crate::fragment::Fragment;
+use bitcoin::hex::DisplayHex;
+use std::fmt;
+
+/// A spending policy.
+///
+/// Keys are reported as the raw bytes of the key as written in the
+/// miniscript expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Can never be satisfied (`0`).
+    Unsatisfiable,
+    /// Always satisfied (`1`).
+    Trivial,
+    /// A signature for the key is required.
+    Key(Vec<u8>),
+    /// An absolute timelock (`after(n)`).
+    After(u32),
+    /// A relative timelock (`older(n)`).
+    Older(u32),
+    /// A SHA256 preimage is required.
+    Sha256([u8; 32]),
+    /// A double-SHA256 preimage is required.
+    Hash256([u8; 32]),
+    /// A RIPEMD160 preimage is required.
+    Ripemd160([u8; 20]),
+    /// A HASH160 preimage is required.
+    Hash160([u8; 20]),
+    /// All sub-policies must be satisfied.
+    And(Vec<Policy>),
+    /// Any one sub-policy must be satisfied.
+    Or(Vec<Policy>),
+    /// At least `k` of the sub-policies must be satisfied.
+    Thresh(usize, Vec<Policy>),
+}
+
+/// Formats the policy in rust-miniscript's concrete policy syntax, e.g.
+/// `or(pk(A),and(pk(B),after(100)))`.
+///
+/// `and` and `or` take exactly two arguments in that syntax, so wider
+/// conjunctions and disjunctions are written as `thresh(n,...)` and
+/// `thresh(1,...)`. Keys are written as in the miniscript expression.
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write_subs = |f: &mut fmt::Formatter<'_>, subs: &[Self]| -> fmt::Result {
+            for (i, sub) in subs.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{sub}")?;
+            }
+            Ok(())
+        };
+
+        match self {
+            Self::Unsatisfiable => f.write_str("UNSATISFIABLE"),
+            Self::Trivial => f.write_str("TRIVIAL"),
+            Self::Key(key) => write!(f, "pk({})", String::from_utf8_lossy(key)),
+            Self::After(n) => write!(f, "after({n})"),
+            Self::Older(n) => write!(f, "older({n})"),
+            Self::Sha256(h) | Self::Hash256(h) => {
+                let name = if matches!(self, Self::Sha256(_)) {
+                    "sha256"
+                } else {
+                    "hash256"
+                };
+                write!(f, "{name}({})", h.as_hex())
+            }
+            Self::Ripemd160(h) | Self::Hash160(h) => {
+                let name = if matches!(self, Self::Ripemd160(_)) {
+                    "ripemd160"
+                } else {
+                    "hash160"
+                };
+                write!(f, "{name}({})", h.as_hex())
+            }
+            Self::And(subs) | Self::Or(subs) if subs.len() == 1 => write!(f, "{}", subs[0]),
+            Self::And(subs) | Self::Or(subs) if subs.len() == 2 => {
+                f.write_str(if matches!(self, Self::And(_)) {
+                    "and("
+                } else {
+                    "or("
+                })?;
+                write_subs(f, subs)?;
+                f.write_str(")")
+            }
+            Self::And(subs) => {
+                write!(f, "thresh({},", subs.len())?;
+                write_subs(f, subs)?;
+                f.write_str(")")
+            }
+            Self::Or(subs) => {
+                f.write_str("thresh(1,")?;
+                write_subs(f, subs)?;
+                f.write_str(")")
+            }
+            Self::Thresh(k, subs) => {
+                write!(f, "thresh({k},")?;
+                write_subs(f, subs)?;
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// A common wallet template recognized by
+/// [`Miniscript::classify()`](crate::Miniscript::classify).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Template {
+    /// A single key.
+    SingleSig,
+    /// `k` of `n` keys, with `n` at least 2.
+    Multisig {
+        /// Number of signatures required.
+        k: usize,
+        /// Number of keys.
+        n: usize,
+    },
+    /// A multisig spendable without delay, plus timelocked paths for
+    /// recovery keys.
+    TimelockRecovery,
+    /// A single owner key spendable without delay, plus timelocked paths
+    /// for heir keys.
+    Inheritance,
+    /// Any other policy.
+    Other,
+}
+
+/// Match a policy against the curated [`Template`]s.
+pub(crate) fn classify(policy: &Policy) -> Template {
+    if let Some((k, n)) = key_threshold(policy) {
+        return if n == 1 {
+            Template::SingleSig
+        } else {
+            Template::Multisig { k, n }
+        };
+    }
+
+    // One immediate key path plus one or more timelocked key paths
+    let mut branches = Vec::new();
+    flatten_or(policy, &mut branches);
+    let (immediate, delayed): (Vec<_>, Vec<_>) = branches
+        .into_iter()
+        .partition(|b| key_threshold(b).is_some());
+    if immediate.len() != 1 || delayed.is_empty() || !delayed.iter().all(|b| is_timelocked_keys(b))
+    {
+        return Template::Other;
+    }
+    match key_threshold(immediate[0]) {
+        Some((_, 1)) => Template::Inheritance,
+        _ => Template::TimelockRecovery,
+    }
+}
+
+/// Get `(k, n)` if the policy only requires `k` of `n` keys.
+fn key_threshold(policy: &Policy) -> Option<(usize, usize)> {
+    let all_keys = |subs: &[Policy]| subs.iter().all(|p| matches!(p, Policy::Key(_)));
+    match policy {
+        Policy::Key(_) => Some((1, 1)),
+        Policy::Thresh(k, subs) if all_keys(subs) && *k >= 1 && *k <= subs.len() => {
+            Some((*k, subs.len()))
+        }
+        Policy::And(subs) if all_keys(subs) => Some((subs.len(), subs.len())),
+        Policy::Or(subs) if all_keys(subs) => Some((1, subs.len())),
+        _ => None,
+    }
+}
+
+/// Collect the alternatives of nested disjunctions.
+fn flatten_or<'a>(policy: &'a Policy, out: &mut Vec<&'a Policy>) {
+    match policy {
+        Policy::Or(subs) => subs.iter().for_each(|p| flatten_or(p, out)),
+        Policy::Thresh(1, subs) => subs.iter().for_each(|p| flatten_or(p, out)),
+        _ => out.push(policy),
+    }
+}
+
+/// Check whether the policy is keys gated behind at least one timelock.
+fn is_timelocked_keys(policy: &Policy) -> bool {
+    let Policy::And(subs) = policy else {
+        return false;
+    };
+    let (locks, rest): (Vec<_>, Vec<_>) = subs
+        .iter()
+        .partition(|p| matches!(p, Policy::After(_) | Policy::Older(_)));
+    if locks.is_empty() || rest.is_empty() {
+        return false;
+    }
+    match rest.as_slice() {
+        [single] => key_threshold(single).is_some(),
+        _ => rest.iter().all(|p| matches!(p, Policy::Key(_))),
+    }
+}
+
+/// Lift a pre-order fragment sequence into a policy.
+///
+/// Returns `None` if the sequence does not describe a complete tree.
+pub(crate) fn lift(fragments: &[Fragment]) -> Option<Policy> {
+    // Visiting a pre-order sequence backwards reaches every fragment after
+    // its children, whose policies are then on top of the stack, first
+    // child first
+    let mut policies: Vec<Policy> = Vec::new();
+    for fragment in fragments.iter().rev() {
+        let n = fragment.child_count();
+        if policies.len() < n {
+            return None;
+        }
+        let subs: Vec<Policy> = policies.drain(policies.len() - n..).rev().collect();
+        policies.push(lift_fragment(fragment, subs)?);
+    }
+    let policy = policies.pop()?;
+    if !policies.is_empty() {
+        return None;
+    }
+    Some(policy)
+}
+
+/// Lift a fragment given the policies of its subs.
+fn lift_fragment(fragment: &Fragment, mut subs: Vec<Policy>) -> Option<Policy> {
+    let policy = match fragment {
+        Fragment::Just0 => Policy::Unsatisfiable,
+        Fragment::Just1 => Policy::Trivial,
+        Fragment::PkK(key) | Fragment::PkH(key) => Policy::Key(key.clone()),
+        Fragment::After(n) => Policy::After(*n),
+        Fragment::Older(n) => Policy::Older(*n),
+        Fragment::Sha256(h) => Policy::Sha256(h.as_slice().try_into().ok()?),
+        Fragment::Hash256(h) => Policy::Hash256(h.as_slice().try_into().ok()?),
+        Fragment::Ripemd160(h) => Policy::Ripemd160(h.as_slice().try_into().ok()?),
+        Fragment::Hash160(h) => Policy::Hash160(h.as_slice().try_into().ok()?),
+        Fragment::WrapA
+        | Fragment::WrapS
+        | Fragment::WrapC
+        | Fragment::WrapD
+        | Fragment::WrapV
+        | Fragment::WrapJ
+        | Fragment::WrapN => subs.pop()?,
+        Fragment::AndV | Fragment::AndB => and(subs),
+        Fragment::OrB | Fragment::OrC | Fragment::OrD | Fragment::OrI => or(subs),
+        Fragment::AndOr => {
+            let z = subs.pop()?;
+            or(vec![and(subs), z])
+        }
+        Fragment::Thresh { k, .. } => thresh(*k as usize, subs),
+        Fragment::Multi { k, keys } | Fragment::MultiA { k, keys } => {
+            Policy::Thresh(*k as usize, keys.iter().cloned().map(Policy::Key).collect())
+        }
+    };
+
+    Some(policy)
+}
+
+/// A conjunction without its trivial sub-policies (as in `t:X`, which is
+/// `and_v(X,1)`).
+fn and(mut subs: Vec<Policy>) -> Policy {
+    subs.retain(|p| *p != Policy::Trivial);
+    match subs.len() {
+        0 => Policy::Trivial,
+        1 => subs.remove(0),
+        _ => Policy::And(subs),
+    }
+}
+
+/// A disjunction without its unsatisfiable sub-policies (as in `l:X` and
+/// `u:X`, which are `or_i(0,X)` and `or_i(X,0)`).
+fn or(mut subs: Vec<Policy>) -> Policy {
+    subs.retain(|p| *p != Policy::Unsatisfiable);
+    match subs.len() {
+        0 => Policy::Unsatisfiable,
+        1 => subs.remove(0),
+        _ => Policy::Or(subs),
+    }
+}
+
+/// A threshold without its unsatisfiable sub-policies.
+fn thresh(k: usize, mut subs: Vec<Policy>) -> Policy {
+    subs.retain(|p| *p != Policy::Unsatisfiable);
+    match subs.len() {
+        n if k > n => Policy::Unsatisfiable,
+        1 => subs.remove(0),
+        _ => Policy::Thresh(k, subs),
+    }
+}
@@ -0,0 +1,48 @@
+//! Spans around FFI calls, for the `tracing` feature.
+//!
+//! [`ffi_span!`] opens a `debug` level span named after the public method,
+//! with the given fields and an empty `success` field that is filled in with
+//! [`FfiSpan::record()`] once the C call returns. Without the feature both
+//! compile to nothing and the field expressions are not evaluated.
+
+/// A span entered for the duration of an FFI call.
+#[cfg(feature = "tracing")]
+pub(crate) struct FfiSpan(pub(crate) tracing::span::EnteredSpan);
+
+/// A span entered for the duration of an FFI call.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct FfiSpan;
+
+impl FfiSpan {
+    /// Record whether the C call succeeded.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn record(&self, success: bool) {
+        self.0.record("success", success);
+    }
+
+    /// Record whether the C call succeeded.
+    #[cfg(not(feature = "tracing"))]
+    #[allow(clippy::unused_self)]
+    pub(crate) const fn record(&self, _success: bool) {}
+}
+
+/// Open an [`FfiSpan`], e.g. `ffi_span!("Descriptor::expand", index)`.
+#[cfg(feature = "tracing")]
+macro_rules! ffi_span {
+    ($name:literal $(, $($fields:tt)+)?) => {
+        $crate::trace::FfiSpan(
+            tracing::debug_span!($name, $($($fields)+,)? success = tracing::field::Empty)
+                .entered(),
+        )
+    };
+}
+
+/// Open an [`FfiSpan`], e.g. `ffi_span!("Descriptor::expand", index)`.
+#[cfg(not(feature = "tracing"))]
+macro_rules! ffi_span {
+    ($name:literal $(, $($fields:tt)+)?) => {
+        $crate::trace::FfiSpan
+    };
+}
+
+pub(crate) use ffi_span;
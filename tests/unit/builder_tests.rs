@@ -0,0 +1,78 @@
+//! Tests for building miniscripts with `MiniscriptBuilder`
+
+use miniscript_core_ffi::{Context, Miniscript, MiniscriptBuilder as B};
+
+/// Check that a builder produces the expected text and the same miniscript as
+/// parsing that text directly
+fn check_built(builder: &B, expected: &str, context: Context) {
+    assert_eq!(builder.as_str(), expected);
+
+    let built = builder
+        .build(context)
+        .expect("built miniscript should parse");
+    let parsed = Miniscript::from_str(expected, context).expect("should parse");
+    assert_eq!(built.to_string(), parsed.to_string(), "{expected}");
+    assert_eq!(
+        built.to_script_bytes(),
+        parsed.to_script_bytes(),
+        "{expected}"
+    );
+}
+
+#[test]
+fn test_builder_and_v_older() {
+    let builder = B::and_v(B::v(B::pk("A")), B::older(100));
+    check_built(&builder, "and_v(v:pk(A),older(100))", Context::Wsh);
+}
+
+#[test]
+fn test_builder_common_fragments() {
+    check_built(
+        &B::or_i(B::pkh("A"), B::and_v(B::v(B::pk("B")), B::after(500_000))),
+        "or_i(pkh(A),and_v(v:pk(B),after(500000)))",
+        Context::Wsh,
+    );
+    check_built(
+        &B::thresh(2, &[B::pk("A"), B::s(B::pk("B")), B::s(B::pk("C"))]),
+        "thresh(2,pk(A),s:pk(B),s:pk(C))",
+        Context::Wsh,
+    );
+    check_built(
+        &B::multi(2, &["A", "B", "C"]),
+        "multi(2,A,B,C)",
+        Context::Wsh,
+    );
+    check_built(
+        &B::multi_a(1, &["A", "B"]),
+        "multi_a(1,A,B)",
+        Context::Tapscript,
+    );
+    check_built(
+        &B::or_d(B::pk("A"), B::andor(B::pk("B"), B::pk("C"), B::pk("D"))),
+        "or_d(pk(A),andor(pk(B),pk(C),pk(D)))",
+        Context::Wsh,
+    );
+}
+
+#[test]
+fn test_builder_merges_wrappers() {
+    assert_eq!(B::v(B::s(B::pk("A"))).as_str(), "vs:pk(A)");
+    assert_eq!(B::a(B::older(10)).to_string(), "a:older(10)");
+    check_built(
+        &B::and_b(B::pk("A"), B::s(B::pk("B"))),
+        "and_b(pk(A),s:pk(B))",
+        Context::Wsh,
+    );
+}
+
+#[test]
+fn test_builder_invalid_combination() {
+    // and_v needs a V first argument
+    assert!(
+        B::and_v(B::pk("A"), B::pk("B"))
+            .build(Context::Wsh)
+            .is_err()
+    );
+    // multi is not available in Tapscript
+    assert!(B::multi(1, &["A"]).build(Context::Tapscript).is_err());
+}
@@ -4,7 +4,7 @@
 //! and produce expected properties.
 
 use super::common::init_testdata;
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, HashKind, HashLock, KeyUsage, Miniscript, PkKind};
 
 #[test]
 fn test_complex_miniscripts_part1() {
@@ -355,3 +355,200 @@ fn test_additional_timelock_tests() {
         "thresh k=1 with timelocks should be valid"
     );
 }
+
+#[test]
+fn test_key_usages() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),pkh(B))", Context::Wsh).expect("should parse");
+
+    assert_eq!(
+        ms.key_usages(),
+        vec![
+            KeyUsage {
+                key: b"A".to_vec(),
+                kind: PkKind::Pk,
+            },
+            KeyUsage {
+                key: b"B".to_vec(),
+                kind: PkKind::PkH,
+            },
+        ]
+    );
+
+    let ms = Miniscript::from_str("multi(2,A,B,C)", Context::Wsh).expect("should parse");
+    let usages = ms.key_usages();
+    assert_eq!(usages.len(), 3);
+    assert!(usages.iter().all(|u| u.kind == PkKind::Pk));
+}
+
+#[test]
+fn test_spend_path_count() {
+    let cases = [
+        ("or_i(pk(A),pk(B))", 2),
+        ("and_v(v:pk(A),pk(B))", 1),
+        ("andor(pk(A),pk(B),pk(C))", 2),
+        ("multi(2,A,B,C)", 3),
+        ("thresh(2,pk(A),s:pk(B),s:pk(C))", 3),
+        ("or_d(pk(A),or_i(pk(B),pk(C)))", 3),
+    ];
+
+    for (input, expected) in cases {
+        let ms = Miniscript::from_str(input, Context::Wsh).expect("should parse");
+        assert_eq!(ms.spend_path_count(), Some(expected), "{input}");
+    }
+}
+
+#[test]
+fn test_branch_probabilities() {
+    let ms = Miniscript::from_str("or_i(pk(A),pk(B))", Context::Wsh).expect("should parse");
+    let branches = ms.branch_probabilities();
+    assert_eq!(branches.len(), 2);
+    assert_eq!(branches[0].0, "pk(A)");
+    assert_eq!(branches[1].0, "pk(B)");
+    let total: f64 = branches.iter().map(|(_, p)| p).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+
+    let ms = Miniscript::from_str("andor(pk(A),pk(B),or_i(pk(C),pk(D)))", Context::Wsh)
+        .expect("should parse");
+    let branches = ms.branch_probabilities();
+    let labels: Vec<&str> = branches.iter().map(|(s, _)| s.as_str()).collect();
+    assert_eq!(labels, ["pk(A)", "or_i(pk(C),pk(D))", "pk(C)", "pk(D)"]);
+    assert_eq!(branches[3].1, 0.25);
+
+    // Each of n subs of thresh(k, ...) is used with probability k/n
+    let ms = Miniscript::from_str("thresh(2,pk(A),s:pk(B),s:pk(C),s:pk(D))", Context::Wsh)
+        .expect("should parse");
+    assert!(ms.branch_probabilities().iter().all(|(_, p)| *p == 0.5));
+
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+    assert!(ms.branch_probabilities().is_empty());
+}
+
+#[test]
+fn test_hash_locks() {
+    let sha = "01".repeat(32);
+    let h160 = "02".repeat(20);
+    let ms = Miniscript::from_str(
+        &format!("and_b(sha256({sha}),a:hash160({h160}))"),
+        Context::Wsh,
+    )
+    .expect("should parse");
+
+    assert_eq!(
+        ms.hash_locks(),
+        vec![
+            HashLock {
+                kind: HashKind::Sha256,
+                digest: vec![0x01; 32],
+            },
+            HashLock {
+                kind: HashKind::Hash160,
+                digest: vec![0x02; 20],
+            },
+        ]
+    );
+    for lock in ms.hash_locks() {
+        assert_eq!(lock.digest.len(), lock.kind.digest_len());
+    }
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+    assert!(ms.hash_locks().is_empty());
+}
+
+#[test]
+fn test_template() {
+    init_testdata();
+    let testdata = super::common::get_testdata();
+    let key = |i: usize| hex::encode(testdata.pubkeys[i].to_bytes());
+
+    let first = Miniscript::from_str(
+        &format!("multi(2,{},{},{})", key(0), key(1), key(2)),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    let second = Miniscript::from_str(
+        &format!("multi(2,{},{},{})", key(3), key(4), key(5)),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    assert_eq!(first.template().as_deref(), Some("multi(2,A,B,C)"));
+    assert_eq!(first.template(), second.template());
+
+    // Repeated keys share a placeholder; timelocks and hashes are kept
+    let sha = "01".repeat(32);
+    let ms = Miniscript::from_str(
+        &format!(
+            "or_d(pk({k0}),and_v(v:pk({k1}),and_v(v:sha256({sha}),or_d(pk({k0}),older(144)))))",
+            k0 = key(0),
+            k1 = key(1),
+        ),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    assert_eq!(
+        ms.template().as_deref(),
+        Some(
+            format!("or_d(pk(A),and_v(v:pk(B),and_v(v:sha256({sha}),or_d(pk(A),older(144)))))")
+                .as_str()
+        )
+    );
+
+    // A different structure gives a different template
+    let other = Miniscript::from_str(
+        &format!("multi(1,{},{},{})", key(0), key(1), key(2)),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    assert_ne!(first.template(), other.template());
+}
+
+#[test]
+fn test_needs_preimage() {
+    let h = "01".repeat(32);
+
+    let ms = Miniscript::from_str(&format!("sha256({h})"), Context::Wsh).expect("should parse");
+    assert!(ms.needs_preimage());
+    assert_eq!(ms.hash_locks().len(), 1);
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+    assert!(!ms.needs_preimage());
+
+    // HTLC: the preimage is only needed on one branch
+    let ms = Miniscript::from_str(
+        &format!("andor(pk(A),sha256({h}),and_v(v:pk(B),older(144)))"),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    assert!(ms.needs_preimage());
+
+    // A hash lock on an unsatisfiable path is never revealed
+    let ms = Miniscript::from_str(&format!("or_i(pk(A),and_v(v:sha256({h}),0))"), Context::Wsh)
+        .expect("should parse");
+    assert!(!ms.needs_preimage());
+    assert_eq!(ms.hash_locks().len(), 1);
+}
+
+#[test]
+fn test_subexpressions() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+    let subs = ms.subexpressions();
+    let strings: Vec<String> = subs.iter().filter_map(Miniscript::to_string).collect();
+    assert_eq!(
+        strings,
+        vec![
+            "and_v(v:pk(A),pk(B))",
+            "v:pk(A)",
+            "pk(A)",
+            "pk_k(A)",
+            "pk(B)",
+            "pk_k(B)"
+        ]
+    );
+    for sub in &subs {
+        assert!(sub.is_valid());
+        assert_eq!(sub.context(), Context::Wsh);
+    }
+    assert!(subs[0].is_valid_top_level());
+    assert!(!subs[1].is_valid_top_level());
+}
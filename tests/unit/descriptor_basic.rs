@@ -352,6 +352,68 @@ fn test_get_address_tr() {
     }
 }
 
+#[test]
+fn test_taproot_output_key() {
+    use bitcoin::XOnlyPublicKey;
+    use bitcoin::hashes::Hash;
+    use bitcoin::opcodes::all::OP_CHECKSIG;
+    use bitcoin::script::Builder;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::taproot::{TaprootBuilder, TaprootSpendInfo};
+    use std::str::FromStr;
+
+    let internal_hex = "a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+    let leaf_hex = "669b8afcec803a0d323e9a17f3ea8e68e8abe5a278020a929adbec52421adbd0";
+    let secp = Secp256k1::verification_only();
+    let internal = XOnlyPublicKey::from_str(internal_hex).unwrap();
+
+    let desc = check_parse_success(&format!("tr({internal_hex})"));
+
+    let script = desc.expand(0).expect("Failed to expand");
+    assert_eq!(script.len(), 34);
+    assert_eq!(&script[..2], &[0x51, 0x20], "Expected OP_1 <32 bytes>");
+
+    // Key-path only: the internal key tweaked with an empty tree
+    let expected = TaprootSpendInfo::new_key_spend(&secp, internal, None);
+    let output_key = desc
+        .taproot_output_key(0)
+        .expect("Failed to get output key");
+    assert_eq!(
+        output_key,
+        expected.output_key().to_x_only_public_key().serialize()
+    );
+    assert_eq!(output_key[..], script[2..], "Output key mismatch");
+    assert!(desc.taproot_merkle_root(0).is_none());
+
+    // With a single pk() leaf, the root is its leaf hash
+    let desc = check_parse_success(&format!("tr({internal_hex},pk({leaf_hex}))"));
+    let leaf = XOnlyPublicKey::from_str(leaf_hex).unwrap();
+    let leaf_script = Builder::new()
+        .push_slice(leaf.serialize())
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    let expected = TaprootBuilder::new()
+        .add_leaf(0, leaf_script)
+        .unwrap()
+        .finalize(&secp, internal)
+        .unwrap();
+    assert_eq!(
+        desc.taproot_merkle_root(0),
+        Some(expected.merkle_root().unwrap().to_byte_array())
+    );
+    assert_eq!(
+        desc.taproot_output_key(0),
+        Some(expected.output_key().to_x_only_public_key().serialize())
+    );
+
+    // Non-Taproot descriptors have no output key
+    let desc = check_parse_success(
+        "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+    );
+    assert!(desc.taproot_output_key(0).is_none());
+    assert!(desc.taproot_merkle_root(0).is_none());
+}
+
 #[test]
 fn test_script_size() {
     let desc = check_parse_success(
@@ -380,6 +442,38 @@ fn test_max_satisfaction_weight() {
     }
 }
 
+#[test]
+fn test_max_weight_to_satisfy() {
+    let desc = check_parse_success(
+        "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+    );
+
+    // Item count, then a length-prefixed low-R signature and key
+    let weight = desc.max_weight_to_satisfy().expect("Failed to get weight");
+    assert_eq!(weight, 1 + 1 + 71 + 1 + 33);
+    assert_eq!(desc.max_satisfaction_weight(false), Some(weight - 1));
+
+    // Nesting in P2SH adds a scriptSig but leaves the witness alone
+    let nested = check_parse_success(
+        "sh(wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd))",
+    );
+    assert_eq!(nested.max_weight_to_satisfy(), Some(weight));
+    assert!(nested.max_satisfaction_weight(false).unwrap() > weight);
+
+    // Likewise for a nested script
+    let script = "wsh(multi(1,03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd))";
+    let wsh = check_parse_success(script);
+    let nested = check_parse_success(&format!("sh({script})"));
+    assert!(wsh.max_weight_to_satisfy().is_some());
+    assert_eq!(nested.max_weight_to_satisfy(), wsh.max_weight_to_satisfy());
+
+    // Legacy outputs have no witness
+    let legacy = check_parse_success(
+        "pkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+    );
+    assert_eq!(legacy.max_weight_to_satisfy(), None);
+}
+
 #[test]
 fn test_get_pubkeys() {
     let desc = check_parse_success(
@@ -399,6 +493,44 @@ fn test_get_pubkeys() {
     }
 }
 
+#[test]
+fn test_sortedmulti_key_order() {
+    let key_a = "02669b8afcec803a0d323e9a17f3ea8e68e8abe5a278020a929adbec52421adbd0";
+    let key_b = "03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+
+    let desc_ba = check_parse_success(&format!("wsh(sortedmulti(2,{key_b},{key_a}))"));
+    let desc_ab = check_parse_success(&format!("wsh(sortedmulti(2,{key_a},{key_b}))"));
+
+    assert_eq!(
+        desc_ba.expand(0),
+        desc_ab.expand(0),
+        "sortedmulti scripts should not depend on input key order"
+    );
+
+    let sorted_ba = desc_ba
+        .sorted_pubkeys(0)
+        .expect("Failed to get sorted pubkeys");
+    let sorted_ab = desc_ab
+        .sorted_pubkeys(0)
+        .expect("Failed to get sorted pubkeys");
+    assert_eq!(sorted_ba, sorted_ab);
+    assert_eq!(
+        sorted_ab,
+        vec![hex::decode(key_a).unwrap(), hex::decode(key_b).unwrap()]
+    );
+
+    // multi() keeps the input order
+    let multi = check_parse_success(&format!("wsh(multi(2,{key_b},{key_a}))"));
+    assert_eq!(
+        multi.sorted_pubkeys(0).unwrap(),
+        vec![hex::decode(key_b).unwrap(), hex::decode(key_a).unwrap()]
+    );
+
+    // Not a multisig descriptor
+    let single = check_parse_success(&format!("wpkh({key_a})"));
+    assert!(single.sorted_pubkeys(0).is_none());
+}
+
 #[test]
 fn test_pkh_with_key_origin() {
     // pkh with key origin info [fingerprint/path]
@@ -441,3 +573,224 @@ fn test_whitespace_in_keys() {
         "whitespace",
     );
 }
+
+#[test]
+fn test_descriptor_try_from() {
+    let valid = "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)";
+    let desc = Descriptor::try_from((valid, Network::Mainnet)).expect("should parse");
+    assert_eq!(desc.expand(0), check_parse_success(valid).expand(0));
+
+    assert!(Descriptor::try_from(("wpkh(not_a_key)", Network::Mainnet)).is_err());
+
+    let parsed: Result<Vec<Descriptor>, String> = [valid, "pkh(not_a_key)"]
+        .into_iter()
+        .map(|d| Descriptor::try_from((d, Network::Mainnet)))
+        .collect();
+    assert!(parsed.is_err());
+}
+
+#[test]
+fn test_address_type() {
+    use bitcoin::AddressType;
+
+    let cases = [
+        (
+            "pkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+            Some(AddressType::P2pkh),
+        ),
+        (
+            "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+            Some(AddressType::P2wpkh),
+        ),
+        (
+            "sh(wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd))",
+            Some(AddressType::P2sh),
+        ),
+        (
+            "wsh(pk(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd))",
+            Some(AddressType::P2wsh),
+        ),
+        (
+            "tr(a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+            Some(AddressType::P2tr),
+        ),
+        (
+            "raw(a914b61b92e2ca21bac1e72a3ab859a742982bea960a87)",
+            Some(AddressType::P2sh),
+        ),
+        (
+            "pk(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+            None,
+        ),
+        (
+            "combo(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+            None,
+        ),
+    ];
+
+    for (desc_str, expected) in cases {
+        assert_eq!(
+            check_parse_success(desc_str).address_type(),
+            expected,
+            "address type mismatch for '{desc_str}'"
+        );
+    }
+}
+
+#[test]
+fn test_network_ordering() {
+    use miniscript_core_ffi::Context;
+    use std::collections::BTreeSet;
+
+    let networks: BTreeSet<Network> = [
+        Network::Regtest,
+        Network::Signet,
+        Network::Testnet4,
+        Network::Testnet,
+        Network::Mainnet,
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        networks.into_iter().collect::<Vec<_>>(),
+        vec![
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Testnet4,
+            Network::Signet,
+            Network::Regtest,
+        ]
+    );
+
+    assert!(Context::Wsh < Context::Tapscript);
+}
+
+#[test]
+fn test_network_all_round_trip() {
+    let all = Network::all();
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+
+    let expected = [
+        bitcoin::Network::Bitcoin,
+        bitcoin::Network::Testnet,
+        bitcoin::Network::Testnet4,
+        bitcoin::Network::Signet,
+        bitcoin::Network::Regtest,
+    ];
+    for (network, btc) in all.into_iter().zip(expected) {
+        assert_eq!(bitcoin::Network::from(network), btc);
+        assert_eq!(Network::from(btc), network);
+        assert_eq!(bitcoin::Network::from(Network::from(btc)), btc);
+    }
+
+    assert_eq!(Network::from(bitcoin::Network::Testnet4), Network::Testnet4);
+    assert_eq!(
+        bitcoin::Network::from(Network::Testnet4),
+        bitcoin::Network::Testnet4
+    );
+    let desc = Descriptor::for_network(Network::Testnet4)
+        .parse("wpkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)")
+        .expect("should parse");
+    assert_eq!(desc.network(), Network::Testnet4);
+}
+
+#[test]
+fn test_descriptor_checksum() {
+    use miniscript_core_ffi::get_descriptor_checksum;
+
+    let desc = Descriptor::for_network(Network::Mainnet)
+        .parse("wpkh(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5)")
+        .expect("should parse");
+
+    let checksum = desc.checksum().expect("should compute checksum");
+    assert_eq!(checksum.len(), 8);
+    assert_eq!(
+        Some(checksum.clone()),
+        get_descriptor_checksum(&desc.to_string().unwrap())
+    );
+    assert!(desc.to_string().unwrap().ends_with(&format!("#{checksum}")));
+}
+
+#[test]
+fn test_addr_and_raw_descriptors() {
+    let script = hex::decode("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+
+    let addr = check_parse_success("addr(bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4)");
+    assert!(!addr.is_solvable());
+    assert_eq!(addr.expand(0), Some(script.clone()));
+    assert_eq!(
+        addr.addr_target().as_deref(),
+        Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+    );
+    assert_eq!(addr.raw_script(), None);
+
+    let raw = check_parse_success("raw(0014751e76e8199196d454941c45d1b3a323f1433bd6)");
+    assert!(!raw.is_solvable());
+    assert_eq!(raw.expand(0), Some(script.clone()));
+    assert_eq!(raw.raw_script(), Some(script));
+    assert_eq!(raw.addr_target(), None);
+
+    let wpkh = check_parse_success(
+        "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+    );
+    assert_eq!(wpkh.addr_target(), None);
+    assert_eq!(wpkh.raw_script(), None);
+}
+
+#[test]
+fn test_tap_leaves() {
+    let key_a = "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+    let key_b = "d0fa46cb883e940ac3dc5421f05b03859972639f51ed2eccbf3dc5a62e2e1b15";
+    let desc = check_parse_success(&format!(
+        "tr(a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd,{{pk({key_a}),pk({key_b})}})"
+    ));
+
+    // pk(K) is <K> OP_CHECKSIG
+    let leaf_script = |key: &str| {
+        let mut script = vec![0x20];
+        script.extend(hex::decode(key).unwrap());
+        script.push(0xac);
+        script
+    };
+    let leaves = desc.tap_leaves(0).expect("Failed to get leaves");
+    assert_eq!(leaves.len(), 2);
+    assert!(leaves.iter().all(|leaf| leaf.version == 0xc0));
+    assert_eq!(leaves[0].script, leaf_script(key_a));
+    assert_eq!(leaves[1].script, leaf_script(key_b));
+
+    // Key-path only: no leaves
+    let desc =
+        check_parse_success("tr(a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)");
+    assert_eq!(desc.tap_leaves(0), Some(Vec::new()));
+
+    let desc = check_parse_success(
+        "wpkh(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd)",
+    );
+    assert_eq!(desc.tap_leaves(0), None);
+}
+
+#[test]
+fn test_descriptor_satisfy_sh_wpkh() {
+    use miniscript_core_ffi::SimpleSatisfier;
+
+    let key_hex = "03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+    let key = hex::decode(key_hex).unwrap();
+    let desc = check_parse_success(&format!("sh(wpkh({key_hex}))"));
+
+    let mut satisfier = SimpleSatisfier::new();
+    satisfier.signatures.insert(key.clone(), vec![0x30; 72]);
+    let spend = desc.satisfy(0, satisfier).expect("should satisfy");
+
+    // The scriptSig is a single push of the redeem script OP_0 <hash160(key)>
+    let pubkey = bitcoin::PublicKey::from_slice(&key).unwrap();
+    let redeem = bitcoin::ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash().unwrap());
+    let mut expected = vec![0x16];
+    expected.extend(redeem.as_bytes());
+    assert_eq!(spend.script_sig.as_bytes(), expected.as_slice());
+
+    let witness: Vec<&[u8]> = spend.witness.iter().collect();
+    assert_eq!(witness, vec![&[0x30; 72][..], key.as_slice()]);
+
+    // Without a signature there is no spend
+    assert!(desc.satisfy(0, SimpleSatisfier::new()).is_err());
+}
@@ -3,7 +3,7 @@
 //! This test suite covers descriptors with BIP32 extended keys (xpub/xprv)
 //! and derivation paths, including wildcards and hardened derivation.
 
-use miniscript_core_ffi::descriptor::{Descriptor, Network};
+use miniscript_core_ffi::descriptor::{Descriptor, DescriptorCache, Network};
 
 /// Helper to check if descriptor parsing succeeds (uses mainnet for xpub keys)
 fn check_parse_success(desc_str: &str) -> Descriptor {
@@ -98,6 +98,30 @@ fn test_hardened_wildcard() {
     assert!(desc.is_range(), "Should be ranged");
     // Hardened derivation from xpub is not solvable without private key
     // The descriptor parses but cannot be expanded
+    assert!(desc.requires_private_key_to_expand());
+
+    let unhardened = check_parse_success(
+        "sh(wpkh(xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8/10/20/30/40/*))",
+    );
+    assert!(!unhardened.requires_private_key_to_expand());
+
+    let xpub = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    let single = "03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd";
+    for (desc_str, expected) in [
+        // Hardened steps in the key origin were already taken by its owner
+        (format!("wpkh([d34db33f/84h/0h/0h]{xpub}/0/*)"), false),
+        (format!("wpkh({xpub}/1h/0/*)"), true),
+        (format!("wsh(multi(1,{single},{xpub}/0/2h))"), true),
+        (format!("wsh(multi(1,{single},{xpub}/0/2))"), false),
+        (format!("wpkh({single})"), false),
+    ] {
+        let desc = check_parse_success(&desc_str);
+        assert_eq!(
+            desc.requires_private_key_to_expand(),
+            expected,
+            "{desc_str}"
+        );
+    }
 }
 
 #[test]
@@ -312,6 +336,40 @@ fn test_pubkey_extraction_from_xpub() {
     assert_ne!(pubkeys0[0], pubkeys1[0], "Derived pubkeys should differ");
 }
 
+#[test]
+fn test_pubkeys_range_matches_get_pubkeys() {
+    let desc_str = "tr(xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/0/*,pk(xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL/1/*))";
+    let desc = check_parse_success(desc_str);
+
+    let range = desc.pubkeys_range(0, 3);
+    assert_eq!(range.len(), 3);
+    for (i, keys) in (0..3).zip(&range) {
+        assert_eq!(*keys, desc.get_pubkeys(i), "Mismatch at index {i}");
+    }
+    assert_eq!(range[0].as_ref().map(Vec::len), Some(2));
+
+    assert!(desc.pubkeys_range(5, 0).is_empty());
+
+    // The last unhardened index is the end of the range
+    let last = desc.pubkeys_range(0x7fff_fffe, 2);
+    assert_eq!(last[1], desc.get_pubkeys(0x7fff_ffff));
+    assert!(last.iter().all(Option::is_some));
+    assert_eq!(desc.pubkeys_range(0x7fff_ffff, 2), vec![None, None]);
+    assert_eq!(desc.pubkeys_range(u32::MAX, 1), vec![None]);
+}
+
+#[test]
+fn test_scripts_equal_hardened_notation() {
+    let xpub = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+    let apostrophe = check_parse_success(&format!("wpkh([01234567/84'/0'/0']{xpub}/0/*)"));
+    let h = check_parse_success(&format!("wpkh([01234567/84h/0h/0h]{xpub}/0/*)"));
+    let change = check_parse_success(&format!("wpkh([01234567/84h/0h/0h]{xpub}/1/*)"));
+
+    assert!(apostrophe.scripts_equal(&h, 0..5));
+    assert!(h.scripts_equal(&apostrophe, 0..5));
+    assert!(!apostrophe.scripts_equal(&change, 0..5));
+}
+
 #[test]
 fn test_non_ranged_descriptor_ignores_index() {
     // Non-ranged descriptors should produce same output regardless of index
@@ -331,3 +389,268 @@ fn test_non_ranged_descriptor_ignores_index() {
         "Non-ranged descriptor should produce same script"
     );
 }
+
+#[test]
+fn test_expand_cached() {
+    let desc = check_parse_success(
+        "wpkh(xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH/1/2/*)",
+    );
+    let mut cache = DescriptorCache::new();
+
+    // First pass populates the cache, second pass reads from it
+    for _ in 0..2 {
+        for i in 0..100 {
+            let cached = desc.expand_cached(i, &mut cache);
+            assert!(cached.is_some(), "cached expansion failed at index {i}");
+            assert_eq!(cached, desc.expand(i), "mismatch at index {i}");
+        }
+    }
+
+    // The cache belongs to the first descriptor it was used with
+    let other = check_parse_success(
+        "pkh(xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH/1/2/*)",
+    );
+    assert_eq!(other.expand_cached(0, &mut cache), None);
+}
+
+#[test]
+fn test_derive_matches_separate_calls() {
+    let desc = check_parse_success(
+        "wpkh(xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH/1/2/*)",
+    );
+
+    for i in [0, 1, 42] {
+        let output = desc.derive(i).expect("Should derive");
+        assert_eq!(Some(output.script.to_bytes()), desc.expand(i), "index {i}");
+        assert_eq!(output.address, desc.get_address(i), "index {i}");
+        assert_eq!(Some(output.pubkeys), desc.get_pubkeys(i), "index {i}");
+    }
+
+    // Bare multisig has a script but no address
+    let bare = check_parse_success(
+        "multi(1,03669b8afcec803a0d323e9a17f3ea8e68e8abe5a278020a929adbec52421adbd0,0260b2003c386519fc9eadf2b5cf124dd8eea4c4e68d5e154050a9346ea98ce600)",
+    );
+    let output = bare.derive(0).expect("Should derive");
+    assert!(!output.script.is_empty());
+    assert_eq!(output.address, None);
+    assert_eq!(output.pubkeys.len(), 2);
+}
+
+#[test]
+fn test_encoding_uses_descriptor_network() {
+    let xpub = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+    let mut tpub: bitcoin::bip32::Xpub = xpub.parse().expect("valid xpub");
+    tpub.network = bitcoin::NetworkKind::Test;
+
+    let testnet = Descriptor::for_network(Network::Testnet)
+        .parse(&format!("wpkh({tpub}/1/2/*)"))
+        .expect("Should parse");
+    // Parsing for mainnet afterwards selects other chain parameters
+    let mainnet = check_parse_success(&format!("wpkh({xpub}/1/2/*)"));
+
+    assert!(testnet.to_string().unwrap().contains("tpub"));
+    assert!(testnet.get_address(0).unwrap().starts_with("tb1q"));
+    let output = testnet.derive(0).expect("Should derive");
+    assert!(output.address.unwrap().starts_with("tb1q"));
+    assert!(mainnet.get_address(0).unwrap().starts_with("bc1q"));
+
+    // The same holds when both are used from several threads at once
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for i in 0..25 {
+                    assert!(testnet.get_address(i).unwrap().starts_with("tb1q"));
+                    assert!(mainnet.get_address(i).unwrap().starts_with("bc1q"));
+                    assert!(testnet.to_string().unwrap().contains("tpub"));
+                }
+            });
+        }
+    });
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_scan_parallel_matches_derive() {
+    let desc = check_parse_success(
+        "wpkh(xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH/1/2/*)",
+    );
+
+    let sequential: Vec<_> = (0..50)
+        .map(|i| desc.derive(i).expect("Should derive"))
+        .collect();
+    assert_eq!(desc.scan_parallel(0..50), sequential);
+
+    assert!(desc.scan_parallel(0..0).is_empty());
+    assert_eq!(desc.scan_parallel(7..8), vec![desc.derive(7).unwrap()]);
+}
+
+#[test]
+fn test_structural_eq() {
+    let x1 = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB";
+    let x2 = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    let x3 = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+    let x4 = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+
+    let a = check_parse_success(&format!("wsh(sortedmulti(2,{x1}/0/*,{x2}/0/*,{x3}/0/*))"));
+    // Different keys, origin info and derivation paths
+    let b = check_parse_success(&format!(
+        "wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]{x4}/1/*,{x3}/7,{x2}/1/*))"
+    ));
+    assert!(a.structural_eq(&b));
+    assert!(b.structural_eq(&a));
+
+    // A 2-of-2 is a different structure
+    let c = check_parse_success(&format!("wsh(sortedmulti(2,{x1}/0/*,{x2}/0/*))"));
+    assert!(!a.structural_eq(&c));
+
+    // So is the same threshold under a different script type
+    let d = check_parse_success(&format!(
+        "sh(wsh(sortedmulti(2,{x1}/0/*,{x2}/0/*,{x3}/0/*)))"
+    ));
+    assert!(!a.structural_eq(&d));
+
+    // Timelocks are part of the structure
+    let e = check_parse_success(&format!("wsh(and_v(v:pk({x1}/0/*),older(144)))"));
+    let f = check_parse_success(&format!("wsh(and_v(v:pk({x2}/0/*),older(144)))"));
+    let g = check_parse_success(&format!("wsh(and_v(v:pk({x2}/0/*),older(1000)))"));
+    assert!(e.structural_eq(&f));
+    assert!(!e.structural_eq(&g));
+}
+
+#[test]
+fn test_contains_key() {
+    let x1 = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB";
+    let x2 = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    let x3 = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+    let x4 = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+
+    let desc = check_parse_success(&format!(
+        "wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]{x4}/0/*,{x2}/0/*,{x3}/0/*))"
+    ));
+
+    let member = desc.get_pubkeys(3).expect("should derive").remove(0);
+    assert!(desc.contains_pubkey(&member, 3));
+    // Keys are only matched at the index they were derived at
+    assert!(!desc.contains_pubkey(&member, 4));
+
+    let outsider = check_parse_success(&format!("wpkh({x1}/0/*)"))
+        .get_pubkeys(3)
+        .expect("should derive")
+        .remove(0);
+    assert!(!desc.contains_pubkey(&outsider, 3));
+
+    assert!(desc.contains_fingerprint([0xd3, 0x4d, 0xb3, 0x3f]));
+    assert!(!desc.contains_fingerprint([0x00, 0x00, 0x00, 0x00]));
+
+    // Hardened steps after an xpub cannot be derived, but origins are still found
+    let hardened = check_parse_success(&format!("wpkh([d34db33f/84h/0h/0h]{x4}/1h/*)"));
+    assert!(hardened.get_pubkeys(0).is_none());
+    assert!(hardened.contains_fingerprint([0xd3, 0x4d, 0xb3, 0x3f]));
+    assert!(!hardened.contains_fingerprint([0x00, 0x00, 0x00, 0x00]));
+}
+
+#[test]
+fn test_signing_requirements() {
+    let x2 = "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8";
+    let x3 = "xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y";
+    let x4 = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+
+    // A watch-only 2-of-3 needs all three private keys to be imported
+    let desc = check_parse_success(&format!(
+        "wsh(sortedmulti(2,[d34db33f/48h/0h/0h/2h]{x4}/0/*,[aabbccdd/48h/0h/0h/2h]{x2}/0/*,[01020304/48h/0h/0h/2h]{x3}/0/*))"
+    ));
+    let requirements = desc.signing_requirements(0).expect("should be determined");
+    assert_eq!(requirements.len(), 3);
+
+    // Requirements follow the order of the keys in the descriptor, not the
+    // sorted order of the multisig script
+    let fingerprints: Vec<[u8; 4]> = requirements.iter().map(|r| r.fingerprint).collect();
+    assert_eq!(
+        fingerprints,
+        vec![
+            [0xd3, 0x4d, 0xb3, 0x3f],
+            [0xaa, 0xbb, 0xcc, 0xdd],
+            [0x01, 0x02, 0x03, 0x04]
+        ]
+    );
+    assert_eq!(requirements[0].path, "m/48h/0h/0h/2h/0/0".parse().unwrap());
+
+    // Each requirement is for one of the keys at index 0
+    let pubkeys = desc.get_pubkeys(0).expect("should derive");
+    assert!(requirements.iter().all(|r| pubkeys.contains(&r.pubkey)));
+
+    // At another index, the paths end in that index
+    let requirements = desc.signing_requirements(5).expect("should be determined");
+    assert_eq!(requirements[0].path, "m/48h/0h/0h/2h/0/5".parse().unwrap());
+    let pubkeys = desc.get_pubkeys(5).expect("should derive");
+    assert!(requirements.iter().all(|r| pubkeys.contains(&r.pubkey)));
+
+    // Without the private key, hardened steps after an xpub cannot be
+    // derived, so the requirements cannot be determined
+    let hardened = check_parse_success(&format!("wpkh([d34db33f/84h]{x4}/1h/*)"));
+    assert!(hardened.signing_requirements(0).is_err());
+
+    // Nor can they for a descriptor no key lets us sign for
+    let unsolvable = check_parse_success("addr(bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4)");
+    assert!(unsolvable.signing_requirements(0).is_err());
+}
+
+#[test]
+fn test_signing_requirements_mixed_keys() {
+    use bitcoin::bip32::Xpriv;
+
+    let x4 = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+    let xprv = Xpriv::new_master(bitcoin::NetworkKind::Main, &[7u8; 32]).unwrap();
+
+    // The key given as an xprv is held already; only the xpub's is needed
+    let desc = check_parse_success(&format!(
+        "wsh(multi(1,{xprv}/0/*,[d34db33f/48h/0h/0h/2h]{x4}/0/*))"
+    ));
+    let requirements = desc.signing_requirements(0).expect("should be determined");
+    assert_eq!(requirements.len(), 1);
+    assert_eq!(requirements[0].fingerprint, [0xd3, 0x4d, 0xb3, 0x3f]);
+    assert_eq!(requirements[0].path, "m/48h/0h/0h/2h/0/0".parse().unwrap());
+
+    // With every private key at hand, nothing more is needed, even across
+    // hardened steps
+    let desc = check_parse_success(&format!("wpkh({xprv}/1h/*)"));
+    assert_eq!(desc.signing_requirements(0), Ok(Vec::new()));
+}
+
+#[test]
+fn test_parse_lenient() {
+    let xpub = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+    let plain = format!("wpkh({xpub}/0/*)");
+
+    let strict = check_parse_success(&plain);
+    let lenient = Descriptor::for_network(Network::Mainnet)
+        .parse_lenient(&plain)
+        .expect("lenient parse should succeed");
+    // Nothing had to be dropped
+    assert!(!strict.is_lenient());
+    assert!(!lenient.is_lenient());
+    for i in 0..5 {
+        assert_eq!(lenient.expand(i), strict.expand(i), "index {i}");
+    }
+
+    // A fingerprint that is not 4 bytes is rejected by the strict parser,
+    // but the lenient parser drops the origin and keeps the scripts
+    let malformed = format!("wpkh([d34db3/84h/0h/0h]{xpub}/0/*)");
+    assert!(
+        Descriptor::for_network(Network::Mainnet)
+            .parse(&malformed)
+            .is_err()
+    );
+    let recovered = Descriptor::for_network(Network::Mainnet)
+        .parse_lenient(&malformed)
+        .expect("lenient parse should drop the origin");
+    assert!(recovered.is_lenient());
+    assert!(recovered.scripts_equal(&strict, 0..5));
+
+    // Structural errors are still reported
+    assert!(
+        Descriptor::for_network(Network::Mainnet)
+            .parse_lenient(&format!("wpkh({xpub}/0/*"))
+            .is_err()
+    );
+}
@@ -3,7 +3,8 @@
 //! Tests complex miniscript structures including the production descriptor
 //! Based on Bitcoin Core's `descriptor_tests.cpp`
 
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::descriptor::{Descriptor, Network};
+use miniscript_core_ffi::{Context, Error, Fragment, FragmentKind, Miniscript};
 
 #[test]
 fn test_simple_andor() {
@@ -275,6 +276,71 @@ fn test_production_descriptor_to_string() {
     }
 }
 
+#[test]
+fn test_production_descriptor_walk() {
+    // Walk the production descriptor and count its key fragments
+    let ms = Miniscript::from_str(
+        "andor(multi(2,A,B,C),or_i(and_v(v:pkh(D),after(1748563200)),thresh(2,pk(E),s:pk(F),s:pk(G),snl:after(1735171200))),and_v(v:thresh(2,pkh(H),a:pkh(I),a:pkh(J)),after(1752451200)))",
+        Context::Wsh
+    ).unwrap();
+
+    let mut fragments = Vec::new();
+    ms.walk(|f| fragments.push(f.clone()));
+
+    assert_eq!(fragments[0], Fragment::AndOr, "Root should be andor");
+
+    let pk_k: Vec<_> = fragments
+        .iter()
+        .filter_map(|f| match f {
+            Fragment::PkK(key) => Some(key.clone()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(pk_k, vec![b"E".to_vec(), b"F".to_vec(), b"G".to_vec()]);
+
+    let pk_h = fragments
+        .iter()
+        .filter(|f| matches!(f, Fragment::PkH(_)))
+        .count();
+    assert_eq!(pk_h, 4, "D, H, I and J are pkh() keys");
+
+    assert!(fragments.contains(&Fragment::Multi {
+        k: 2,
+        keys: vec![b"A".to_vec(), b"B".to_vec(), b"C".to_vec()],
+    }));
+    assert!(fragments.contains(&Fragment::Thresh { k: 2, n: 4 }));
+    assert!(fragments.contains(&Fragment::After(1_748_563_200)));
+
+    // Every fragment except the root is a child of exactly one other fragment
+    let children: usize = fragments.iter().map(Fragment::child_count).sum();
+    assert_eq!(children + 1, fragments.len());
+}
+
+#[test]
+fn test_production_descriptor_fragment_counts() {
+    let ms = Miniscript::from_str(
+        "andor(multi(2,A,B,C),or_i(and_v(v:pkh(D),after(1748563200)),thresh(2,pk(E),s:pk(F),s:pk(G),snl:after(1735171200))),and_v(v:thresh(2,pkh(H),a:pkh(I),a:pkh(J)),after(1752451200)))",
+        Context::Wsh
+    ).unwrap();
+
+    let counts = ms.fragment_counts();
+    assert_eq!(
+        counts[&FragmentKind::PkH],
+        4,
+        "D, H, I and J are pkh() keys"
+    );
+    assert_eq!(counts[&FragmentKind::Multi], 1);
+    assert_eq!(counts[&FragmentKind::After], 3);
+    assert_eq!(counts[&FragmentKind::Thresh], 2);
+    assert_eq!(counts[&FragmentKind::AndOr], 1);
+    assert!(!counts.contains_key(&FragmentKind::Older));
+
+    // The counts cover every fragment of the walk
+    let mut total = 0;
+    ms.walk(|_| total += 1);
+    assert_eq!(counts.values().sum::<usize>(), total);
+}
+
 #[test]
 fn test_complex_structure_with_real_keys() {
     // Test with actual key formats (tpub)
@@ -285,13 +351,19 @@ fn test_complex_structure_with_real_keys() {
     let ms_str =
         format!("andor(multi(2,{tpub1}/0/0,{tpub2}/0/0,{tpub3}/0/0),pk({tpub1}),pk({tpub2}))");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "Complex structure with real keys should parse");
+    // The first descriptor key is reported; such keys need the Descriptor API
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert_eq!(
+        err,
+        Error::UnsupportedKeyFormat {
+            key: format!("{tpub1}/0/0")
+        }
+    );
 
-    let ms = ms.unwrap();
+    let desc = Descriptor::for_network(Network::Testnet).parse(&format!("wsh({ms_str})"));
     assert!(
-        ms.is_valid(),
-        "Complex structure with real keys should be valid"
+        desc.is_ok(),
+        "Complex structure with real keys should parse as a descriptor"
     );
 }
 
@@ -437,12 +509,20 @@ fn test_production_descriptor_with_wildcards() {
 
     let ms_str = format!("andor(multi(2,{tpub1}/0/*,{tpub2}/0/*,{tpub3}/0/*),pk(A),pk(B))");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "Production pattern with wildcards should parse");
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert_eq!(
+        err,
+        Error::UnsupportedKeyFormat {
+            key: format!("{tpub1}/0/*")
+        }
+    );
 
-    let ms = ms.unwrap();
+    let ms_str = format!(
+        "andor(multi(2,{tpub1}/0/*,{tpub2}/0/*,{tpub3}/0/*),pk({tpub1}/1/*),pk({tpub2}/1/*))"
+    );
+    let desc = Descriptor::for_network(Network::Testnet).parse(&format!("wsh({ms_str})"));
     assert!(
-        ms.is_valid(),
-        "Production pattern with wildcards should be valid"
+        desc.is_ok(),
+        "Production pattern with wildcards should parse as a descriptor"
     );
 }
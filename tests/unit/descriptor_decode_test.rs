@@ -15,11 +15,35 @@ const PROBLEM_DESCRIPTOR: &str = "wsh(andor(multi(2,[a0d3c79c/48'/1'/0'/2']tpubD
 const WILDCARD_DESCRIPTOR: &str = "wsh(andor(multi(2,[a0d3c79c/48'/1'/0'/2']tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL/0/*,[ea2484f9/48'/1'/0'/2']tpubDFQZzjy6GwSV6yk3X3aDZ6ETfoiNaquKhQHQ2EBG9jysaVqv7gMDBdUjYizYC1Sx8iQ41Rdxir64wcZrH8jZAeg8dhyGQFfKkGFkL3y6wnC/0/*,[93f245d7/48'/1'/0'/2']tpubDFNSUCdEmqX1HKkf3ykVz2VyuTsCja3dheQXiKmDyfDqTE9BD2Gmm3nszWRg8YBktEoTGYVS4waGqkEuycpiDnGcScrC2h4wVzDuq6RR7jT/0/*),or_i(and_v(v:pkh([61cdf766/84'/1'/0'/0]tpubDEmyALkSddGqCaSewWiCm2UA9ESmwtoq4RW4RJdkveAgbzfURVe3HgqfWX6b8f9w68JXjbPfUDRACPSoZg1qG4APr2W6P5yi6z7APjHrvzQ/0/*),after(1748563200)),thresh(2,pk([dc222dd4/48'/1'/0'/2']tpubDEsjRwVZFMds9KRH7J1sJ8RfQhZ6z7bD76fei4Bmgvo585dy9prVtiZy9R99tQoLiXPcAmbgoEzM6vtnhJ8TtyA6fWDwratqjW29p1DzZVF/0/*),s:pk([c95919a9/48'/1'/0'/2']tpubDF6xx8MeBmvwAcDsjFsukYfDdTfJnhQXMnRdSLW9uMvGsjv4Lw9cL9DxHgNzXRHdgVnnvrm5cBTs2ckhYms3NK3eyPYxRtUbsBUypPuqPrs/0/*),s:pk([9aeb59b9/48'/1'/0'/2']tpubDEWbaBvvddXg7kaGYiAZZZZG6H9j4ojR2SeJGWWFVGHcoEgyRGpPEaFdqmJs9XTX8jU7dWfSUDXiJuc8f54rBR7JdHeMLVB5bbpDijsvWdS/0/*),snl:after(1735171200))),and_v(v:thresh(2,pkh([dc222dd4/48'/1'/0'/2']tpubDEsjRwVZFMds9KRH7J1sJ8RfQhZ6z7bD76fei4Bmgvo585dy9prVtiZy9R99tQoLiXPcAmbgoEzM6vtnhJ8TtyA6fWDwratqjW29p1DzZVF/2/*),a:pkh([c95919a9/48'/1'/0'/2']tpubDF6xx8MeBmvwAcDsjFsukYfDdTfJnhQXMnRdSLW9uMvGsjv4Lw9cL9DxHgNzXRHdgVnnvrm5cBTs2ckhYms3NK3eyPYxRtUbsBUypPuqPrs/2/*),a:pkh([9aeb59b9/48'/1'/0'/2']tpubDEWbaBvvddXg7kaGYiAZZZZG6H9j4ojR2SeJGWWFVGHcoEgyRGpPEaFdqmJs9XTX8jU7dWfSUDXiJuc8f54rBR7JdHeMLVB5bbpDijsvWdS/2/*)),after(1752451200))))";
 
 /// Extract just the miniscript portion (inside wsh(...))
-fn extract_miniscript(descriptor: &str) -> Option<&str> {
+///
+/// Bare miniscript rejects key origins and derivation paths, so each distinct
+/// descriptor key is replaced by a placeholder name (`K0`, `K1`, ...).
+fn extract_miniscript(descriptor: &str) -> Option<String> {
     // Remove "wsh(" prefix and ")" suffix
     let stripped = descriptor.strip_prefix("wsh(")?;
     let stripped = stripped.strip_suffix(")")?;
-    Some(stripped)
+
+    let mut keys: Vec<&str> = Vec::new();
+    let mut result = String::with_capacity(stripped.len());
+    for token in stripped.split_inclusive(['(', ')', ',']) {
+        let (arg, delim) = token.split_at(token.len() - 1);
+        let (arg, delim) = if "(),".contains(delim) {
+            (arg, delim)
+        } else {
+            (token, "")
+        };
+        if arg.contains(['[', '/']) {
+            let index = keys.iter().position(|k| *k == arg).unwrap_or_else(|| {
+                keys.push(arg);
+                keys.len() - 1
+            });
+            result.push_str(&format!("K{index}"));
+        } else {
+            result.push_str(arg);
+        }
+        result.push_str(delim);
+    }
+    Some(result)
 }
 
 #[test]
@@ -39,7 +63,7 @@ fn test_full_descriptor_parsing() {
         );
         println!("{miniscript_str}");
 
-        let result = Miniscript::from_str(miniscript_str, Context::Wsh);
+        let result = Miniscript::from_str(&miniscript_str, Context::Wsh);
         match result {
             Ok(ms) => {
                 println!("\n✓ Miniscript parsed successfully!");
@@ -352,7 +376,7 @@ fn test_descriptor_analysis() {
     // Let's analyze its properties in detail
     if let Some(miniscript_str) = extract_miniscript(PROBLEM_DESCRIPTOR) {
         let ms =
-            Miniscript::from_str(miniscript_str, Context::Wsh).expect("Miniscript should parse");
+            Miniscript::from_str(&miniscript_str, Context::Wsh).expect("Miniscript should parse");
 
         println!("Miniscript Properties:");
         println!("  Valid: {}", ms.is_valid());
@@ -416,7 +440,7 @@ fn test_production_descriptor_success() {
     // This test proves the miniscript is valid according to Bitcoin Core's implementation
     let miniscript_str = extract_miniscript(PROBLEM_DESCRIPTOR).expect("Should extract miniscript");
 
-    let ms = Miniscript::from_str(miniscript_str, Context::Wsh)
+    let ms = Miniscript::from_str(&miniscript_str, Context::Wsh)
         .expect("Miniscript should parse successfully");
 
     // Verify all sanity checks pass
@@ -456,7 +480,7 @@ fn test_wildcard_descriptor() {
         println!("{}", &miniscript_str[..200.min(miniscript_str.len())]);
         println!("...");
 
-        let result = Miniscript::from_str(miniscript_str, Context::Wsh);
+        let result = Miniscript::from_str(&miniscript_str, Context::Wsh);
         match result {
             Ok(ms) => {
                 println!("\n✓ Wildcard miniscript parsed successfully!");
@@ -490,10 +514,10 @@ fn test_concrete_vs_wildcard() {
     println!("\n=== Comparing Concrete vs Wildcard Descriptors ===\n");
 
     let concrete_ms = extract_miniscript(PROBLEM_DESCRIPTOR)
-        .and_then(|s| Miniscript::from_str(s, Context::Wsh).ok());
+        .and_then(|s| Miniscript::from_str(&s, Context::Wsh).ok());
 
     let wildcard_ms = extract_miniscript(WILDCARD_DESCRIPTOR)
-        .and_then(|s| Miniscript::from_str(s, Context::Wsh).ok());
+        .and_then(|s| Miniscript::from_str(&s, Context::Wsh).ok());
 
     match (&concrete_ms, &wildcard_ms) {
         (Some(concrete), Some(wildcard)) => {
@@ -628,7 +652,7 @@ fn test_script_to_address_components() {
     // Use a concrete derived miniscript (index 0)
     let miniscript_str = extract_miniscript(PROBLEM_DESCRIPTOR).expect("Should extract miniscript");
 
-    let ms = Miniscript::from_str(miniscript_str, Context::Wsh).expect("Should parse");
+    let ms = Miniscript::from_str(&miniscript_str, Context::Wsh).expect("Should parse");
 
     if let Some(script) = ms.to_script() {
         println!("Witness Script:");
@@ -696,3 +720,73 @@ fn test_batch_address_validation() {
 
     assert_eq!(valid_count, 10, "All derived addresses should be valid");
 }
+
+/// `wsh_script_hash` matches hashing the witness script by hand
+#[test]
+fn test_wsh_script_hash() {
+    let miniscript_str = extract_miniscript(PROBLEM_DESCRIPTOR).expect("Should extract miniscript");
+    let ms = Miniscript::from_str(&miniscript_str, Context::Wsh).expect("Should parse");
+    let script = ms.to_script().expect("Should produce script");
+
+    let expected = sha256::Hash::hash(script.as_bytes());
+    assert_eq!(ms.wsh_script_hash(), Some(expected.to_byte_array()));
+
+    let tap = Miniscript::from_str(
+        "pk(d0fa46cb883e940ac3dc5421f05b03859972639f51ed2eccbf3dc5a62e2e1b15)",
+        Context::Tapscript,
+    )
+    .expect("Should parse");
+    assert_eq!(tap.wsh_script_hash(), None);
+}
+
+/// `witness_program` is a v0 program over the witness script hash
+#[test]
+fn test_witness_program() {
+    let miniscript_str = extract_miniscript(PROBLEM_DESCRIPTOR).expect("Should extract miniscript");
+    let ms = Miniscript::from_str(&miniscript_str, Context::Wsh).expect("Should parse");
+
+    let program = ms
+        .witness_program()
+        .expect("Should produce witness program");
+    assert_eq!(program.version(), bitcoin::WitnessVersion::V0);
+    assert_eq!(
+        program.program().as_bytes(),
+        ms.wsh_script_hash().unwrap().as_slice()
+    );
+
+    let address = Address::from_witness_program(program, bitcoin::KnownHrp::Mainnet);
+    assert_eq!(
+        address.script_pubkey(),
+        bitcoin::ScriptBuf::new_p2wsh(&ms.to_script().unwrap().wscript_hash())
+    );
+
+    let tap = Miniscript::from_str(
+        "pk(d0fa46cb883e940ac3dc5421f05b03859972639f51ed2eccbf3dc5a62e2e1b15)",
+        Context::Tapscript,
+    )
+    .expect("Should parse");
+    assert_eq!(tap.witness_program(), None);
+}
+
+/// `sh_wsh_script_pubkey` matches the output of an `sh(wsh(...))` descriptor
+#[test]
+fn test_sh_wsh_script_pubkey() {
+    use miniscript_core_ffi::{Descriptor, DescriptorNetwork};
+
+    let inner = "and_v(v:pk(03a34b99f22c790c4e36b2b3c2c35a36db06226e41c692fc82b8b56ac1c540c5bd),pk(02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5))";
+    let ms = Miniscript::from_str(inner, Context::Wsh).expect("Should parse");
+    let desc = Descriptor::for_network(DescriptorNetwork::Mainnet)
+        .parse(&format!("sh(wsh({inner}))"))
+        .expect("Should parse descriptor");
+
+    let script_pubkey = ms.sh_wsh_script_pubkey().expect("Should produce script");
+    assert!(script_pubkey.is_p2sh());
+    assert_eq!(Some(script_pubkey.to_bytes()), desc.expand(0));
+
+    let tap = Miniscript::from_str(
+        "pk(d0fa46cb883e940ac3dc5421f05b03859972639f51ed2eccbf3dc5a62e2e1b15)",
+        Context::Tapscript,
+    )
+    .expect("Should parse");
+    assert_eq!(tap.sh_wsh_script_pubkey(), None);
+}
@@ -3,7 +3,7 @@
 //! Tests basic descriptor parsing, key formats, and simple miniscript components.
 //! Based on Bitcoin Core's `descriptor_tests.cpp`
 
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, Error, ErrorCode, Miniscript};
 
 #[test]
 fn test_simple_pk_parsing() {
@@ -149,8 +149,14 @@ fn test_tpub_format() {
     let tpub = "tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL";
     let ms_str = format!("pk({tpub})");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "pk with tpub should parse");
+    // Extended keys need the Descriptor API
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert_eq!(
+        err,
+        Error::UnsupportedKeyFormat {
+            key: tpub.to_string()
+        }
+    );
 }
 
 #[test]
@@ -159,8 +165,14 @@ fn test_tpub_with_derivation_path() {
     let tpub = "tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL";
     let ms_str = format!("pk({tpub}/0/0)");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "pk with tpub and path should parse");
+    // Derivation paths need the Descriptor API
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert_eq!(
+        err,
+        Error::UnsupportedKeyFormat {
+            key: format!("{tpub}/0/0")
+        }
+    );
 }
 
 #[test]
@@ -169,8 +181,9 @@ fn test_key_origin_format() {
     let tpub = "tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL";
     let ms_str = format!("pk([a0d3c79c/48'/1'/0'/2']{tpub}/0/0)");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "pk with key origin should parse");
+    // Key origins need the Descriptor API
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert!(matches!(err, Error::UnsupportedKeyFormat { .. }));
 }
 
 #[test]
@@ -179,8 +192,26 @@ fn test_wildcard_derivation() {
     let tpub = "tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL";
     let ms_str = format!("pk({tpub}/0/*)");
 
-    let ms = Miniscript::from_str(&ms_str, Context::Wsh);
-    assert!(ms.is_ok(), "pk with wildcard should parse");
+    let err = Miniscript::from_str(&ms_str, Context::Wsh).unwrap_err();
+    assert!(matches!(err, Error::UnsupportedKeyFormat { .. }));
+}
+
+#[test]
+fn test_descriptor_key_rejected() {
+    // Key origins and derivation paths need the Descriptor API
+    let tpub = "tpubDF81GR3CqbLCT7ND3q4pPWDtpbkKfHihUMwVgQeXV9ZqJ6YJ5gJgd1W1cWbiVRfXfjc1KyRCRCpVUKVHVYjrPLbtbvRLB9L4hWfWyrZqGEL";
+    let key = format!("[a0d3c79c/48'/1'/0'/2']{tpub}/0/0");
+
+    let err = Miniscript::from_str(&format!("pk({key})"), Context::Wsh).unwrap_err();
+    assert_eq!(err, Error::UnsupportedKeyFormat { key });
+    assert_eq!(err.code(), Some(ErrorCode::UnknownKeyFormat));
+
+    // Names that only look like descriptor keys are opaque key names
+    let ms = Miniscript::from_str("pk([a0d3c79c/48'/1'/0'/2']A/0/0)", Context::Wsh);
+    assert!(
+        ms.is_ok(),
+        "Key names with descriptor punctuation should parse"
+    );
 }
 
 #[test]
@@ -340,3 +371,26 @@ fn test_placeholder_key_names() {
         assert!(ms.is_ok(), "pk({placeholder}) should parse");
     }
 }
+
+#[test]
+fn test_from_descriptor_fragment() {
+    let wsh = Miniscript::from_descriptor_fragment("wsh(pk(A))").unwrap();
+    assert_eq!(wsh.context(), Context::Wsh);
+    assert_eq!(wsh.to_string().as_deref(), Some("pk(A)"));
+
+    let tr = Miniscript::from_descriptor_fragment("tr(pk(A))").unwrap();
+    assert_eq!(tr.context(), Context::Tapscript);
+    assert_eq!(tr.to_string().as_deref(), Some("pk(A)"));
+
+    // Internal key and checksum are skipped
+    let tr = Miniscript::from_descriptor_fragment("tr(K,and_v(v:pk(A),pk(B)))#abcdefgh").unwrap();
+    assert_eq!(tr.context(), Context::Tapscript);
+    assert_eq!(tr.to_string().as_deref(), Some("and_v(v:pk(A),pk(B))"));
+
+    // Bare miniscript is parsed as P2WSH
+    let bare = Miniscript::from_descriptor_fragment("pk(A)").unwrap();
+    assert_eq!(bare.context(), Context::Wsh);
+
+    assert!(Miniscript::from_descriptor_fragment("wsh(pk(A)").is_err());
+    assert!(Miniscript::from_descriptor_fragment("tr(K,{pk(A),pk(B)})").is_err());
+}
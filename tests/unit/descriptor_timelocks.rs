@@ -445,3 +445,25 @@ fn test_wrapped_timelock_to_string() {
         );
     }
 }
+
+#[test]
+fn test_timelock_mix_detail() {
+    // Height and time mixed within after()
+    let ms = Miniscript::from_str("and_b(after(100),a:after(1000000000))", Context::Wsh).unwrap();
+    assert!(ms.has_timelock_mix());
+    let report = ms.timelock_mix_detail();
+    assert!(report.height_time_in_after);
+    assert!(!report.height_time_in_older);
+    assert!(report.has_mix());
+
+    // Height and time mixed within older() (bit 22 marks time-based locks)
+    let ms = Miniscript::from_str("and_b(older(100),a:older(4194305))", Context::Wsh).unwrap();
+    let report = ms.timelock_mix_detail();
+    assert!(!report.height_time_in_after);
+    assert!(report.height_time_in_older);
+
+    // Absolute height combined with relative time is not mixing
+    let ms = Miniscript::from_str("and_b(after(100),a:older(4194305))", Context::Wsh).unwrap();
+    assert!(!ms.has_timelock_mix());
+    assert!(!ms.timelock_mix_detail().has_mix());
+}
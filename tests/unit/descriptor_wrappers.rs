@@ -3,7 +3,7 @@
 //! Tests miniscript type wrappers (a:, s:, c:, d:, v:, j:, n:, l:, u:, t:)
 //! Based on Bitcoin Core's `descriptor_tests.cpp`
 
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, Miniscript, wrapper_size_delta};
 
 #[test]
 fn test_a_wrapper() {
@@ -469,3 +469,24 @@ fn test_wrapper_to_string() {
         );
     }
 }
+
+#[test]
+fn test_wrapper_size_delta() {
+    // v: adds OP_VERIFY after an expression ending in OP_CHECKSEQUENCEVERIFY
+    let delta = wrapper_size_delta("older(144)", "v:", Context::Wsh).expect("should parse");
+    assert!(delta > 0);
+    assert_eq!(delta, 1);
+
+    // ... but turns OP_CHECKSIG into OP_CHECKSIGVERIFY for free
+    assert_eq!(wrapper_size_delta("pk(A)", "v:", Context::Wsh).unwrap(), 0);
+
+    // OP_SWAP, with or without the colon, merged into existing wrappers
+    assert_eq!(wrapper_size_delta("pk(A)", "s", Context::Wsh).unwrap(), 1);
+    assert_eq!(
+        wrapper_size_delta("n:pk(A)", "s:", Context::Wsh).unwrap(),
+        1
+    );
+
+    // s: needs a B-type expression, v:pk(A) is V-type
+    assert!(wrapper_size_delta("v:pk(A)", "s:", Context::Wsh).is_err());
+}
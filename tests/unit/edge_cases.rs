@@ -4,7 +4,7 @@
 //! script roundtrips, and other corner cases.
 
 use super::common::init_testdata;
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, Miniscript, Policy};
 
 #[test]
 fn test_duplicate_keys_not_sane() {
@@ -110,6 +110,110 @@ fn test_script_roundtrip() {
     assert_eq!(script, script2, "Roundtrip failed");
 }
 
+#[test]
+fn test_script_hex_roundtrip() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("multi(2,A,B,C)", Context::Wsh).expect("Failed to parse");
+    let script_hex = ms.script_hex().expect("Failed to get script hex");
+    assert_eq!(
+        script_hex,
+        hex::encode(ms.to_script().unwrap().as_bytes()),
+        "script_hex should match the encoded script"
+    );
+
+    let ms2 = Miniscript::from_script_hex(&script_hex, Context::Wsh)
+        .expect("Failed to parse from script hex");
+    assert_eq!(ms2.script_hex().as_deref(), Some(script_hex.as_str()));
+
+    assert!(Miniscript::from_script_hex("52zz", Context::Wsh).is_err());
+}
+
+#[test]
+fn test_matches_script() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("Failed to parse");
+    let script = ms.to_script_bytes().expect("Failed to get script");
+    assert!(ms.matches_script(&script));
+    assert!(Miniscript::from_script_matches(&script, Context::Wsh, &ms));
+
+    // Flipping a key byte still decodes, but to a different miniscript
+    let mut mutated = script.clone();
+    mutated[1] ^= 0x01;
+    assert!(!ms.matches_script(&mutated));
+    assert!(!Miniscript::from_script_matches(
+        &mutated,
+        Context::Wsh,
+        &ms
+    ));
+
+    // The expected miniscript must be in the same context
+    assert!(!Miniscript::from_script_matches(
+        &script,
+        Context::Tapscript,
+        &ms
+    ));
+}
+
+#[test]
+fn test_expanded_string() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("Failed to parse");
+    assert_eq!(ms.normalized_string().as_deref(), Some("pk(A)"));
+    assert_eq!(ms.expanded_string().as_deref(), Some("c:pk_k(A)"));
+
+    let ms = Miniscript::from_str(
+        "andor(pk(A),tv:older(10),and_n(pkh(B),l:after(100)))",
+        Context::Wsh,
+    )
+    .expect("Failed to parse");
+    let expanded = ms.expanded_string().expect("Failed to expand");
+    assert_eq!(
+        expanded,
+        "andor(c:pk_k(A),and_v(v:older(10),1),andor(c:pk_h(B),or_i(0,after(100)),0))"
+    );
+
+    // The expanded form parses back to the same miniscript
+    let reparsed = Miniscript::from_str(&expanded, Context::Wsh).expect("Failed to reparse");
+    assert_eq!(reparsed.to_string(), ms.to_string());
+}
+
+#[test]
+fn test_script_asm() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("Failed to parse");
+    let asm = ms.script_asm().expect("Failed to get ASM");
+    assert!(asm.starts_with("OP_PUSHBYTES_33 "), "{asm}");
+    assert!(asm.ends_with("OP_CHECKSIG"), "{asm}");
+
+    let ms =
+        Miniscript::from_str("and_v(v:pk(A),older(144))", Context::Wsh).expect("Failed to parse");
+    let asm = ms.script_asm().expect("Failed to get ASM");
+    assert!(asm.contains("OP_CHECKSIGVERIFY"), "{asm}");
+    assert!(asm.ends_with("OP_CSV"), "{asm}");
+}
+
+#[test]
+fn test_with_script_bytes() {
+    init_testdata();
+
+    for ms_str in ["pk(A)", "and_v(v:pk(A),older(144))", "multi(2,A,B,C)"] {
+        let ms = Miniscript::from_str(ms_str, Context::Wsh).expect("Failed to parse");
+        let len = ms
+            .with_script_bytes(<[u8]>::len)
+            .expect("Failed to get script");
+        assert_eq!(len, ms.to_script_bytes().unwrap().len(), "{ms_str}");
+        assert_eq!(
+            ms.with_script_bytes(<[u8]>::to_vec),
+            ms.to_script_bytes(),
+            "{ms_str}"
+        );
+    }
+}
+
 #[test]
 fn test_non_minimal_push_invalid() {
     init_testdata();
@@ -170,6 +274,22 @@ fn test_check_duplicate_key_method() {
     assert!(ms_no_dup.is_sane(), "Should be sane without duplicate keys");
 }
 
+#[test]
+fn test_duplicate_keys() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(A))", Context::Wsh).expect("should parse");
+    assert!(!ms.check_duplicate_key());
+    assert_eq!(ms.duplicate_keys(), vec![b"A".to_vec()]);
+
+    let ms = Miniscript::from_str("multi(2,A,B,C)", Context::Wsh).expect("should parse");
+    assert!(ms.check_duplicate_key());
+    assert!(ms.duplicate_keys().is_empty());
+
+    // pk and pkh of the same key count together
+    let ms = Miniscript::from_str("or_d(pk(B),and_v(v:pkh(B),pk(A)))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(ms.duplicate_keys(), vec![b"B".to_vec()]);
+}
+
 #[test]
 fn test_check_ops_and_stack_limits() {
     init_testdata();
@@ -295,3 +415,278 @@ fn test_static_ops_tapscript() {
         assert!(ops > 0, "Should have at least 1 op");
     }
 }
+
+#[test]
+fn test_is_canonical_input() {
+    assert!(Miniscript::is_canonical_input("pk(A)", Context::Wsh).unwrap());
+
+    // Whitespace is not part of the canonical form
+    assert!(!matches!(
+        Miniscript::is_canonical_input(" pk( A ) ", Context::Wsh),
+        Ok(true)
+    ));
+
+    // c:pk_k(A) normalizes to pk(A)
+    assert!(!Miniscript::is_canonical_input("c:pk_k(A)", Context::Wsh).unwrap());
+
+    assert!(Miniscript::is_canonical_input("invalid", Context::Wsh).is_err());
+}
+
+#[test]
+fn test_miniscript_try_from() {
+    let ms = Miniscript::try_from("and_v(v:pk(A),pk(B))").expect("should parse");
+    assert_eq!(ms.context(), Context::Wsh);
+    assert_eq!(ms.to_string().as_deref(), Some("and_v(v:pk(A),pk(B))"));
+
+    assert!(Miniscript::try_from("and_v(pk(A))").is_err());
+
+    let parsed: Result<Vec<Miniscript>, _> = ["pk(A)", "or_b(pk(A),s:pk(B))"]
+        .into_iter()
+        .map(Miniscript::try_from)
+        .collect();
+    assert_eq!(parsed.expect("should parse").len(), 2);
+}
+
+#[test]
+fn test_input_length_limit() {
+    use miniscript_core_ffi::{Error, MAX_INPUT_LEN};
+
+    // At the limit the input is parsed
+    let ms = Miniscript::from_str_limited("pk(A)", Context::Wsh, 5).expect("should parse");
+    assert_eq!(ms.to_string().as_deref(), Some("pk(A)"));
+
+    assert_eq!(
+        Miniscript::from_str_limited("pk(A)", Context::Wsh, 4).unwrap_err(),
+        Error::InputTooLong { len: 5, max: 4 }
+    );
+
+    // The default cap rejects oversized input before it reaches the parser
+    let huge = "a".repeat(MAX_INPUT_LEN + 1);
+    assert_eq!(
+        Miniscript::from_str(&huge, Context::Wsh).unwrap_err(),
+        Error::InputTooLong {
+            len: MAX_INPUT_LEN + 1,
+            max: MAX_INPUT_LEN,
+        }
+    );
+}
+
+#[test]
+fn test_non_ascii_input() {
+    use miniscript_core_ffi::Error;
+
+    assert_eq!(
+        Miniscript::from_str("pk(A)\0", Context::Wsh).unwrap_err(),
+        Error::NonAsciiInput { position: 5 }
+    );
+    assert_eq!(
+        Miniscript::from_str("pk(\u{c1})", Context::Wsh).unwrap_err(),
+        Error::NonAsciiInput { position: 3 }
+    );
+    assert_eq!(
+        Miniscript::from_str("and_v(v:pk(A),pk(B))\u{e9}", Context::Tapscript).unwrap_err(),
+        Error::NonAsciiInput { position: 20 }
+    );
+}
+
+#[test]
+fn test_fuzz_roundtrip_stable() {
+    use miniscript_core_ffi::{RoundtripOutcome, fuzz_roundtrip};
+
+    init_testdata();
+
+    let wsh = [
+        "pk(A)",
+        "pkh(A)",
+        "and_v(v:pk(A),pk(B))",
+        "or_d(pk(A),and_v(v:pkh(B),older(144)))",
+        "andor(pk(A),tv:older(10),and_n(pkh(B),l:after(100)))",
+        "thresh(2,pk(A),s:pk(B),sln:older(12960))",
+        "multi(2,A,B,C)",
+        "or_i(and_v(v:pkh(A),after(1000)),sha256(0000000000000000000000000000000000000000000000000000000000000001))",
+        "v:pk(A)",
+    ];
+    for input in wsh {
+        assert_eq!(
+            fuzz_roundtrip(input, Context::Wsh),
+            RoundtripOutcome::Accepted { stable: true },
+            "{input}"
+        );
+    }
+
+    for input in ["pk(A)", "multi_a(2,A,B,C)", "and_v(v:pk(A),pk(B))"] {
+        assert_eq!(
+            fuzz_roundtrip(input, Context::Tapscript),
+            RoundtripOutcome::Accepted { stable: true },
+            "{input}"
+        );
+    }
+
+    for input in ["", "pk(", "and_v(pk(A),pk(B))", "multi_a(1,A)", "\0"] {
+        assert_eq!(
+            fuzz_roundtrip(input, Context::Wsh),
+            RoundtripOutcome::Rejected
+        );
+    }
+}
+
+#[test]
+fn test_verify_lines() {
+    use miniscript_core_ffi::verify_lines;
+
+    init_testdata();
+
+    let vectors = "and_v(v:pk(A),pk(B))\nand_v(pk(A),pk(B))\n  l:older(1)  ";
+    let results: Vec<_> = verify_lines(vectors.lines(), Context::Wsh).collect();
+    assert_eq!(results.len(), 3);
+
+    assert_eq!(results[0].input, "and_v(v:pk(A),pk(B))");
+    assert!(results[0].parsed && results[0].valid && results[0].sane);
+    assert_eq!(
+        results[0].canonical.as_deref(),
+        Some("and_v(v:pk(A),pk(B))")
+    );
+
+    // Invalid type: and_v needs a V-type first argument
+    assert!(!results[1].parsed);
+    assert_eq!(results[1].canonical, None);
+
+    // Valid, but not sane (no signature required)
+    assert_eq!(results[2].input, "  l:older(1)  ");
+    assert!(results[2].parsed && results[2].valid);
+    assert!(!results[2].sane);
+    assert_eq!(results[2].canonical.as_deref(), Some("l:older(1)"));
+}
+
+#[test]
+fn test_bytes_roundtrip() {
+    init_testdata();
+
+    for context in [Context::Wsh, Context::Tapscript] {
+        let ms = Miniscript::from_str("and_v(v:pk(A),older(100))", context).expect("should parse");
+        let script = ms.to_script_bytes().expect("should produce script");
+
+        let bytes = ms.to_bytes().expect("should serialize");
+        assert_eq!(&bytes[1..], script.as_slice());
+
+        let decoded = Miniscript::from_bytes(&bytes).expect("should decode");
+        assert_eq!(decoded.context(), context);
+        assert!(decoded.matches_script(&script));
+        assert_eq!(decoded.to_bytes(), Some(bytes));
+    }
+
+    assert!(Miniscript::from_bytes(&[]).is_err());
+    assert!(Miniscript::from_bytes(&[2, 0x51]).is_err());
+}
+
+#[test]
+fn test_depth_bound() {
+    use miniscript_core_ffi::Error;
+
+    init_testdata();
+
+    // or_i -> or_i -> c -> pk_k
+    let input = "or_i(pk(A),or_i(pk(B),pk(C)))";
+    let ms = Miniscript::from_str(input, Context::Wsh).expect("should parse");
+    assert_eq!(ms.depth(), 4);
+    assert_eq!(
+        Miniscript::from_str("pk(A)", Context::Wsh).unwrap().depth(),
+        2
+    );
+
+    assert!(Miniscript::from_str_bounded(input, Context::Wsh, 4).is_ok());
+    // The text shows three levels; the hidden c: makes four
+    assert_eq!(
+        Miniscript::from_str_bounded(input, Context::Wsh, 3).unwrap_err(),
+        Error::TooDeep { depth: 4, max: 3 }
+    );
+
+    // Rejected from the text alone, before parsing
+    let mut deep = "pk(A)".to_string();
+    for _ in 0..1000 {
+        deep = format!("and_v(v:pk(A),{deep})");
+    }
+    match Miniscript::from_str_bounded(&deep, Context::Wsh, 100).unwrap_err() {
+        Error::TooDeep { depth, max } => {
+            assert!(depth > 100);
+            assert_eq!(max, 100);
+        }
+        err => panic!("unexpected error {err:?}"),
+    }
+}
+
+/// A tree far deeper than the native stack allows recursing into: each `l:`
+/// is an `or_i(0,X)`, so this is 50,000 `or_i` levels around `c:pk_k(A)`
+fn very_deep_tree() -> Miniscript {
+    let input = format!("{}:pk(A)", "l".repeat(50_000));
+    Miniscript::from_str_permissive(&input, Context::Tapscript).expect("should parse")
+}
+
+#[test]
+fn test_walk_very_deep_tree() {
+    let mut count = 0;
+    very_deep_tree().walk(|_| count += 1);
+    assert_eq!(count, 2 * 50_000 + 2);
+}
+
+#[test]
+fn test_spend_paths_very_deep_tree() {
+    let ms = very_deep_tree();
+    // or_i(0,X) has the paths of X alone
+    assert_eq!(ms.spend_path_count(), Some(1));
+    assert_eq!(ms.branch_timelocks().map(|locks| locks.len()), Some(1));
+}
+
+#[test]
+fn test_expanded_string_very_deep_tree() {
+    let expanded = very_deep_tree().expanded_string().expect("should print");
+    let expected = format!(
+        "{}c:pk_k(A){}",
+        "or_i(0,".repeat(50_000),
+        ")".repeat(50_000)
+    );
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_template_very_deep_tree() {
+    let template = very_deep_tree().template().expect("should have a template");
+    assert_eq!(template, format!("{}:pk(A)", "l".repeat(50_000)));
+}
+
+#[test]
+fn test_needs_preimage_very_deep_tree() {
+    let h = "0000000000000000000000000000000000000000000000000000000000000001";
+    let input = format!("{}:sha256({h})", "l".repeat(50_000));
+    let ms = Miniscript::from_str_permissive(&input, Context::Tapscript).expect("should parse");
+    assert!(ms.needs_preimage());
+    assert!(!very_deep_tree().needs_preimage());
+}
+
+#[test]
+fn test_branch_probabilities_deep_tree() {
+    // Each branch is printed in full, so this is shallower than
+    // very_deep_tree() to keep the output small
+    let input = format!("{}:pk(A)", "l".repeat(5_000));
+    let ms = Miniscript::from_str_permissive(&input, Context::Tapscript).expect("should parse");
+    let branches = ms.branch_probabilities();
+    assert_eq!(branches.len(), 2 * 5_000);
+    assert_eq!(branches[0], ("0".to_string(), 0.5));
+    assert_eq!(branches[1], (format!("{}:pk(A)", "l".repeat(4_999)), 0.5));
+}
+
+#[test]
+fn test_lift_very_deep_tree() {
+    // Each or_i(0,X) lifts to X alone
+    assert_eq!(very_deep_tree().lift(), Some(Policy::Key(b"A".to_vec())));
+}
+
+#[test]
+fn test_satisfaction_size_very_deep_tree() {
+    // The signature and its length prefix, plus a 1 selecting the right
+    // branch of each or_i
+    assert_eq!(
+        very_deep_tree().satisfaction_size_with(64),
+        Some(1 + 64 + 50_000)
+    );
+}
@@ -2,6 +2,7 @@
 //!
 //! This module organizes tests into logical categories matching Bitcoin Core's test structure.
 
+pub mod builder_tests;
 pub mod common;
 pub mod complex_miniscripts;
 pub mod descriptor_basic;
@@ -13,7 +14,10 @@ pub mod descriptor_timelocks;
 pub mod descriptor_validation;
 pub mod descriptor_wrappers;
 pub mod edge_cases;
+pub mod policy_tests;
 pub mod resource_limits;
 pub mod satisfaction_tests;
 pub mod tapscript_tests;
+#[cfg(feature = "tracing")]
+pub mod tracing_tests;
 pub mod validity_tests;
@@ -0,0 +1,142 @@
+//! Policy lifting tests
+//!
+//! These tests verify that miniscripts lift to the spending policy they enforce.
+
+use miniscript_core_ffi::{Context, Miniscript, Policy, Template};
+
+fn key(name: &str) -> Policy {
+    Policy::Key(name.as_bytes().to_vec())
+}
+
+#[test]
+fn test_lift_or_i() {
+    let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(100)))", Context::Wsh)
+        .expect("should parse");
+
+    assert_eq!(
+        ms.lift(),
+        Some(Policy::Or(vec![
+            key("A"),
+            Policy::And(vec![key("B"), Policy::After(100)]),
+        ]))
+    );
+}
+
+#[test]
+fn test_lift_thresh_and_multi() {
+    let ms = Miniscript::from_str("thresh(2,pk(A),s:pk(B),snl:older(144))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(
+        ms.lift(),
+        Some(Policy::Thresh(
+            2,
+            vec![key("A"), key("B"), Policy::Older(144)]
+        ))
+    );
+
+    let ms = Miniscript::from_str("andor(multi(2,A,B,C),pk(D),pkh(E))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(
+        ms.lift(),
+        Some(Policy::Or(vec![
+            Policy::And(vec![
+                Policy::Thresh(2, vec![key("A"), key("B"), key("C")]),
+                key("D"),
+            ]),
+            key("E"),
+        ]))
+    );
+}
+
+#[test]
+fn test_lift_drops_wrapper_sugar() {
+    // l:X is or_i(0,X), whose unsatisfiable branch adds nothing
+    let ms = Miniscript::from_str("andor(pk(A),pk(B),l:older(144))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(
+        ms.lift(),
+        Some(Policy::Or(vec![
+            Policy::And(vec![key("A"), key("B")]),
+            Policy::Older(144),
+        ]))
+    );
+    assert_eq!(
+        ms.to_policy_string().as_deref(),
+        Some("or(and(pk(A),pk(B)),older(144))")
+    );
+
+    // t:X is and_v(X,1), whose trivial condition adds nothing
+    let ms = Miniscript::from_str("or_d(pk(A),t:v:pk(B))", Context::Wsh).expect("should parse");
+    assert_eq!(ms.lift(), Some(Policy::Or(vec![key("A"), key("B")])));
+}
+
+#[test]
+fn test_lift_timelock_mix_fails() {
+    let ms = Miniscript::from_str("and_b(after(100),a:after(1000000000))", Context::Wsh)
+        .expect("should parse");
+    assert!(ms.lift().is_none());
+}
+
+#[test]
+fn test_classify_templates() {
+    let classify = |s: &str| {
+        Miniscript::from_str(s, Context::Wsh)
+            .expect("should parse")
+            .classify()
+    };
+
+    assert_eq!(classify("pk(A)"), Some(Template::SingleSig));
+    assert_eq!(
+        classify("multi(2,A,B,C)"),
+        Some(Template::Multisig { k: 2, n: 3 })
+    );
+    assert_eq!(
+        classify("or_i(multi(2,A,B,C),and_v(v:pk(R),after(52560)))"),
+        Some(Template::TimelockRecovery)
+    );
+    assert_eq!(
+        classify("or_d(pk(A),and_v(v:pkh(H),older(52560)))"),
+        Some(Template::Inheritance)
+    );
+    assert_eq!(
+        classify(
+            "and_v(v:pk(A),sha256(0000000000000000000000000000000000000000000000000000000000000000))"
+        ),
+        Some(Template::Other)
+    );
+}
+
+#[test]
+fn test_to_policy_string() {
+    let policy_string = |s: &str| {
+        Miniscript::from_str(s, Context::Wsh)
+            .expect("should parse")
+            .to_policy_string()
+    };
+
+    assert_eq!(
+        policy_string("or_i(pk(A),and_v(v:pk(B),after(100)))").as_deref(),
+        Some("or(pk(A),and(pk(B),after(100)))")
+    );
+    assert_eq!(
+        policy_string("andor(multi(2,A,B,C),pk(D),older(144))").as_deref(),
+        Some("or(and(thresh(2,pk(A),pk(B),pk(C)),pk(D)),older(144))")
+    );
+    assert_eq!(
+        policy_string(
+            "and_v(v:pk(A),sha256(0101010101010101010101010101010101010101010101010101010101010101))"
+        )
+        .as_deref(),
+        Some("and(pk(A),sha256(0101010101010101010101010101010101010101010101010101010101010101))")
+    );
+
+    // Unliftable miniscripts have no policy string
+    assert_eq!(policy_string("and_b(after(100),a:after(1000000000))"), None);
+
+    // Wider conjunctions use thresh, as and/or take two arguments
+    assert_eq!(
+        Policy::And(vec![key("A"), key("B"), key("C")]).to_string(),
+        "thresh(3,pk(A),pk(B),pk(C))"
+    );
+    assert_eq!(Policy::Trivial.to_string(), "TRIVIAL");
+}
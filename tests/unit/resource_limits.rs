@@ -380,3 +380,200 @@ fn test_deeply_nested_script() {
         assert!(ms.get_stack_size().is_some(), "Should have stack size");
     }
 }
+
+#[test]
+fn test_spend_cost_multisig_vs_and_v() {
+    let multi = Miniscript::from_str("multi(2,A,B,C)", Context::Wsh).expect("should parse");
+    let and_v = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+
+    let multi_cost = multi
+        .spend_cost(10.0, true)
+        .expect("multi should have a cost");
+    let and_v_cost = and_v
+        .spend_cost(10.0, true)
+        .expect("and_v should have a cost");
+
+    // multi needs the extra CHECKMULTISIG dummy element on top of two signatures
+    assert!(
+        multi_cost > and_v_cost,
+        "multi(2,A,B,C) ({multi_cost}) should cost more than and_v ({and_v_cost})"
+    );
+
+    // Cost is a whole number of vbytes times the fee rate
+    let weight = multi.max_satisfaction_size().unwrap();
+    assert!(
+        (multi.spend_cost(1.0, true).unwrap() - weight.div_ceil(4) as f64).abs() < f64::EPSILON
+    );
+}
+
+#[test]
+fn test_spend_cost_use_max_sig() {
+    // Eight signatures, so that one byte each changes the vsize
+    let keys: Vec<String> = (0..8).map(|i| format!("K{i}")).collect();
+    for (context, fragment) in [(Context::Wsh, "multi"), (Context::Tapscript, "multi_a")] {
+        let ms = Miniscript::from_str(&format!("{fragment}(8,{})", keys.join(",")), context)
+            .expect("should parse");
+        let max = ms.spend_cost(1.0, true).expect("max cost");
+        let min = ms.spend_cost(1.0, false).expect("min cost");
+        // One byte less per signature: 8 weight units, 2 vbytes
+        assert!(
+            (max - min - 2.0).abs() < f64::EPSILON,
+            "{context:?}: {max} vs {min}"
+        );
+    }
+}
+
+#[test]
+fn test_resource_usage_matches_checks() {
+    init_testdata();
+    let testdata = get_testdata();
+
+    // thresh with 70 keys: over the 201 ops limit in P2WSH, but within the
+    // stack item and script size limits
+    let mut ms_str = String::from("thresh(35");
+    for i in 0..70 {
+        let key = hex::encode(testdata.pubkeys[i].to_bytes());
+        if i == 0 {
+            let _ = write!(ms_str, ",pk({key})");
+        } else {
+            let _ = write!(ms_str, ",s:pk({key})");
+        }
+    }
+    ms_str.push(')');
+
+    for ctx in [Context::Wsh, Context::Tapscript] {
+        let ms = Miniscript::from_str(&ms_str, ctx).expect("should parse");
+        let usage = ms.resource_usage();
+
+        assert_eq!(usage.ops.within_limit, ms.check_ops_limit());
+        assert_eq!(
+            usage.stack_size.within_limit && usage.exec_stack_size.within_limit,
+            ms.check_stack_size()
+        );
+        assert_eq!(usage.ops.used, ms.get_ops().map(|n| n as usize));
+        assert_eq!(usage.script_size.used, ms.get_script_size());
+        assert!(usage.script_size.within_limit);
+    }
+
+    let ms = Miniscript::from_str(&ms_str, Context::Wsh).unwrap();
+    let usage = ms.resource_usage();
+    assert_eq!(usage.ops.limit, Some(201));
+    assert!(!usage.ops.within_limit);
+    assert!(!usage.within_limits());
+
+    let ms = Miniscript::from_str(&ms_str, Context::Tapscript).unwrap();
+    assert_eq!(ms.resource_usage().ops.limit, None);
+}
+
+#[test]
+fn test_is_standard_per_context() {
+    init_testdata();
+    let testdata = get_testdata();
+
+    // 70 keys exceed the P2WSH ops limit but are fine in Tapscript
+    let mut ms_str = String::from("thresh(35");
+    for i in 0..70 {
+        let key = hex::encode(testdata.pubkeys[i].to_bytes());
+        let wrapper = if i == 0 { "" } else { "s:" };
+        let _ = write!(ms_str, ",{wrapper}pk({key})");
+    }
+    ms_str.push(')');
+
+    let wsh = Miniscript::from_str(&ms_str, Context::Wsh).expect("should parse");
+    assert!(!wsh.is_standard());
+    assert!(!wsh.check_ops_limit());
+
+    let tap = Miniscript::from_str(&ms_str, Context::Tapscript).expect("should parse");
+    assert!(tap.is_standard());
+    assert!(tap.get_static_ops().is_some());
+
+    // A small threshold is standard in both contexts
+    let small = format!(
+        "thresh(2,pk({}),s:pk({}),s:pk({}))",
+        hex::encode(testdata.pubkeys[0].to_bytes()),
+        hex::encode(testdata.pubkeys[1].to_bytes()),
+        hex::encode(testdata.pubkeys[2].to_bytes()),
+    );
+    for ctx in [Context::Wsh, Context::Tapscript] {
+        let ms = Miniscript::from_str(&small, ctx).expect("should parse");
+        assert!(ms.is_standard(), "{ctx}");
+    }
+}
+
+#[test]
+fn test_satisfaction_size_with_sig_len() {
+    init_testdata();
+    let testdata = get_testdata();
+
+    let keys: Vec<String> = (0..5)
+        .map(|i| hex::encode(testdata.pubkeys[i].to_bytes()))
+        .collect();
+    let ms_str = format!("multi(3,{})", keys.join(","));
+    let ms = Miniscript::from_str(&ms_str, Context::Wsh).expect("should parse");
+
+    // Standard high-R ECDSA signatures match the default estimate
+    assert_eq!(ms.satisfaction_size_with(72), ms.max_satisfaction_size());
+
+    // Three signatures, so each extra signature byte adds three bytes
+    let low_r = ms.satisfaction_size_with(71).unwrap();
+    let high_r = ms.satisfaction_size_with(72).unwrap();
+    assert_eq!(high_r - low_r, 3);
+    // Dummy element plus three length-prefixed signatures
+    assert_eq!(low_r, 1 + 3 * (1 + 71));
+
+    // Unsatisfiable miniscripts have no size
+    let never = Miniscript::from_str("0", Context::Wsh).expect("should parse");
+    assert_eq!(never.satisfaction_size_with(72), None);
+}
+
+#[test]
+fn test_exceeds_standard_limits() {
+    use miniscript_core_ffi::{LimitKind, LimitViolation};
+
+    init_testdata();
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+    assert_eq!(ms.exceeds_standard_limits(), None);
+
+    // Each level of thresh(2,pk(A),s:pk(B),a:...) adds about 8 ops
+    let mut ms_str = "pk(X)".to_string();
+    for i in 0..30 {
+        ms_str = format!("thresh(2,pk(A{i}),s:pk(B{i}),a:{ms_str})");
+    }
+    let ms = Miniscript::from_str(&ms_str, Context::Wsh).expect("should parse");
+    let ops = ms.get_ops().expect("should count ops") as usize;
+    assert!(ops > 201);
+
+    let violations = ms
+        .exceeds_standard_limits()
+        .expect("should exceed the ops limit");
+    assert!(violations.contains(&LimitViolation {
+        kind: LimitKind::Ops,
+        measured: ops,
+        max: 201,
+    }));
+    assert!(!ms.is_standard());
+}
+
+#[test]
+fn test_context_limits() {
+    let wsh = Context::Wsh.limits();
+    assert_eq!(wsh.max_script_size, 3600);
+    assert_eq!(wsh.max_ops, Some(201));
+    assert_eq!(wsh.max_witness_items, Some(100));
+    assert_eq!(wsh.max_exec_stack_size, None);
+
+    let tap = Context::Tapscript.limits();
+    assert!(tap.max_script_size > wsh.max_script_size);
+    assert_eq!(tap.max_ops, None);
+    assert_eq!(tap.max_witness_items, None);
+    assert_eq!(tap.max_exec_stack_size, Some(1000));
+
+    // resource_usage() checks against the same limits
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+    let usage = ms.resource_usage();
+    assert_eq!(usage.ops.limit, wsh.max_ops);
+    assert_eq!(usage.stack_size.limit, wsh.max_witness_items);
+    assert_eq!(usage.exec_stack_size.limit, wsh.max_exec_stack_size);
+    assert_eq!(usage.script_size.limit, Some(wsh.max_script_size));
+}
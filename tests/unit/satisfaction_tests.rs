@@ -3,7 +3,10 @@
 //! These tests verify the `Satisfy()` functionality that produces witness stacks
 //! for spending miniscript-based outputs.
 
-use miniscript_core_ffi::{Availability, Context, Miniscript, Satisfier, SimpleSatisfier};
+use miniscript_core_ffi::{
+    Availability, Context, DummyKeyMap, Error, Miniscript, Satisfier, SatisfyResult,
+    SatisfyWarning, SimpleSatisfier,
+};
 
 /// Test that `SimpleSatisfier` can be created and used
 #[test]
@@ -35,8 +38,8 @@ fn test_satisfy_pk_with_signature() {
     let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
 
     let mut satisfier = SimpleSatisfier::new();
-    // The key "A" gets converted to 33 zero bytes in WSH context
-    let key_bytes = vec![0u8; 33];
+    // Signatures are keyed by the public key "A" stands for in WSH
+    let key_bytes = DummyKeyMap::new().pubkey_for("A", Context::Wsh);
     let dummy_sig = vec![0x30, 0x44, 0x02, 0x20]; // Partial DER signature prefix
     satisfier.signatures.insert(key_bytes, dummy_sig);
 
@@ -69,6 +72,30 @@ fn test_satisfy_sha256_preimage() {
     println!("SHA256 satisfaction result: {result:?}");
 }
 
+/// Test that a preimage of the wrong length is rejected instead of producing a bad witness
+#[test]
+fn test_satisfy_sha256_short_preimage() {
+    let hash_hex = "0000000000000000000000000000000000000000000000000000000000000001";
+    let ms_str = format!("sha256({hash_hex})");
+    let ms = Miniscript::from_str(&ms_str, Context::Wsh).expect("should parse");
+
+    let mut satisfier = SimpleSatisfier::new();
+    let hash = hex::decode(hash_hex).unwrap();
+    satisfier
+        .sha256_preimages
+        .insert(hash.clone(), vec![0x42; 31]);
+
+    let result = ms
+        .satisfy(satisfier, false)
+        .expect("satisfy should not error");
+
+    assert_eq!(result.availability, Availability::No);
+    assert_eq!(
+        result.warnings,
+        vec![SatisfyWarning::InvalidPreimageLength { hash, len: 31 }]
+    );
+}
+
 /// Test satisfying a timelock miniscript
 #[test]
 fn test_satisfy_after_timelock() {
@@ -102,10 +129,15 @@ fn test_satisfy_older_timelock() {
 fn test_satisfy_and_v() {
     let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
 
+    let keys = DummyKeyMap::new();
     let mut satisfier = SimpleSatisfier::new();
-    let key_bytes = vec![0u8; 33];
     let dummy_sig = vec![0x30, 0x44, 0x02, 0x20];
-    satisfier.signatures.insert(key_bytes, dummy_sig);
+    satisfier
+        .signatures
+        .insert(keys.pubkey_for("A", Context::Wsh), dummy_sig.clone());
+    satisfier
+        .signatures
+        .insert(keys.pubkey_for("B", Context::Wsh), dummy_sig);
 
     let result = ms
         .satisfy(satisfier, true)
@@ -157,7 +189,7 @@ fn test_satisfy_tapscript_pk() {
 
     let mut satisfier = SimpleSatisfier::new();
     // In Tapscript, keys are 32 bytes (x-only)
-    let key_bytes = vec![0u8; 32];
+    let key_bytes = DummyKeyMap::new().pubkey_for("A", Context::Tapscript);
     let dummy_schnorr_sig = vec![0u8; 64]; // Schnorr signatures are 64 bytes
     satisfier.signatures.insert(key_bytes, dummy_schnorr_sig);
 
@@ -297,6 +329,50 @@ fn test_witness_stack_structure() {
     }
 }
 
+/// Test that a P2WSH witness ends with the witness script
+#[test]
+fn test_to_wsh_witness() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+
+    let result = ms
+        .satisfy(AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(result.availability, Availability::Yes);
+
+    let witness = result.to_wsh_witness(&ms).expect("should build witness");
+    assert_eq!(witness.len(), result.stack.len() + 1);
+    assert_eq!(
+        witness.last(),
+        Some(ms.to_script_bytes().unwrap().as_slice())
+    );
+
+    // Tapscript spends need a control block as well
+    let tap = Miniscript::from_str("pk(A)", Context::Tapscript).expect("should parse");
+    let result = tap
+        .satisfy(AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert!(result.to_wsh_witness(&tap).is_none());
+}
+
+/// Test setting a transaction input's witness from a satisfaction
+#[test]
+fn test_apply_to_txin() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+    let result = ms
+        .satisfy(AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+
+    let mut txin = bitcoin::TxIn::default();
+    txin.witness.push([0xff]);
+    result.apply_to_txin(&mut txin, None);
+    assert_eq!(txin.witness.len(), result.stack.len());
+    assert_eq!(txin.witness, result.to_witness());
+
+    let script = ms.to_script().expect("should build script");
+    result.apply_to_txin(&mut txin, Some(&script));
+    assert_eq!(Some(txin.witness), result.to_wsh_witness(&ms));
+}
+
 /// Custom satisfier implementation test
 struct AlwaysYesSatisfier;
 
@@ -402,3 +478,827 @@ fn test_maybe_satisfier_for_size_estimation() {
         "MAYBE satisfaction should produce a witness stack"
     );
 }
+
+/// The `Satisfier` method that `PanickingSatisfier` panics in
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PanicIn {
+    Sign,
+    CheckAfter,
+    Sha256,
+}
+
+/// A satisfier that panics in one method and signs with a dummy signature otherwise
+struct PanickingSatisfier(PanicIn);
+
+impl Satisfier for PanickingSatisfier {
+    fn sign(&self, _key: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        assert!(self.0 != PanicIn::Sign, "signing device disconnected");
+        (Availability::Yes, Some(vec![0x30; 72]))
+    }
+
+    fn check_after(&self, _value: u32) -> bool {
+        assert!(self.0 != PanicIn::CheckAfter, "chain tip unavailable");
+        true
+    }
+
+    fn check_older(&self, _value: u32) -> bool {
+        true
+    }
+
+    fn sat_sha256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        assert!(self.0 != PanicIn::Sha256, "preimage store unavailable");
+        (Availability::Yes, Some(vec![0x42; 32]))
+    }
+
+    fn sat_ripemd160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+
+    fn sat_hash256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+
+    fn sat_hash160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+}
+
+/// Satisfy `ms_str` with a satisfier panicking in `panic_in` and return the error
+fn satisfy_panicking(ms_str: &str, panic_in: PanicIn) -> Error {
+    let ms = Miniscript::from_str(ms_str, Context::Wsh).expect("should parse");
+    ms.satisfy(PanickingSatisfier(panic_in), false)
+        .expect_err("a panicking satisfier should produce an error")
+}
+
+/// Test that a panicking `sign` produces an error instead of unwinding into C++
+#[test]
+fn test_satisfy_panicking_sign() {
+    let err = satisfy_panicking("pk(A)", PanicIn::Sign);
+    assert_eq!(err, Error::Satisfaction("satisfier panicked".to_string()));
+}
+
+/// Test that a panicking `check_after` produces an error
+#[test]
+fn test_satisfy_panicking_check_after() {
+    let err = satisfy_panicking("and_v(v:pk(A),after(100))", PanicIn::CheckAfter);
+    assert_eq!(err, Error::Satisfaction("satisfier panicked".to_string()));
+}
+
+/// Test that a panicking preimage callback produces an error
+#[test]
+fn test_satisfy_panicking_preimage() {
+    let err = satisfy_panicking(
+        "and_v(v:pk(A),sha256(0000000000000000000000000000000000000000000000000000000000000001))",
+        PanicIn::Sha256,
+    );
+    assert_eq!(err, Error::Satisfaction("satisfier panicked".to_string()));
+}
+
+/// A buggy satisfier claiming signatures are available without returning them
+struct SignatureLessSatisfier;
+
+impl Satisfier for SignatureLessSatisfier {
+    fn sign(&self, _key: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::Yes, None)
+    }
+
+    fn check_after(&self, _value: u32) -> bool {
+        false
+    }
+
+    fn check_older(&self, _value: u32) -> bool {
+        false
+    }
+
+    fn sat_sha256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+
+    fn sat_ripemd160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+
+    fn sat_hash256(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+
+    fn sat_hash160(&self, _hash: &[u8]) -> (Availability, Option<Vec<u8>>) {
+        (Availability::No, None)
+    }
+}
+
+/// Test that `(Yes, None)` from `sign` is treated as unavailable
+#[test]
+fn test_satisfy_signature_missing_bytes() {
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+
+    let result = ms
+        .satisfy(SignatureLessSatisfier, true)
+        .expect("satisfy should not error");
+
+    assert_eq!(result.availability, Availability::No);
+    assert!(matches!(
+        result.warnings.as_slice(),
+        [SatisfyWarning::MissingSignature { .. }]
+    ));
+}
+
+/// Test hex-encoding of a witness stack and its `Debug` output
+#[test]
+fn test_satisfy_result_hex_stack() {
+    let result = SatisfyResult {
+        availability: Availability::Yes,
+        stack: vec![vec![0xde, 0xad, 0xbe, 0xef], vec![]],
+        warnings: Vec::new(),
+    };
+
+    assert_eq!(
+        result.to_hex_stack(),
+        vec!["deadbeef".to_string(), String::new()]
+    );
+    assert!(format!("{result:?}").contains("element_lens: [4, 0]"));
+}
+
+/// Test that repeated satisfactions of the same miniscript keep succeeding
+#[test]
+fn test_repeated_satisfactions() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),or_d(pk(B),older(144)))", Context::Wsh)
+        .expect("should parse");
+
+    let first = ms
+        .satisfy(AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(first.availability, Availability::Yes);
+
+    for _ in 0..1000 {
+        let result = ms
+            .satisfy(AlwaysYesSatisfier, true)
+            .expect("satisfy should not error");
+        assert_eq!(result.availability, Availability::Yes);
+        assert_eq!(result.stack, first.stack);
+    }
+}
+
+/// Time 10k satisfactions.
+///
+/// Satisfaction performs no secp256k1 operations (signatures come from the
+/// `Satisfier`), so the per-call cost is dominated by the FFI round-trips
+/// and witness allocation. Run with `cargo test -- --ignored --nocapture`.
+#[test]
+#[ignore = "benchmark"]
+fn bench_satisfy_10k() {
+    let ms = Miniscript::from_str("and_v(v:pk(A),or_d(pk(B),older(144)))", Context::Wsh)
+        .expect("should parse");
+
+    let start = std::time::Instant::now();
+    for _ in 0..10_000 {
+        let result = ms
+            .satisfy(AlwaysYesSatisfier, true)
+            .expect("satisfy should not error");
+        assert_eq!(result.availability, Availability::Yes);
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "10k satisfactions: {elapsed:?} ({:?} per call)",
+        elapsed / 10_000
+    );
+}
+
+/// Test satisfying several miniscripts with one shared satisfier
+#[test]
+fn test_satisfy_many() {
+    let items = [
+        Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse"),
+        Miniscript::from_str("and_v(v:pk(A),after(100))", Context::Wsh).expect("should parse"),
+        Miniscript::from_str("or_d(pk(B),older(144))", Context::Wsh).expect("should parse"),
+    ];
+
+    let results = Miniscript::satisfy_many(&items, &AlwaysYesSatisfier, true);
+    assert_eq!(results.len(), items.len());
+
+    for (ms, result) in items.iter().zip(&results) {
+        let result = result.as_ref().expect("satisfy should not error");
+        assert_eq!(result.availability, Availability::Yes);
+
+        let single = ms
+            .satisfy(AlwaysYesSatisfier, true)
+            .expect("satisfy should not error");
+        assert_eq!(result.stack, single.stack);
+    }
+}
+
+/// Test that zeroizing a satisfier wipes its secrets and satisfaction results
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_zeroize_satisfier() {
+    use zeroize::Zeroize;
+
+    let hash_hex = "0000000000000000000000000000000000000000000000000000000000000001";
+    let ms_str = format!("and_v(v:pk(A),sha256({hash_hex}))");
+    let ms = Miniscript::from_str(&ms_str, Context::Wsh).expect("should parse");
+
+    let mut satisfier = SimpleSatisfier::new();
+    satisfier.signatures.insert(b"A".to_vec(), vec![0x30; 72]);
+    satisfier
+        .sha256_preimages
+        .insert(hex::decode(hash_hex).unwrap(), vec![0x42; 32]);
+
+    let mut result = Miniscript::satisfy_many(std::slice::from_ref(&ms), &satisfier, true)
+        .pop()
+        .expect("one result")
+        .expect("satisfy should not error");
+    assert_eq!(result.availability, Availability::Yes);
+    assert!(result.stack.contains(&vec![0x42; 32]));
+
+    result.zeroize();
+    assert!(result.stack.is_empty());
+
+    satisfier.zeroize();
+    assert!(satisfier.signatures.is_empty());
+    assert!(satisfier.sha256_preimages.is_empty());
+}
+
+/// Allocator that reports freed blocks still holding a test secret
+#[cfg(feature = "zeroize")]
+mod wipe_check {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Length and byte of the test secret; only blocks of this length are checked
+    pub const SECRET_LEN: usize = 37;
+    pub const SECRET_BYTE: u8 = 0xa5;
+
+    /// Number of freed blocks that still held the test secret
+    pub static SECRETS_FREED: AtomicUsize = AtomicUsize::new(0);
+
+    struct CheckingAllocator;
+
+    unsafe impl GlobalAlloc for CheckingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            unsafe { System.alloc_zeroed(layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            if layout.size() == SECRET_LEN {
+                // SAFETY: the block is still allocated and SECRET_LEN bytes long
+                let block = unsafe { std::slice::from_raw_parts(ptr, SECRET_LEN) };
+                if block.iter().all(|&b| b == SECRET_BYTE) {
+                    SECRETS_FREED.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CheckingAllocator = CheckingAllocator;
+}
+
+/// Test that dropping a satisfier wipes its secrets before freeing them
+#[cfg(feature = "zeroize")]
+#[test]
+fn test_satisfier_wiped_on_drop() {
+    use std::sync::atomic::Ordering;
+    use wipe_check::{SECRET_BYTE, SECRET_LEN, SECRETS_FREED};
+
+    // A plain vector is freed with the secret still in it
+    let before = SECRETS_FREED.load(Ordering::SeqCst);
+    drop(std::hint::black_box(vec![SECRET_BYTE; SECRET_LEN]));
+    assert_eq!(SECRETS_FREED.load(Ordering::SeqCst), before + 1);
+
+    let mut satisfier = SimpleSatisfier::new();
+    satisfier
+        .sha256_preimages
+        .insert(vec![0; 32], vec![SECRET_BYTE; SECRET_LEN]);
+    satisfier
+        .signatures
+        .insert(b"A".to_vec(), vec![SECRET_BYTE; SECRET_LEN]);
+
+    let before = SECRETS_FREED.load(Ordering::SeqCst);
+    drop(satisfier);
+    assert_eq!(SECRETS_FREED.load(Ordering::SeqCst), before);
+}
+
+/// Test forcing a specific spend path regardless of which one is cheapest
+#[test]
+fn test_satisfy_branch() {
+    let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(100)))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(ms.spend_path_count(), Some(2));
+
+    // Unconstrained, the cheaper pk(A) branch is taken (OP_IF selector 1)
+    let cheapest = ms
+        .satisfy(AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(cheapest.stack.last(), Some(&vec![1]));
+
+    // Branch 1 is the timelocked pk(B) path (OP_IF selector empty)
+    let recovery = ms
+        .satisfy_branch(1, AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(recovery.availability, Availability::Yes);
+    assert_eq!(recovery.stack.len(), 2);
+    assert_eq!(recovery.stack.last(), Some(&Vec::new()));
+
+    let primary = ms
+        .satisfy_branch(0, AlwaysYesSatisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(primary.stack, cheapest.stack);
+
+    assert!(ms.satisfy_branch(2, AlwaysYesSatisfier, true).is_err());
+}
+
+/// Test that an out-of-range branch is rejected without walking the paths
+#[test]
+fn test_satisfy_branch_out_of_range() {
+    // 16-of-32 has C(32, 16) = 601080390 paths
+    let keys: Vec<String> = (0..32).map(|i| format!("K{i}")).collect();
+    let ms = Miniscript::from_str(
+        &format!("multi_a(16,{})", keys.join(",")),
+        Context::Tapscript,
+    )
+    .expect("should parse");
+    let count = ms.spend_path_count().expect("count should not overflow");
+    assert_eq!(count, 601_080_390);
+
+    let err = ms
+        .satisfy_branch(count, AlwaysYesSatisfier, true)
+        .unwrap_err();
+    assert!(err.to_string().contains("out of range"), "{err}");
+    assert!(
+        ms.satisfy_branch(usize::MAX, AlwaysYesSatisfier, true)
+            .is_err()
+    );
+}
+
+/// Test checking spendability from the set of held keys alone
+#[test]
+fn test_can_satisfy_with_keys() {
+    let either = Miniscript::from_str("or_i(pk(A),pk(B))", Context::Wsh).expect("should parse");
+    let both = Miniscript::from_str("and_v(v:pk(A),pk(B))", Context::Wsh).expect("should parse");
+
+    assert!(either.can_satisfy_with_keys(&[b"A"]));
+    assert!(!both.can_satisfy_with_keys(&[b"A"]));
+    assert!(both.can_satisfy_with_keys(&[b"A", b"B"]));
+    assert!(!either.can_satisfy_with_keys(&[b"C"]));
+    assert!(!either.can_satisfy_with_keys(&[]));
+
+    // Timelocks are treated as unavailable
+    let timelocked =
+        Miniscript::from_str("and_v(v:pk(A),after(100))", Context::Wsh).expect("should parse");
+    assert!(!timelocked.can_satisfy_with_keys(&[b"A"]));
+}
+
+/// Test building a satisfier from a PSBT input's signatures and preimages
+#[test]
+fn test_satisfier_from_psbt_input() {
+    use bitcoin::hashes::{Hash, sha256};
+    use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[1u8; 32]).unwrap();
+    let pubkey = bitcoin::PublicKey::new(secret.public_key(&secp));
+    let sig = bitcoin::ecdsa::Signature::sighash_all(
+        secp.sign_ecdsa(&Message::from_digest([2u8; 32]), &secret),
+    );
+    let preimage = vec![0x42; 32];
+    let hash = sha256::Hash::hash(&preimage);
+
+    let mut input = bitcoin::psbt::Input::default();
+    input.partial_sigs.insert(pubkey, sig);
+    input.sha256_preimages.insert(hash, preimage.clone());
+
+    let satisfier = SimpleSatisfier::from_psbt_input(&input, None);
+    assert_eq!(
+        satisfier.signatures.get(&pubkey.to_bytes()),
+        Some(&sig.to_vec())
+    );
+    assert_eq!(
+        satisfier
+            .sha256_preimages
+            .get(hash.as_byte_array().as_slice()),
+        Some(&preimage)
+    );
+
+    let ms = Miniscript::from_str(
+        &format!("and_v(v:pk({pubkey}),sha256({hash}))"),
+        Context::Wsh,
+    )
+    .expect("should parse");
+    let result = ms
+        .satisfy(satisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(result.availability, Availability::Yes);
+    assert!(result.stack.contains(&sig.to_vec()));
+    assert!(result.stack.contains(&preimage));
+}
+
+/// Test that only the signatures for the satisfied leaf are taken
+#[test]
+fn test_satisfier_from_psbt_input_two_leaves() {
+    use bitcoin::TapLeafHash;
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{Keypair, Message, Secp256k1};
+
+    let secp = Secp256k1::new();
+    let keypair = Keypair::from_seckey_slice(&secp, &[1u8; 32]).unwrap();
+    let (xonly, _) = keypair.x_only_public_key();
+    let leaf_sig = |digest: u8| bitcoin::taproot::Signature {
+        signature: secp.sign_schnorr_no_aux_rand(&Message::from_digest([digest; 32]), &keypair),
+        sighash_type: bitcoin::TapSighashType::Default,
+    };
+
+    // The same key signs two leaves, with a different signature for each
+    let leaf_a = TapLeafHash::from_byte_array([0xaa; 32]);
+    let leaf_b = TapLeafHash::from_byte_array([0xbb; 32]);
+    let (sig_a, sig_b) = (leaf_sig(1), leaf_sig(2));
+    let mut input = bitcoin::psbt::Input::default();
+    input.tap_script_sigs.insert((xonly, leaf_a), sig_a);
+    input.tap_script_sigs.insert((xonly, leaf_b), sig_b);
+
+    let key = xonly.serialize().to_vec();
+    let satisfier = SimpleSatisfier::from_psbt_input(&input, Some(leaf_a));
+    assert_eq!(satisfier.signatures.get(&key), Some(&sig_a.to_vec()));
+    let satisfier = SimpleSatisfier::from_psbt_input(&input, Some(leaf_b));
+    assert_eq!(satisfier.signatures.get(&key), Some(&sig_b.to_vec()));
+
+    let satisfier = SimpleSatisfier::from_psbt_input(&input, None);
+    assert!(satisfier.signatures.is_empty());
+    let satisfier =
+        SimpleSatisfier::from_psbt_input(&input, Some(TapLeafHash::from_byte_array([0; 32])));
+    assert!(satisfier.signatures.is_empty());
+}
+
+#[test]
+fn test_insert_signature_key_length() {
+    let compressed = [0x02; 33];
+    let xonly = [0x02; 32];
+    let mut satisfier = SimpleSatisfier::new();
+
+    // Correct lengths for each context
+    satisfier
+        .insert_signature(&compressed, vec![0x30; 72], Context::Wsh)
+        .expect("33-byte key is valid in P2WSH");
+    satisfier
+        .insert_signature(&xonly, vec![0x01; 64], Context::Tapscript)
+        .expect("32-byte key is valid in Tapscript");
+    assert_eq!(satisfier.signatures.len(), 2);
+    assert_eq!(satisfier.signatures.get(&xonly[..]), Some(&vec![0x01; 64]));
+
+    // Mismatched lengths are rejected and leave the satisfier unchanged
+    let err = satisfier
+        .insert_signature(&xonly, vec![0x30; 72], Context::Wsh)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        Error::InvalidKeyLength {
+            context: Context::Wsh,
+            len: 32,
+            expected: 33,
+        }
+    );
+    assert_eq!(err.code(), None);
+
+    let err = satisfier
+        .insert_signature(&compressed, vec![0x01; 64], Context::Tapscript)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::InvalidKeyLength {
+            context: Context::Tapscript,
+            len: 33,
+            expected: 32,
+        }
+    ));
+    assert_eq!(satisfier.signatures.len(), 2);
+}
+
+#[test]
+fn test_malleability_for() {
+    use miniscript_core_ffi::Malleability;
+
+    let ms = Miniscript::from_str("or_b(pk(A),s:pk(B))", Context::Wsh).expect("should parse");
+
+    // With both signatures there are two satisfactions, but both carry a
+    // signature a third party cannot forge, so Core picks a non-malleable one
+    let keys = DummyKeyMap::new();
+    let mut both = SimpleSatisfier::new();
+    for name in ["A", "B"] {
+        both.signatures
+            .insert(keys.pubkey_for(name, Context::Wsh), vec![0x30; 72]);
+    }
+    assert_eq!(ms.malleability_for(both), Malleability::NonMalleable);
+
+    assert_eq!(
+        ms.malleability_for(SimpleSatisfier::new()),
+        Malleability::Unsatisfiable
+    );
+
+    // A timelock alone can be satisfied, but the witness has no signature
+    let ms = Miniscript::from_str("older(144)", Context::Wsh).expect("should parse");
+    let mut satisfier = SimpleSatisfier::new();
+    satisfier.older_satisfied.insert(144);
+    assert_eq!(ms.malleability_for(satisfier), Malleability::Malleable);
+}
+
+#[test]
+fn test_satisfaction_weights() {
+    let ms = Miniscript::from_str("or_b(pk(A),s:pk(B))", Context::Wsh).expect("should parse");
+
+    let keys = DummyKeyMap::new();
+    let mut both = SimpleSatisfier::new();
+    for name in ["A", "B"] {
+        both.signatures
+            .insert(keys.pubkey_for(name, Context::Wsh), vec![0x30; 72]);
+    }
+    let (nonmalleable, malleable) = ms.satisfaction_weights(&both);
+    let nonmalleable = nonmalleable.expect("non-malleable satisfaction");
+    let malleable = malleable.expect("malleable satisfaction");
+    assert!(malleable <= nonmalleable);
+
+    // At least the element count, one signature and one empty dissatisfaction
+    let signature_and_dissat = 1 + (1 + 72) + 1;
+    assert!(nonmalleable >= signature_and_dissat);
+
+    assert_eq!(
+        ms.satisfaction_weights(&SimpleSatisfier::new()),
+        (None, None)
+    );
+}
+
+#[test]
+fn test_worst_case_branch_weight() {
+    let ms =
+        Miniscript::from_str("or_i(pk(A),multi(3,B,C,D,E,F))", Context::Wsh).expect("should parse");
+
+    // pk(A) branch: count, 72-byte signature, 0x01 selector
+    let pk_branch = 1 + (1 + 72) + (1 + 1);
+    // multi branch: count, empty dummy, three signatures, empty selector
+    let multi_branch = 1 + 1 + 3 * (1 + 72) + 1;
+    assert!(multi_branch > pk_branch);
+    assert_eq!(ms.worst_case_branch_weight(), Some(multi_branch));
+
+    let ms = Miniscript::from_str("pk(A)", Context::Tapscript).expect("should parse");
+    assert_eq!(ms.worst_case_branch_weight(), Some(1 + (1 + 65)));
+
+    // 0 has no satisfaction
+    let ms = Miniscript::from_str("and_b(0,a:pk(A))", Context::Wsh).expect("should parse");
+    assert_eq!(ms.worst_case_branch_weight(), None);
+}
+
+/// Test that too many spend paths give no weight rather than a partial maximum
+#[test]
+fn test_worst_case_branch_weight_too_many_paths() {
+    use miniscript_core_ffi::MAX_SPEND_PATHS;
+
+    // 2-of-50 has C(50, 2) = 1225 paths
+    let keys: Vec<String> = (0..50).map(|i| format!("K{i}")).collect();
+    let ms = Miniscript::from_str(
+        &format!("multi_a(2,{})", keys.join(",")),
+        Context::Tapscript,
+    )
+    .expect("should parse");
+    assert!(ms.spend_path_count().expect("count") > MAX_SPEND_PATHS);
+    assert_eq!(ms.worst_case_branch_weight(), None);
+
+    // 2-of-40 has C(40, 2) = 780 paths, all of the same weight
+    let ms = Miniscript::from_str(
+        &format!("multi_a(2,{})", keys[..40].join(",")),
+        Context::Tapscript,
+    )
+    .expect("should parse");
+    assert!(ms.spend_path_count().expect("count") <= MAX_SPEND_PATHS);
+    assert!(ms.worst_case_branch_weight().is_some());
+}
+
+#[test]
+fn test_merge_satisfiers() {
+    let ms = Miniscript::from_str("multi(2,A,B)", Context::Wsh).expect("should parse");
+    let keys = DummyKeyMap::new();
+
+    let mut alice = SimpleSatisfier::new();
+    alice
+        .signatures
+        .insert(keys.pubkey_for("A", Context::Wsh), vec![0x30; 72]);
+    let mut bob = SimpleSatisfier::new();
+    bob.signatures
+        .insert(keys.pubkey_for("B", Context::Wsh), vec![0x30; 72]);
+    bob.older_satisfied.insert(144);
+
+    alice.merge(bob).expect("no conflicts");
+    assert_eq!(alice.signatures.len(), 2);
+    assert!(alice.older_satisfied.contains(&144));
+
+    let result = ms.satisfy(alice, true).expect("should satisfy");
+    assert_eq!(result.availability, Availability::Yes);
+
+    // A different signature for the same key is rejected and nothing merges
+    let key = keys.pubkey_for("A", Context::Wsh);
+    let mut ours = SimpleSatisfier::new();
+    ours.signatures.insert(key.clone(), vec![0x30; 72]);
+    let mut theirs = SimpleSatisfier::new();
+    theirs.signatures.insert(key.clone(), vec![0x31; 72]);
+    theirs.after_satisfied.insert(100);
+    assert_eq!(ours.merge(theirs), Err(Error::SatisfierConflict { key }));
+    assert!(ours.after_satisfied.is_empty());
+}
+
+#[test]
+fn test_has_nonmalleable_satisfaction() {
+    for (input, expected) in [
+        ("pk(A)", true),
+        ("and_v(v:pk(A),older(144))", true),
+        // Spending through older(1) needs no signature, but pk(A) is safe
+        ("or_i(pk(A),older(1))", true),
+        // No signature on any path
+        ("older(144)", false),
+        (
+            "sha256(0000000000000000000000000000000000000000000000000000000000000000)",
+            false,
+        ),
+        // Either timelock branch can be swapped for the other
+        ("and_v(v:pk(A),or_i(older(1),older(2)))", false),
+    ] {
+        let ms = Miniscript::from_str(input, Context::Wsh).expect("should parse");
+        assert!(ms.is_valid(), "{input}");
+        assert_eq!(ms.has_nonmalleable_satisfaction(), expected, "{input}");
+    }
+}
+
+/// Test that a miniscript with 2^40 spend paths is answered after a bounded search
+#[test]
+fn test_has_nonmalleable_satisfaction_many_paths() {
+    let mut input = String::from("or_i(older(1),older(2))");
+    for i in 1..40 {
+        input = format!(
+            "and_v(v:or_i(older({}),older({})),{input})",
+            2 * i + 1,
+            2 * i + 2
+        );
+    }
+    let ms = Miniscript::from_str(&format!("and_v(v:pk(A),{input})"), Context::Wsh)
+        .expect("should parse");
+    assert_eq!(ms.spend_path_count(), Some(1 << 40));
+    assert!(!ms.has_nonmalleable_satisfaction());
+}
+
+#[test]
+fn test_witness_executes() {
+    let keys = DummyKeyMap::new();
+
+    for (context, sig_len) in [(Context::Wsh, 72), (Context::Tapscript, 64)] {
+        let ms = Miniscript::from_str("or_d(pk(A),and_v(v:pk(B),older(144)))", context)
+            .expect("should parse");
+
+        let mut satisfier = SimpleSatisfier::new();
+        satisfier
+            .signatures
+            .insert(keys.pubkey_for("B", context), vec![0x30; sig_len]);
+        satisfier.older_satisfied.insert(144);
+        let result = ms.satisfy(satisfier, true).expect("should satisfy");
+        assert_eq!(result.availability, Availability::Yes);
+        assert!(ms.witness_executes(&result.stack).unwrap(), "{context}");
+
+        // An empty signature in place of B's fails OP_CHECKSIGVERIFY
+        let mut corrupted = result.stack.clone();
+        corrupted[0] = Vec::new();
+        assert!(!ms.witness_executes(&corrupted).unwrap(), "{context}");
+
+        // So does one no real signature could be, too short or too long
+        for garbage in [vec![0x30; 5], vec![0x30; 80]] {
+            let mut corrupted = result.stack.clone();
+            corrupted[0] = garbage;
+            assert!(!ms.witness_executes(&corrupted).unwrap(), "{context}");
+        }
+
+        // Missing elements leave the script without enough stack items
+        assert!(
+            !ms.witness_executes(&result.stack[1..]).unwrap(),
+            "{context}"
+        );
+        assert!(!ms.witness_executes(&[]).unwrap(), "{context}");
+    }
+}
+
+#[test]
+fn test_required_signers() {
+    use miniscript_core_ffi::{MAX_SPEND_PATHS, SignerSet};
+
+    let set = |keys: &[&str]| SignerSet {
+        keys: keys.iter().map(|k| k.as_bytes().to_vec()).collect(),
+    };
+
+    let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),pk(C)))", Context::Wsh)
+        .expect("should parse");
+    let signers = ms.required_signers();
+    assert!(!signers.truncated);
+    assert_eq!(signers.sets, vec![set(&["A"]), set(&["B", "C"])]);
+
+    let ms = Miniscript::from_str("thresh(2,pk(A),s:pk(B),s:pk(C))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(
+        ms.required_signers().sets,
+        vec![set(&["A", "B"]), set(&["A", "C"]), set(&["B", "C"])]
+    );
+
+    // The timelock-only recovery path has no signature, so it is not a
+    // non-malleable spend path
+    let ms = Miniscript::from_str("or_d(pk(A),older(144))", Context::Wsh).expect("should parse");
+    assert_eq!(ms.required_signers().sets, vec![set(&["A"])]);
+
+    // thresh(10,...) over 20 keys has far more paths than are examined
+    let keys: Vec<String> = (0..20).map(|i| format!("K{i}")).collect();
+    let ms_str = format!(
+        "thresh(10,pk({}),{})",
+        keys[0],
+        keys[1..]
+            .iter()
+            .map(|k| format!("s:pk({k})"))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let ms = Miniscript::from_str(&ms_str, Context::Tapscript).expect("should parse");
+    let signers = ms.required_signers();
+    assert!(signers.truncated);
+    assert!(signers.sets.len() <= MAX_SPEND_PATHS);
+    assert!(signers.sets.iter().all(|s| s.keys.len() == 10));
+}
+
+#[test]
+fn test_branch_timelocks() {
+    use miniscript_core_ffi::{BranchTimelock, LockTime, RelativeLockTime};
+
+    let ms = Miniscript::from_str("or_i(pk(A),and_v(v:pk(B),after(1000)))", Context::Wsh)
+        .expect("should parse");
+    assert_eq!(
+        ms.branch_timelocks().expect("few paths"),
+        vec![
+            BranchTimelock::default(),
+            BranchTimelock {
+                after: Some(LockTime::from_height(1000).unwrap()),
+                ..BranchTimelock::default()
+            },
+        ]
+    );
+
+    // The strictest lock of a path is the one reported
+    let ms = Miniscript::from_str(
+        "and_v(v:pk(A),and_v(v:older(10),and_v(v:older(144),after(500))))",
+        Context::Wsh,
+    )
+    .expect("should parse");
+    let locks = ms.branch_timelocks().expect("one path");
+    assert_eq!(locks.len(), 1);
+    assert_eq!(locks[0].older, Some(RelativeLockTime::from_height(144)));
+    assert_eq!(locks[0].after, Some(LockTime::from_height(500).unwrap()));
+    assert!(!locks[0].conflicting);
+
+    // Too many paths to list them all: 2-of-50 has C(50, 2) = 1225
+    let keys: Vec<String> = (0..50).map(|i| format!("K{i}")).collect();
+    let ms = Miniscript::from_str(
+        &format!("multi_a(2,{})", keys.join(",")),
+        Context::Tapscript,
+    )
+    .expect("should parse");
+    assert_eq!(ms.branch_timelocks(), None);
+}
+
+#[test]
+fn test_dummy_key_map() {
+    let keys = DummyKeyMap::new();
+
+    let a = keys.pubkey_for("A", Context::Wsh);
+    let b = keys.pubkey_for("B", Context::Wsh);
+    assert_eq!(a.len(), 33);
+    assert_ne!(a, b);
+    assert!(bitcoin::PublicKey::from_slice(&a).is_ok());
+    // The Tapscript key is the x-only part of the WSH key
+    assert_eq!(keys.pubkey_for("A", Context::Tapscript), a[1..]);
+
+    // Hex keys stand for themselves
+    let hex = "02c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5";
+    assert_eq!(
+        keys.pubkey_for(hex, Context::Wsh),
+        hex::decode(hex).unwrap()
+    );
+
+    // The script encodes the same key the satisfier is asked to sign for
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).expect("should parse");
+    assert_eq!(ms.to_script_bytes().unwrap()[1..34], a[..]);
+
+    let mut satisfier = SimpleSatisfier::new();
+    satisfier.signatures.insert(a, vec![0x30; 72]);
+    let result = ms
+        .satisfy(satisfier, true)
+        .expect("satisfy should not error");
+    assert_eq!(result.availability, Availability::Yes);
+}
@@ -4,7 +4,7 @@
 //! different type rules compared to P2WSH.
 
 use super::common::init_testdata;
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, Error, Miniscript};
 
 #[test]
 fn test_tapscript_multi_a() {
@@ -169,3 +169,103 @@ fn test_tapscript_x_only_pubkeys() {
         }
     }
 }
+
+#[test]
+fn test_tapscript_multi_a_script_roundtrip() {
+    // multi_a compiles to OP_CHECKSIG/OP_CHECKSIGADD, which must decode
+    // back under the Tapscript context to the same script
+    let ms = Miniscript::from_str("multi_a(2,A,B,C)", Context::Tapscript).unwrap();
+    let script = ms.to_script_bytes().unwrap();
+
+    let decoded = Miniscript::from_script_bytes(&script, Context::Tapscript).unwrap();
+    assert_eq!(decoded.context(), Context::Tapscript);
+    assert!(decoded.is_valid());
+    assert!(decoded.to_string().unwrap().starts_with("multi_a(2,"));
+    assert_eq!(decoded.to_script_bytes().unwrap(), script);
+
+    // With real x-only keys the decoded keys are preserved
+    let keys = [
+        "d01115d548e7561b15c38f004d734633687cf4419620095bc5b0f47070afe85a",
+        "5601570cb47f238d2b0286db4a990fa0f3ba28d1a319f5e7cf55c2a2444da7cc",
+        "d30199d74fb5a22d47b6e054e2f378cedacffcb89904a61d75d0dbd407143e65",
+    ];
+    let ms_str = format!("multi_a(2,{},{},{})", keys[0], keys[1], keys[2]);
+    let ms = Miniscript::from_str(&ms_str, Context::Tapscript).unwrap();
+    let script = ms.to_script_bytes().unwrap();
+
+    let decoded = Miniscript::from_script_bytes(&script, Context::Tapscript).unwrap();
+    assert_eq!(decoded.to_string().unwrap(), ms_str);
+    assert_eq!(decoded.to_script_bytes().unwrap(), script);
+}
+
+#[test]
+fn test_script_context_mismatch() {
+    use bitcoin::opcodes::all::{OP_CHECKMULTISIG, OP_CHECKSIGADD};
+
+    // A multi_a script decoded as P2WSH reports its OP_CHECKSIGADD
+    let multi_a = Miniscript::from_str("multi_a(2,A,B,C)", Context::Tapscript).unwrap();
+    let script = multi_a.to_script_bytes().unwrap();
+    assert_eq!(
+        Miniscript::from_script_bytes(&script, Context::Wsh).unwrap_err(),
+        Error::ContextMismatch {
+            expected: Context::Wsh,
+            found_opcode: OP_CHECKSIGADD,
+        }
+    );
+
+    // And a multi script decoded as Tapscript reports its OP_CHECKMULTISIG
+    let multi = Miniscript::from_str("multi(2,A,B,C)", Context::Wsh).unwrap();
+    let script = multi.to_script_bytes().unwrap();
+    assert_eq!(
+        Miniscript::from_script_bytes(&script, Context::Tapscript).unwrap_err(),
+        Error::ContextMismatch {
+            expected: Context::Tapscript,
+            found_opcode: OP_CHECKMULTISIG,
+        }
+    );
+}
+
+#[test]
+fn test_compare_contexts_multi() {
+    // multi() is only valid in P2WSH, multi_a() only in Tapscript
+    let multi = Miniscript::compare_contexts("multi(2,A,B,C)").expect("valid in P2WSH");
+    let multi_a = Miniscript::compare_contexts("multi_a(2,A,B,C)").expect("valid in Tapscript");
+
+    assert!(multi.tapscript.is_err());
+    assert!(multi_a.wsh.is_err());
+
+    let wsh = multi.wsh.expect("P2WSH metrics");
+    let tap = multi_a.tapscript.expect("Tapscript metrics");
+
+    // Schnorr signatures are smaller than ECDSA signatures
+    let wsh_weight = wsh.max_satisfaction_weight.expect("P2WSH weight");
+    let tap_weight = tap.max_satisfaction_weight.expect("Tapscript weight");
+    assert!(tap_weight < wsh_weight, "{tap_weight} >= {wsh_weight}");
+
+    // A string valid in both contexts yields metrics for both
+    let both = Miniscript::compare_contexts("and_v(v:pk(A),pk(B))").expect("valid in both");
+    assert!(both.wsh.is_ok());
+    assert!(both.tapscript.is_ok());
+
+    assert!(Miniscript::compare_contexts("pk(A").is_err());
+}
+
+#[test]
+fn test_labeled_string_roundtrip() {
+    let wsh = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    assert_eq!(wsh.labeled_string(), "wsh:pk(A)");
+
+    let tap = Miniscript::from_str("multi_a(2,A,B,C)", Context::Tapscript).unwrap();
+    assert_eq!(tap.labeled_string(), "tap:multi_a(2,A,B,C)");
+
+    for ms in [wsh, tap] {
+        let parsed = Miniscript::from_labeled_string(&ms.labeled_string()).unwrap();
+        assert_eq!(parsed.context(), ms.context());
+        assert_eq!(parsed.to_string(), ms.to_string());
+    }
+
+    // multi_a is not valid under P2WSH
+    assert!(Miniscript::from_labeled_string("wsh:multi_a(2,A,B,C)").is_err());
+    assert!(Miniscript::from_labeled_string("pk(A)").is_err());
+    assert!(Miniscript::from_labeled_string("tr:pk(A)").is_err());
+}
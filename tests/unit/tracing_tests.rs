@@ -0,0 +1,81 @@
+//! Tests for the spans emitted with the `tracing` feature
+
+use miniscript_core_ffi::{Context, Miniscript};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// A recorded span: its name and its fields rendered as `name=value`
+type SpanRecord = (&'static str, Vec<String>);
+
+/// A subscriber that records every span it sees
+#[derive(Default, Clone)]
+struct Recorder {
+    spans: Arc<Mutex<Vec<SpanRecord>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut Vec<String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push(format!("{}={value:?}", field.name()));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut fields = Vec::new();
+        span.record(&mut FieldVisitor(&mut fields));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push((span.metadata().name(), fields));
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut spans = self.spans.lock().unwrap();
+        let index = usize::try_from(span.into_u64()).unwrap() - 1;
+        values.record(&mut FieldVisitor(&mut spans[index].1));
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn test_from_str_span() {
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+        Miniscript::from_str("pk(A", Context::Tapscript).unwrap_err();
+    });
+
+    let spans = recorder.spans.lock().unwrap();
+    let parses: Vec<&SpanRecord> = spans
+        .iter()
+        .filter(|(name, _)| *name == "Miniscript::from_str")
+        .collect();
+    assert_eq!(parses.len(), 2, "one span per parse: {spans:?}");
+
+    let (_, fields) = parses[0];
+    assert!(fields.contains(&"input_len=5".to_string()), "{fields:?}");
+    assert!(fields.contains(&"context=wsh".to_string()), "{fields:?}");
+    assert!(fields.contains(&"success=true".to_string()), "{fields:?}");
+
+    let (_, fields) = parses[1];
+    assert!(
+        fields.contains(&"context=tapscript".to_string()),
+        "{fields:?}"
+    );
+    assert!(fields.contains(&"success=false".to_string()), "{fields:?}");
+}
@@ -4,7 +4,7 @@
 //! various fragment combinations according to Bitcoin Core's rules.
 
 use super::common::init_testdata;
-use miniscript_core_ffi::{Context, Miniscript};
+use miniscript_core_ffi::{Context, ErrorCode, Miniscript, Warning};
 
 #[test]
 fn test_older_validity() {
@@ -407,3 +407,126 @@ fn test_numbers_no_sign_prefix() {
     );
     assert!(ms.is_err(), "multi(+1,...) should be invalid");
 }
+
+#[test]
+fn test_parse_checked_warnings() {
+    let (ms, warnings) = Miniscript::parse_checked("pk(A)", Context::Wsh).expect("should parse");
+    assert!(ms.is_sane());
+    assert!(warnings.is_empty(), "pk(A) should have no warnings");
+
+    let (ms, warnings) =
+        Miniscript::parse_checked("after(100)", Context::Wsh).expect("should parse");
+    assert!(!ms.is_sane());
+    assert!(warnings.contains(&Warning::NoSignatureRequired));
+
+    let (_, warnings) =
+        Miniscript::parse_checked("and_b(after(100),a:after(1000000000))", Context::Wsh)
+            .expect("should parse");
+    assert!(warnings.contains(&Warning::TimelockMix));
+
+    assert!(Miniscript::parse_checked("invalid", Context::Wsh).is_err());
+}
+
+#[test]
+fn test_error_codes() {
+    // Unbalanced parentheses: syntax error
+    let err = Miniscript::from_str("pk(A", Context::Wsh).unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::Syntax));
+
+    // and_v requires a V-typed first argument: type error
+    let err = Miniscript::from_str("and_v(pk(A),pk(B))", Context::Wsh).unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::Type));
+
+    // Errors raised before reaching the C wrapper carry no code
+    let err = Miniscript::from_str("pk(A\0)", Context::Wsh).unwrap_err();
+    assert_eq!(err.code(), None);
+}
+
+#[test]
+fn test_sanity_failures() {
+    init_testdata();
+
+    let sanity = |input: &str| {
+        Miniscript::from_str(input, Context::Wsh)
+            .expect("should parse")
+            .sanity()
+    };
+
+    assert_eq!(sanity("and_v(v:pk(A),pk(B))"), Ok(()));
+
+    // A third party can switch between the two timelock branches
+    assert_eq!(
+        sanity("and_v(v:pk(A),or_i(older(1),older(2)))"),
+        Err(Warning::Malleable)
+    );
+    assert_eq!(sanity("and_v(v:pk(A),pk(A))"), Err(Warning::DuplicateKey));
+    assert_eq!(
+        sanity("and_v(v:pk(A),and_v(v:older(10),older(4194305)))"),
+        Err(Warning::TimelockMix)
+    );
+    assert_eq!(sanity("older(144)"), Err(Warning::NoSignatureRequired));
+    assert_eq!(sanity("v:pk(A)"), Err(Warning::NotTopLevel));
+}
+
+#[test]
+fn test_from_str_permissive() {
+    init_testdata();
+
+    // t: needs a V-type argument and or_c(0,1) is not even valid itself
+    assert!(Miniscript::from_str("t:or_c(0,1)", Context::Wsh).is_err());
+    let ms = Miniscript::from_str_permissive("t:or_c(0,1)", Context::Wsh)
+        .expect("should parse permissively");
+    assert!(!ms.is_valid());
+    assert_eq!(ms.to_string().as_deref(), Some("t:or_c(0,1)"));
+    // and_v(or_c(0,1),1): and_v, or_c, 0, 1 and 1
+    assert_eq!(ms.subexpressions().len(), 5);
+
+    // Valid input parses the same as from_str
+    let ms = Miniscript::from_str_permissive("pk(A)", Context::Wsh).expect("should parse");
+    assert!(ms.is_valid());
+
+    // Syntax errors are still errors
+    let err = Miniscript::from_str_permissive("t:or_c(0,1", Context::Wsh).unwrap_err();
+    assert_eq!(err.code(), Some(ErrorCode::Syntax));
+}
+
+#[test]
+fn test_type_explain() {
+    init_testdata();
+
+    let ms = Miniscript::from_str("pk(A)", Context::Wsh).unwrap();
+    let typ = ms.typ().expect("pk(A) should have a type");
+    assert_eq!(Some(typ.as_str()), ms.get_type().as_deref());
+
+    let explanation = typ.explain();
+    assert!(
+        explanation
+            .iter()
+            .any(|(flag, meaning)| *flag == 'B' && meaning.starts_with("Base expression")),
+        "{explanation:?}"
+    );
+    assert!(
+        !explanation
+            .iter()
+            .any(|(_, meaning)| meaning.starts_with("Verify expression")),
+        "{explanation:?}"
+    );
+    // Only set flags are explained
+    assert!(explanation.iter().all(|(flag, _)| typ.has(*flag)));
+    assert_eq!(explanation.len(), typ.as_str().len());
+
+    // k marks the absence of a heightlock/timelock mix, not a timelock
+    assert!(typ.has('k'));
+    assert!(
+        explanation.contains(&(
+            'k',
+            "No timelock mix property (does not combine height and time locks)"
+        )),
+        "{explanation:?}"
+    );
+    let mixed = Miniscript::from_str("and_b(after(100),a:after(1000000000))", Context::Wsh)
+        .unwrap()
+        .typ()
+        .expect("should have a type");
+    assert!(!mixed.has('k'));
+}